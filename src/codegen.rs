@@ -3,9 +3,10 @@ use std::io::Write;
 use std::io;
 
 use crate::JsonValue;
-use crate::number::Number;
+use crate::number::{ Number, Decimal };
 use crate::object::Object;
 use crate::util::print_dec;
+use crate::parser::{ PRESERVED, SURROGATE_MARK };
 
 const QU: u8 = b'"';
 const BS: u8 = b'\\';
@@ -65,6 +66,11 @@ pub trait Generator {
     #[inline(always)]
     fn dedent(&mut self) {}
 
+    // Whether the characters that `LoneSurrogates::Preserve` put surrogates
+    // in are written out as escapes of those surrogates.
+    #[inline(always)]
+    fn escapes_lone_surrogates(&self) -> bool { false }
+
     #[inline(never)]
     fn write_string_complex(&mut self, string: &str, mut start: usize) -> io::Result<()> {
         self.write(&string.as_bytes()[ .. start])?;
@@ -79,6 +85,18 @@ pub trait Generator {
             if escape == b'u' {
                 write!(self.get_writer(), "{:04x}", ch)?;
             }
+            // Only after the mark, other private use characters are fine
+            if ch == 0xEF && self.escapes_lone_surrogates() {
+                let mut chars = string[index ..].chars();
+
+                if chars.next() == Some(SURROGATE_MARK) {
+                    if let Some(code) = chars.next().map(u32::from).filter(|&code| code >= PRESERVED) {
+                        self.write(&string.as_bytes()[start .. index])?;
+                        write!(self.get_writer(), "\\u{:04x}", code - PRESERVED + 0xD800)?;
+                        start = index + SURROGATE_MARK.len_utf8() + 4;
+                    }
+                }
+            }
         }
         self.write(&string.as_bytes()[start ..])?;
 
@@ -90,7 +108,7 @@ pub trait Generator {
         self.write_char(b'"')?;
 
         for (index, ch) in string.bytes().enumerate() {
-            if ESCAPED[ch as usize] > 0 {
+            if ESCAPED[ch as usize] > 0 || (ch == 0xEF && self.escapes_lone_surrogates()) {
                 return self.write_string_complex(string, index)
             }
         }
@@ -115,6 +133,11 @@ pub trait Generator {
         }
     }
 
+    #[inline(always)]
+    fn write_decimal(&mut self, decimal: &Decimal) -> io::Result<()> {
+        self.write(decimal.as_str().as_bytes())
+    }
+
     #[inline(always)]
     fn write_object(&mut self, object: &Object) -> io::Result<()> {
         self.write_char(b'{')?;
@@ -146,13 +169,14 @@ pub trait Generator {
 
     fn write_json(&mut self, json: &JsonValue) -> io::Result<()> {
         match *json {
-            JsonValue::Null               => self.write(b"null"),
-            JsonValue::Short(ref short)   => self.write_string(short.as_str()),
-            JsonValue::String(ref string) => self.write_string(string),
-            JsonValue::Number(ref number) => self.write_number(number),
-            JsonValue::Boolean(true)      => self.write(b"true"),
-            JsonValue::Boolean(false)     => self.write(b"false"),
-            JsonValue::Array(ref array)   => {
+            JsonValue::Null                 => self.write(b"null"),
+            JsonValue::Short(ref short)     => self.write_string(short.as_str()),
+            JsonValue::String(ref string)   => self.write_string(string),
+            JsonValue::Number(ref number)   => self.write_number(number),
+            JsonValue::Decimal(ref decimal) => self.write_decimal(decimal),
+            JsonValue::Boolean(true)        => self.write(b"true"),
+            JsonValue::Boolean(false)       => self.write(b"false"),
+            JsonValue::Array(ref array)     => {
                 self.write_char(b'[')?;
                 let mut iter = array.iter();
 
@@ -175,7 +199,7 @@ pub trait Generator {
                 self.new_line()?;
                 self.write_char(b']')
             },
-            JsonValue::Object(ref object) => {
+            JsonValue::Object(ref object)   => {
                 self.write_object(object)
             }
         }
@@ -185,15 +209,25 @@ pub trait Generator {
 /// In-Memory Generator, this uses a Vec to store the JSON result.
 pub struct DumpGenerator {
     code: Vec<u8>,
+    lone_surrogates: bool,
 }
 
 impl DumpGenerator {
     pub fn new() -> Self {
         DumpGenerator {
             code: Vec::with_capacity(1024),
+            lone_surrogates: false,
         }
     }
 
+    /// Write the lone surrogates that `LoneSurrogates::Preserve` keeps in
+    /// strings back out as `\u` escapes. Those are characters from U+10F800
+    /// to U+10FFFF right after a U+FFFF, nothing else is touched.
+    pub fn escape_lone_surrogates(mut self, escape: bool) -> Self {
+        self.lone_surrogates = escape;
+        self
+    }
+
     pub fn consume(self) -> String {
         // Original strings were unicode, numbers are all ASCII,
         // therefore this is safe.
@@ -225,6 +259,11 @@ impl Generator for DumpGenerator {
         self.code.push(min);
         Ok(())
     }
+
+    #[inline(always)]
+    fn escapes_lone_surrogates(&self) -> bool {
+        self.lone_surrogates
+    }
 }
 
 /// Pretty In-Memory Generator, this uses a Vec to store the JSON result and add indent.
@@ -232,6 +271,7 @@ pub struct PrettyGenerator {
     code: Vec<u8>,
     dent: u16,
     spaces_per_indent: u16,
+    lone_surrogates: bool,
 }
 
 impl PrettyGenerator {
@@ -239,10 +279,19 @@ impl PrettyGenerator {
         PrettyGenerator {
             code: Vec::with_capacity(1024),
             dent: 0,
-            spaces_per_indent: spaces
+            spaces_per_indent: spaces,
+            lone_surrogates: false,
         }
     }
 
+    /// Write the lone surrogates that `LoneSurrogates::Preserve` keeps in
+    /// strings back out as `\u` escapes. Those are characters from U+10F800
+    /// to U+10FFFF right after a U+FFFF, nothing else is touched.
+    pub fn escape_lone_surrogates(mut self, escape: bool) -> Self {
+        self.lone_surrogates = escape;
+        self
+    }
+
     pub fn consume(self) -> String {
         unsafe { String::from_utf8_unchecked(self.code) }
     }
@@ -274,6 +323,11 @@ impl Generator for PrettyGenerator {
         Ok(())
     }
 
+    #[inline(always)]
+    fn escapes_lone_surrogates(&self) -> bool {
+        self.lone_surrogates
+    }
+
     fn new_line(&mut self) -> io::Result<()> {
         self.code.push(b'\n');
         for _ in 0..(self.dent * self.spaces_per_indent) {
@@ -293,15 +347,25 @@ impl Generator for PrettyGenerator {
 
 /// Writer Generator, this uses a custom writer to store the JSON result.
 pub struct WriterGenerator<'a, W: 'a + Write> {
-    writer: &'a mut W
+    writer: &'a mut W,
+    lone_surrogates: bool,
 }
 
 impl<'a, W> WriterGenerator<'a, W> where W: 'a + Write {
     pub fn new(writer: &'a mut W) -> Self {
         WriterGenerator {
-            writer: writer
+            writer: writer,
+            lone_surrogates: false,
         }
     }
+
+    /// Write the lone surrogates that `LoneSurrogates::Preserve` keeps in
+    /// strings back out as `\u` escapes. Those are characters from U+10F800
+    /// to U+10FFFF right after a U+FFFF, nothing else is touched.
+    pub fn escape_lone_surrogates(mut self, escape: bool) -> Self {
+        self.lone_surrogates = escape;
+        self
+    }
 }
 
 impl<'a, W> Generator for WriterGenerator<'a, W> where W: Write {
@@ -316,6 +380,11 @@ impl<'a, W> Generator for WriterGenerator<'a, W> where W: Write {
     fn write_min(&mut self, _: &[u8], min: u8) -> io::Result<()> {
         self.writer.write_all(&[min])
     }
+
+    #[inline(always)]
+    fn escapes_lone_surrogates(&self) -> bool {
+        self.lone_surrogates
+    }
 }
 
 /// Pretty Writer Generator, this uses a custom writer to store the JSON result and add indent.
@@ -323,6 +392,7 @@ pub struct PrettyWriterGenerator<'a, W: 'a + Write> {
     writer: &'a mut W,
     dent: u16,
     spaces_per_indent: u16,
+    lone_surrogates: bool,
 }
 
 impl<'a, W> PrettyWriterGenerator<'a, W> where W: 'a + Write {
@@ -331,8 +401,17 @@ impl<'a, W> PrettyWriterGenerator<'a, W> where W: 'a + Write {
             writer: writer,
             dent: 0,
             spaces_per_indent: spaces,
+            lone_surrogates: false,
         }
     }
+
+    /// Write the lone surrogates that `LoneSurrogates::Preserve` keeps in
+    /// strings back out as `\u` escapes. Those are characters from U+10F800
+    /// to U+10FFFF right after a U+FFFF, nothing else is touched.
+    pub fn escape_lone_surrogates(mut self, escape: bool) -> Self {
+        self.lone_surrogates = escape;
+        self
+    }
 }
 
 impl<'a, W> Generator for PrettyWriterGenerator<'a, W> where W: Write {
@@ -348,6 +427,11 @@ impl<'a, W> Generator for PrettyWriterGenerator<'a, W> where W: Write {
         self.writer.write_all(slice)
     }
 
+    #[inline(always)]
+    fn escapes_lone_surrogates(&self) -> bool {
+        self.lone_surrogates
+    }
+
     fn new_line(&mut self) -> io::Result<()> {
         self.write_char(b'\n')?;
         for _ in 0..(self.dent * self.spaces_per_indent) {
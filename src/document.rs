@@ -0,0 +1,505 @@
+// Documents parsed into an arena, see `json::parse_document`.
+//
+// A `JsonValue` tree is made of lots of small allocations: every array,
+// every object store, every string and every key that doesn't fit inline.
+// A `Document` keeps the whole tree in two buffers instead, one for the
+// nodes and one for the bytes of strings, keys and lossless numbers, so
+// parsing is cheaper, and so is dropping the whole thing when done.
+
+use std::convert::TryInto;
+use std::ops::{ Index, Deref };
+use std::{ fmt, str, slice };
+
+use crate::number::{ Number, Decimal };
+use crate::object::Object;
+use crate::JsonValue;
+
+static NULL: Node = Node(Kind::Null);
+
+// Same as the ones for `JsonValue`
+macro_rules! number_to_unsigned {
+    ($unsigned:ident, $value:expr, $high:ty) => {
+        if $value > $unsigned::MAX as $high {
+            None
+        } else {
+            Some($value as $unsigned)
+        }
+    }
+}
+
+macro_rules! number_to_signed {
+    ($signed:ident, $value:expr, $high:ty) => {
+        if $value < $signed::MIN as $high || $value > $signed::MAX as $high {
+            None
+        } else {
+            Some($value as $signed)
+        }
+    }
+}
+
+/// A parsed document, with all of its values in a single arena.
+///
+/// The document dereferences to its root `Node`, which has the same read
+/// API as `JsonValue`: indexing, `members`, `entries` and the `as_*`
+/// methods. Nodes can only be borrowed from the document, so nothing in it
+/// can outlive it.
+///
+/// ```
+/// let document = json::parse_document(r#"
+///     {
+///         "name": "json",
+///         "versions": [0.11, 0.12]
+///     }
+/// "#).unwrap();
+///
+/// assert_eq!(document["name"].as_str(), Some("json"));
+/// assert_eq!(document["versions"][1].as_f64(), Some(0.12));
+/// assert_eq!(document["versions"].members().count(), 2);
+///
+/// for (key, value) in document.entries() {
+///     assert!(key == "name" || value.is_array());
+/// }
+/// ```
+pub struct Document {
+    root: Node,
+
+    // Every other node, members of an array and entries of an object (a key
+    // followed by its value) are next to one another
+    nodes: Vec<Node>,
+
+    // Bytes of strings, keys and lossless numbers
+    strings: Vec<u8>,
+}
+
+// Nodes only ever point into the buffers owned by the document, which are
+// never changed once it's built.
+unsafe impl Send for Document {}
+
+/// A single value in a `Document`.
+///
+/// Arrays and objects are flat lists of nodes, finding a key in an object
+/// goes through its entries one by one. Those are in the order their keys
+/// first appeared in, duplicates are dealt with the same way they are when
+/// parsing a `JsonValue`.
+pub struct Node(Kind);
+
+unsafe impl Sync for Node {}
+
+enum Kind {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    Decimal(Span<u8>),
+    String(Span<u8>),
+    Array(Span<Node>),
+
+    // Two nodes per entry, the key is always a `String`
+    Object(Span<Node>),
+}
+
+// A run of items in one of the buffers of a `Document`. While the document
+// is being built, and the buffers can still move around, the pointer holds
+// the offset of the first item instead.
+struct Span<T> {
+    ptr: *const T,
+    len: usize,
+}
+
+impl<T> Span<T> {
+    fn at(offset: usize, len: usize) -> Self {
+        Span {
+            ptr: offset as *const T,
+            len,
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.ptr as usize
+    }
+
+    // Turn the offset into a pointer into the `buffer`.
+    fn locate(&mut self, buffer: *const T) {
+        self.ptr = buffer.wrapping_add(self.offset());
+    }
+
+    fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl Span<u8> {
+    fn as_str(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(self.as_slice()) }
+    }
+}
+
+/// Iterator over members of an array `Node`.
+pub type Members<'d> = slice::Iter<'d, Node>;
+
+/// Iterator over key value pairs of an object `Node`.
+pub struct Entries<'d> {
+    nodes: slice::ChunksExact<'d, Node>,
+}
+
+impl<'d> Entries<'d> {
+    fn new(nodes: &'d [Node]) -> Self {
+        Entries {
+            nodes: nodes.chunks_exact(2),
+        }
+    }
+}
+
+impl<'d> Iterator for Entries<'d> {
+    type Item = (&'d str, &'d Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<'d> DoubleEndedIterator for Entries<'d> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.nodes.next_back().map(entry)
+    }
+}
+
+impl<'d> ExactSizeIterator for Entries<'d> {}
+
+fn entry(pair: &[Node]) -> (&str, &Node) {
+    (pair[0].as_str().unwrap_or(""), &pair[1])
+}
+
+impl Node {
+    pub(crate) fn null() -> Self {
+        Node(Kind::Null)
+    }
+
+    pub(crate) fn boolean(value: bool) -> Self {
+        Node(Kind::Boolean(value))
+    }
+
+    pub(crate) fn number(value: Number) -> Self {
+        Node(Kind::Number(value))
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self.0, Kind::String(_))
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self.0, Kind::Number(_) | Kind::Decimal(_))
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        matches!(self.0, Kind::Boolean(_))
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.0, Kind::Null)
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self.0, Kind::Object(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self.0, Kind::Array(_))
+    }
+
+    /// Checks whether the value is empty, same as `JsonValue::is_empty`.
+    pub fn is_empty(&self) -> bool {
+        match self.0 {
+            Kind::Null              => true,
+            Kind::Boolean(value)    => !value,
+            Kind::Number(ref value) => value.is_empty(),
+            Kind::Decimal(_)        => self.as_number().map(|value| value.is_empty()).unwrap_or(true),
+            Kind::String(ref span)  => span.len == 0,
+            Kind::Array(ref span)   => span.len == 0,
+            Kind::Object(ref span)  => span.len == 0,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self.0 {
+            Kind::String(ref span) => Some(span.as_str()),
+            _                      => None,
+        }
+    }
+
+    /// Get the number, `Decimal` values are rounded to the closest `Number`.
+    pub fn as_number(&self) -> Option<Number> {
+        match self.0 {
+            Kind::Number(value)     => Some(value),
+            Kind::Decimal(ref span) => crate::parser::parse_number(span.as_str()).ok(),
+            _                       => None,
+        }
+    }
+
+    /// Get the number with all of its digits, only available when parsed
+    /// with `lossless_numbers` set on `ParserOptions`.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self.0 {
+            Kind::Decimal(ref span) => Some(Decimal::from_valid(span.as_str().to_owned())),
+            _                       => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(|value| value.into())
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_number().map(|value| value.into())
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(|value| value.try_into().ok())
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_u64().and_then(|value| number_to_unsigned!(u32, value, u64))
+    }
+
+    pub fn as_u16(&self) -> Option<u16> {
+        self.as_u64().and_then(|value| number_to_unsigned!(u16, value, u64))
+    }
+
+    pub fn as_u8(&self) -> Option<u8> {
+        self.as_u64().and_then(|value| number_to_unsigned!(u8, value, u64))
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_u64().and_then(|value| number_to_unsigned!(usize, value, u64))
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(|value| value.try_into().ok())
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_i64().and_then(|value| number_to_signed!(i32, value, i64))
+    }
+
+    pub fn as_i16(&self) -> Option<i16> {
+        self.as_i64().and_then(|value| number_to_signed!(i16, value, i64))
+    }
+
+    pub fn as_i8(&self) -> Option<i8> {
+        self.as_i64().and_then(|value| number_to_signed!(i8, value, i64))
+    }
+
+    pub fn as_isize(&self) -> Option<isize> {
+        self.as_i64().and_then(|value| number_to_signed!(isize, value, i64))
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.0 {
+            Kind::Boolean(value) => Some(value),
+            _                    => None,
+        }
+    }
+
+    /// Checks that self is an object and that it has an entry with the `key`.
+    pub fn has_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns length of array or object (number of keys), defaults to `0` for
+    /// other types.
+    pub fn len(&self) -> usize {
+        match self.0 {
+            Kind::Array(ref span)  => span.len,
+            Kind::Object(ref span) => span.len / 2,
+            _                      => 0,
+        }
+    }
+
+    /// Works on arrays - returns an iterator over members. Will return an
+    /// empty iterator if called on non-array types.
+    pub fn members(&self) -> Members<'_> {
+        match self.0 {
+            Kind::Array(ref span) => span.as_slice().iter(),
+            _                     => [].iter(),
+        }
+    }
+
+    /// Works on objects - returns an iterator over key value pairs. Will
+    /// return an empty iterator if called on non-object types.
+    pub fn entries(&self) -> Entries<'_> {
+        match self.0 {
+            Kind::Object(ref span) => Entries::new(span.as_slice()),
+            _                      => Entries::new(&[]),
+        }
+    }
+
+    /// Copy the node, and everything in it, out of the document.
+    pub fn to_json_value(&self) -> JsonValue {
+        match self.0 {
+            Kind::Null              => JsonValue::Null,
+            Kind::Boolean(value)    => JsonValue::Boolean(value),
+            Kind::Number(value)     => JsonValue::Number(value),
+            Kind::Decimal(ref span) => JsonValue::Decimal(Decimal::from_valid(span.as_str().to_owned())),
+            Kind::String(ref span)  => span.as_str().into(),
+            Kind::Array(_)          => {
+                JsonValue::Array(self.members().map(Node::to_json_value).collect())
+            },
+            Kind::Object(_)         => {
+                let mut object = Object::with_capacity(self.len());
+
+                for (key, value) in self.entries() {
+                    object.insert(key, value.to_json_value());
+                }
+
+                JsonValue::Object(object)
+            },
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Node> {
+        self.entries().find(|entry| entry.0 == key).map(|entry| entry.1)
+    }
+}
+
+impl fmt::Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Kind::Null              => f.write_str("null"),
+            Kind::Boolean(value)    => fmt::Debug::fmt(&value, f),
+            Kind::Number(value)     => fmt::Display::fmt(&value, f),
+            Kind::Decimal(ref span) => f.write_str(span.as_str()),
+            Kind::String(ref span)  => fmt::Debug::fmt(span.as_str(), f),
+            Kind::Array(_)          => f.debug_list().entries(self.members()).finish(),
+            Kind::Object(_)         => f.debug_map().entries(self.entries()).finish(),
+        }
+    }
+}
+
+/// Members of arrays can be read by their index, anything out of bounds
+/// is `null`.
+impl Index<usize> for Node {
+    type Output = Node;
+
+    fn index(&self, index: usize) -> &Node {
+        self.members().nth(index).unwrap_or(&NULL)
+    }
+}
+
+/// Values of object entries can be read by their key, anything missing is
+/// `null`.
+impl Index<&str> for Node {
+    type Output = Node;
+
+    fn index(&self, key: &str) -> &Node {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Deref for Document {
+    type Target = Node;
+
+    fn deref(&self) -> &Node {
+        &self.root
+    }
+}
+
+impl fmt::Debug for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.root, f)
+    }
+}
+
+// Puts a `Document` together while it's being parsed. Nodes for arrays and
+// objects are only made once all of their contents are known, at which
+// point those are moved into the arena in one go.
+pub(crate) struct Builder {
+    nodes: Vec<Node>,
+    strings: Vec<u8>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Builder {
+            nodes: Vec::new(),
+            strings: Vec::new(),
+        }
+    }
+
+    pub fn string(&mut self, value: &str) -> Node {
+        Node(Kind::String(self.push_str(value)))
+    }
+
+    pub fn decimal(&mut self, value: &str) -> Node {
+        Node(Kind::Decimal(self.push_str(value)))
+    }
+
+    // Make an array out of the `members` past `start`.
+    pub fn array(&mut self, members: &mut Vec<Node>, start: usize) -> Node {
+        Node(Kind::Array(self.push_nodes(members, start)))
+    }
+
+    // Make an object out of the `entries` past `start`, each a key made by
+    // `string` followed by its value.
+    pub fn object(&mut self, entries: &mut Vec<Node>, start: usize) -> Node {
+        Node(Kind::Object(self.push_nodes(entries, start)))
+    }
+
+    // Get the string of a node made by `string`.
+    pub fn str_of(&self, node: &Node) -> &str {
+        match node.0 {
+            Kind::String(ref span) => {
+                let start = span.offset();
+
+                unsafe { str::from_utf8_unchecked(&self.strings[start..start + span.len]) }
+            },
+            _ => "",
+        }
+    }
+
+    pub fn finish(self, root: Node) -> Document {
+        let mut document = Document {
+            root,
+            nodes: self.nodes,
+            strings: self.strings,
+        };
+
+        // Nothing moves from now on, offsets can be turned into pointers
+        let nodes = document.nodes.as_mut_ptr();
+        let strings = document.strings.as_ptr();
+
+        locate(&mut document.root, nodes, strings);
+
+        for index in 0..document.nodes.len() {
+            locate(unsafe { &mut *nodes.add(index) }, nodes, strings);
+        }
+
+        document
+    }
+
+    fn push_str(&mut self, value: &str) -> Span<u8> {
+        let span = Span::at(self.strings.len(), value.len());
+
+        self.strings.extend_from_slice(value.as_bytes());
+        span
+    }
+
+    fn push_nodes(&mut self, nodes: &mut Vec<Node>, start: usize) -> Span<Node> {
+        let span = Span::at(self.nodes.len(), nodes.len() - start);
+
+        self.nodes.extend(nodes.drain(start..));
+        span
+    }
+}
+
+fn locate(node: &mut Node, nodes: *const Node, strings: *const u8) {
+    match node.0 {
+        Kind::Decimal(ref mut span) |
+        Kind::String(ref mut span)  => span.locate(strings),
+        Kind::Array(ref mut span)   |
+        Kind::Object(ref mut span)  => span.locate(nodes),
+        _                           => {},
+    }
+}
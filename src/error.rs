@@ -1,27 +1,183 @@
-use std::{ char, error, fmt };
+use std::{ char, error, fmt, io };
+
+use crate::Encoding;
 
 /// Error type of this crate.
 ///
 ///
 /// *Note:* Since `0.9.0` using `JsonError` is deprecated. Always use
 /// `json::Error` instead!
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Error {
     UnexpectedCharacter {
         ch: char,
         line: usize,
         column: usize,
+        offset: usize,
+        expected: Expected,
     },
     UnexpectedEndOfJson,
     ExceededDepthLimit,
+    ExceededSizeLimit,
+    ExceededStringLengthLimit,
+    ExceededObjectEntriesLimit,
+    ExceededArrayLengthLimit,
     FailedUtf8Parsing,
+    InvalidUtf8 {
+        offset: usize,
+        line: usize,
+        column: usize,
+    },
+    DuplicateKey {
+        key: String,
+        line: usize,
+        column: usize,
+    },
+    LoneSurrogate {
+        codepoint: u16,
+        offset: usize,
+        line: usize,
+        column: usize,
+    },
+    InvalidEncoding {
+        encoding: Encoding,
+        offset: usize,
+    },
     WrongType(String),
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+/// What the parser was looking for when it ran into an unexpected character.
+/// Displays as a short description, such as ``"`,` or `}`"``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    /// Any JSON value.
+    Value,
+    /// The rest of `true`, `false` or `null`.
+    Literal(&'static str),
+    /// Next element of an array, or its end.
+    CommaOrEndOfArray,
+    /// Next entry of an object, or its end.
+    CommaOrEndOfObject,
+    /// Key of an object entry.
+    Key,
+    /// Key of the first object entry, or the end of an empty object.
+    KeyOrEndOfObject,
+    /// Colon between a key and its value.
+    Colon,
+    /// Digit of a number.
+    Digit,
+    /// Digit of a `\u` escape or a hexadecimal number.
+    HexDigit,
+    /// Character following a backslash in a string.
+    Escape,
+    /// Low surrogate escape following a high surrogate one.
+    LowSurrogate,
+    /// Anything but a control character in a string, those need escaping.
+    StringCharacter,
+    /// Second character of a comment, after the `/`.
+    Comment,
+    /// Nothing but whitespace after the value.
+    EndOfJson,
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Expected::*;
+
+        match *self {
+            Value              => f.write_str("a value"),
+            Literal(literal)   => write!(f, "`{}`", literal),
+            CommaOrEndOfArray  => f.write_str("`,` or `]`"),
+            CommaOrEndOfObject => f.write_str("`,` or `}`"),
+            Key                => f.write_str("object key"),
+            KeyOrEndOfObject   => f.write_str("object key or `}`"),
+            Colon              => f.write_str("`:`"),
+            Digit              => f.write_str("a digit"),
+            HexDigit           => f.write_str("a hexadecimal digit"),
+            Escape             => f.write_str("an escape sequence"),
+            LowSurrogate       => f.write_str("a low surrogate `\\u` escape"),
+            StringCharacter    => f.write_str("an escaped control character"),
+            Comment            => f.write_str("`/` or `*`"),
+            EndOfJson          => f.write_str("end of JSON"),
+        }
+    }
 }
 
 impl Error {
     pub fn wrong_type(expected: &str) -> Self {
         Error::WrongType(expected.into())
     }
+
+    /// Render the error for CLI or log output, followed by the line of the
+    /// `source` it points at, with a caret under the offending character.
+    /// Running out of input points past the end of the source. Errors that
+    /// don't point anywhere are rendered on their own.
+    ///
+    /// ```
+    /// let source = "{\n    \"foo\": bar\n}";
+    /// let error = json::parse(source).unwrap_err();
+    ///
+    /// assert_eq!(error.render(source), [
+    ///     "Unexpected character: b at (2:12), expected a value",
+    ///     "  |",
+    ///     "2 |     \"foo\": bar",
+    ///     "  |            ^",
+    /// ].join("\n"));
+    /// ```
+    pub fn render<S: AsRef<[u8]>>(&self, source: S) -> String {
+        let source = source.as_ref();
+        let message = self.to_string();
+
+        let (line, column) = match *self {
+            Error::UnexpectedCharacter { line, column, .. } |
+            Error::InvalidUtf8 { line, column, .. } |
+            Error::DuplicateKey { line, column, .. } |
+            Error::LoneSurrogate { line, column, .. } => (line, column),
+            Error::UnexpectedEndOfJson => {
+                let last = source.split(|&byte| byte == b'\n').count();
+                let last = match source.ends_with(b"\n") {
+                    true if last > 1 => last - 1,
+                    _                => last,
+                };
+
+                (last, usize::MAX)
+            },
+            _ => return message,
+        };
+
+        let text = match source.split(|&byte| byte == b'\n').nth(line.wrapping_sub(1)) {
+            Some(text) => String::from_utf8_lossy(text),
+            None       => return message,
+        };
+        let text = text.strip_suffix('\r').unwrap_or(&text);
+
+        // Keep tabs so the caret lines up with what's above it
+        let mut marker: String = text
+            .chars()
+            .take(column.saturating_sub(1))
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        marker.push('^');
+
+        let number = line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        format!("{}\n{} |\n{} | {}\n{} | {}", message, gutter, number, text, gutter, marker)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -33,12 +189,44 @@ impl fmt::Display for Error {
                 ref ch,
                 ref line,
                 ref column,
-            } => write!(f, "Unexpected character: {} at ({}:{})", ch, line, column),
+                ref expected,
+                ..
+            } => write!(f, "Unexpected character: {} at ({}:{}), expected {}", ch, line, column, expected),
+
+            UnexpectedEndOfJson        => write!(f, "Unexpected end of JSON"),
+            ExceededDepthLimit         => write!(f, "Exceeded depth limit"),
+            ExceededSizeLimit          => write!(f, "Exceeded size limit"),
+            ExceededStringLengthLimit  => write!(f, "Exceeded string length limit"),
+            ExceededObjectEntriesLimit => write!(f, "Exceeded object entries limit"),
+            ExceededArrayLengthLimit   => write!(f, "Exceeded array length limit"),
+            FailedUtf8Parsing          => write!(f, "Failed to parse UTF-8 bytes"),
+
+            InvalidUtf8 {
+                ref offset,
+                ref line,
+                ref column,
+            } => write!(f, "Invalid UTF-8 byte at offset {} ({}:{})", offset, line, column),
+
+            DuplicateKey {
+                ref key,
+                ref line,
+                ref column,
+            } => write!(f, "Duplicate key: {:?} at ({}:{})", key, line, column),
+
+            LoneSurrogate {
+                ref codepoint,
+                ref line,
+                ref column,
+                ..
+            } => write!(f, "Lone surrogate: \\u{:04X} at ({}:{})", codepoint, line, column),
+
+            InvalidEncoding {
+                ref encoding,
+                ref offset,
+            } => write!(f, "Invalid {} at offset {}", encoding, offset),
 
-            UnexpectedEndOfJson   => write!(f, "Unexpected end of JSON"),
-            ExceededDepthLimit    => write!(f, "Exceeded depth limit"),
-            FailedUtf8Parsing     => write!(f, "Failed to parse UTF-8 bytes"),
-            WrongType(ref s)      => write!(f, "Wrong type, expected: {}", s),
+            WrongType(ref s)           => write!(f, "Wrong type, expected: {}", s),
+            Io { ref message, .. }     => write!(f, "I/O error: {}", message),
         }
     }
 }
@@ -51,8 +239,17 @@ impl error::Error for Error {
             UnexpectedCharacter { .. } => "Unexpected character",
             UnexpectedEndOfJson        => "Unexpected end of JSON",
             ExceededDepthLimit         => "Exceeded depth limit",
+            ExceededSizeLimit          => "Exceeded size limit",
+            ExceededStringLengthLimit  => "Exceeded string length limit",
+            ExceededObjectEntriesLimit => "Exceeded object entries limit",
+            ExceededArrayLengthLimit   => "Exceeded array length limit",
             FailedUtf8Parsing          => "Failed to read bytes as UTF-8 from JSON",
+            InvalidUtf8 { .. }         => "Invalid UTF-8 byte",
+            DuplicateKey { .. }        => "Duplicate key",
+            LoneSurrogate { .. }       => "Lone surrogate",
+            InvalidEncoding { .. }     => "Invalid encoding",
             WrongType(_)               => "Wrong type",
+            Io { .. }                  => "I/O error",
         }
     }
 }
@@ -208,9 +208,10 @@ mod util;
 pub mod short;
 pub mod object;
 pub mod number;
+pub mod document;
 
-pub use error::Error;
-pub use value::JsonValue;
+pub use error::{ Error, Expected };
+pub use value::{ JsonValue, JsonValueRef };
 pub use value::JsonValue::Null;
 
 /// Result type used by this crate.
@@ -240,7 +241,11 @@ pub use Error as JsonError;
 #[deprecated(since="0.9.0", note="use `json::Result` instead")]
 pub use crate::Result as JsonResult;
 
-pub use parser::parse;
+pub use parser::{ parse, parse_with, parse_bytes, parse_bytes_with, parse_bytes_detect, parse_bytes_detect_with, parse_reader, parse_reader_with, parse_recover, parse_recover_with, parse_borrowed, parse_borrowed_with, parse_document, parse_document_with, parse_lazy, parse_lazy_with, parse_spanned, parse_spanned_with, extract, extract_with, validate, validate_with, validate_bytes, validate_bytes_with, ParserOptions, DuplicateKeys, LoneSurrogates, Encoding, ReusableParser, parse_into, parse_into_with, parse_truncated, parse_truncated_with, Truncated, Completion, Incomplete };
+pub use parser::{ push, pull, stream, lazy, spans };
+
+#[cfg(feature = "async")]
+pub use parser::{ parse_async, parse_async_with };
 
 pub type Array = Vec<JsonValue>;
 
@@ -1,4 +1,5 @@
 use std::{ ops, fmt, f32, f64 };
+use std::str::FromStr;
 use std::convert::{TryFrom, Infallible};
 use std::num::{FpCategory, TryFromIntError};
 use crate::util::grisu2;
@@ -578,3 +579,222 @@ fn decimal_power(mut e: u16) -> u64 {
         pow
     }
 }
+
+/// A number that keeps every one of its decimal digits, exactly as written
+/// in the source. `Number` only has a 64 bit mantissa, anything beyond that
+/// is rounded away, which won't do for 128 bit IDs or amounts of money that
+/// must come back out exactly as they went in.
+///
+/// Parsing produces these in place of `Number` when `lossless_numbers` is
+/// set on `ParserOptions`, and they are written back out unchanged.
+///
+/// ```
+/// # use json::number::{ Decimal, Number };
+/// # use std::convert::TryFrom;
+/// let options = json::ParserOptions::new().lossless_numbers(true);
+/// let data = json::parse_with(r#"{"id":170141183460469231731687303715884105727,"amount":10.50}"#, &options).unwrap();
+///
+/// let id = data["id"].as_decimal().unwrap();
+///
+/// assert_eq!(i128::try_from(id).ok(), Some(i128::MAX));
+/// assert_eq!(data["amount"].as_decimal().map(Decimal::as_str), Some("10.50"));
+/// assert_eq!(data["amount"].as_f64(), Some(10.5));
+/// assert_eq!(data.dump(), r#"{"id":170141183460469231731687303715884105727,"amount":10.50}"#);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Decimal {
+    // Always a valid JSON number
+    text: String,
+}
+
+impl Decimal {
+    // The `text` has already been checked to be a valid JSON number.
+    pub(crate) fn from_valid(text: String) -> Self {
+        Decimal { text }
+    }
+
+    /// The number as it was written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Convert into the closest `Number`, the same one parsing without
+    /// `lossless_numbers` would have produced. For one without any loss,
+    /// use `Number::try_from`.
+    pub fn to_number(&self) -> Number {
+        crate::parser::parse_number(&self.text).expect("Decimal is always a valid number")
+    }
+
+    // Sign, significant digits with no zeroes on either end, and the
+    // exponent that goes with them. `None` if the exponent is too large to
+    // be of any use.
+    fn parts(&self) -> Option<(bool, String, i64)> {
+        let (positive, text) = match self.text.strip_prefix('-') {
+            Some(text) => (false, text),
+            None       => (true, self.text.as_str()),
+        };
+
+        let (mantissa, exponent) = match text.find(['e', 'E']) {
+            Some(at) => (&text[..at], text[at + 1..].parse::<i64>().ok()?),
+            None     => (text, 0),
+        };
+
+        let (integer, fraction) = match mantissa.find('.') {
+            Some(at) => (&mantissa[..at], &mantissa[at + 1..]),
+            None     => (mantissa, ""),
+        };
+
+        let mut digits: String = integer.chars()
+            .chain(fraction.chars())
+            .skip_while(|&ch| ch == '0')
+            .collect();
+
+        let mut exponent = exponent.checked_sub(fraction.len() as i64)?;
+
+        while digits.ends_with('0') {
+            digits.pop();
+            exponent = exponent.checked_add(1)?;
+        }
+
+        if digits.is_empty() {
+            exponent = 0;
+        }
+
+        Some((positive, digits, exponent))
+    }
+
+    // The integer this number is, if it is one.
+    fn as_integer(&self) -> Option<(bool, u128)> {
+        let (positive, digits, exponent) = self.parts()?;
+
+        if digits.is_empty() {
+            return Some((true, 0));
+        }
+
+        if exponent < 0 || digits.len() as i64 + exponent > 39 {
+            return None;
+        }
+
+        let magnitude = digits.parse::<u128>().ok()?.checked_mul(10u128.checked_pow(exponent as u32)?)?;
+
+        Some((positive, magnitude))
+    }
+}
+
+impl PartialEq for Decimal {
+    /// Decimals are equal if their values are, `1.50` is the same as `1.5`.
+    fn eq(&self, other: &Decimal) -> bool {
+        match (self.parts(), other.parts()) {
+            (Some(a), Some(b)) => a == b || a.1.is_empty() && b.1.is_empty(),
+            _                  => self.text == other.text,
+        }
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialEq<Number> for Decimal {
+    fn eq(&self, other: &Number) -> bool {
+        matches!(Number::try_from(self), Ok(number) if number == *other)
+    }
+}
+
+impl PartialEq<Decimal> for Number {
+    fn eq(&self, other: &Decimal) -> bool {
+        other == self
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Check that the string is a valid JSON number, failing with the same error
+/// `json::parse` would.
+///
+/// ```
+/// # use json::number::Decimal;
+/// let decimal: Decimal = "-12345678901234567890.00000000001".parse().unwrap();
+///
+/// assert_eq!(decimal.as_str(), "-12345678901234567890.00000000001");
+/// assert!("1.".parse::<Decimal>().is_err());
+/// assert!("0x10".parse::<Decimal>().is_err());
+/// ```
+impl FromStr for Decimal {
+    type Err = crate::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        crate::parser::parse_number(text)?;
+
+        Ok(Decimal { text: text.into() })
+    }
+}
+
+impl TryFrom<Number> for Decimal {
+    type Error = NumberOutOfScope;
+
+    /// Fails for NaN, which isn't a number JSON can have.
+    fn try_from(number: Number) -> Result<Self, Self::Error> {
+        if number.is_nan() {
+            return Err(NumberOutOfScope);
+        }
+
+        Ok(Decimal { text: number.to_string() })
+    }
+}
+
+impl TryFrom<&Decimal> for Number {
+    type Error = NumberOutOfScope;
+
+    /// Fails if the number can't be represented exactly.
+    fn try_from(decimal: &Decimal) -> Result<Self, Self::Error> {
+        let (positive, digits, exponent) = decimal.parts().ok_or(NumberOutOfScope)?;
+
+        let mantissa = match digits.as_str() {
+            ""     => 0,
+            digits => digits.parse::<u64>().map_err(|_| NumberOutOfScope)?,
+        };
+
+        Ok(Number::from_parts(positive, mantissa, i16::try_from(exponent)?))
+    }
+}
+
+impl TryFrom<&Decimal> for u128 {
+    type Error = NumberOutOfScope;
+
+    /// Fails for anything that isn't a whole number in range.
+    fn try_from(decimal: &Decimal) -> Result<Self, Self::Error> {
+        match decimal.as_integer() {
+            Some((true, magnitude)) => Ok(magnitude),
+            _                       => Err(NumberOutOfScope),
+        }
+    }
+}
+
+impl TryFrom<&Decimal> for i128 {
+    type Error = NumberOutOfScope;
+
+    /// Fails for anything that isn't a whole number in range.
+    fn try_from(decimal: &Decimal) -> Result<Self, Self::Error> {
+        match decimal.as_integer() {
+            Some((true, magnitude)) => Ok(i128::try_from(magnitude)?),
+            Some((false, magnitude)) if magnitude <= i128::MAX as u128 => Ok(-(magnitude as i128)),
+            Some((false, magnitude)) if magnitude == i128::MAX as u128 + 1 => Ok(i128::MIN),
+            _ => Err(NumberOutOfScope),
+        }
+    }
+}
+
+macro_rules! impl_decimal {
+    ($( $t:ty ),*) => ($(
+        impl From<$t> for Decimal {
+            fn from(num: $t) -> Decimal {
+                Decimal { text: num.to_string() }
+            }
+        }
+    )*)
+}
+
+impl_decimal!(isize, i8, i16, i32, i64, i128, usize, u8, u16, u32, u64, u128);
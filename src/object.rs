@@ -1,11 +1,19 @@
 use std::{ ptr, mem, str, slice, fmt };
 use std::ops::{ Index, IndexMut, Deref };
 use std::iter::FromIterator;
+use std::collections::HashMap;
+use std::hash::{ BuildHasherDefault, Hasher };
+use std::sync::{ Arc, Mutex, MutexGuard };
 
 use crate::codegen::{ DumpGenerator, Generator, PrettyGenerator };
 use crate::value::JsonValue;
 
-const KEY_BUF_LEN: usize = 32;
+pub(crate) const KEY_BUF_LEN: usize = 32;
+
+// Marker in the first byte of `buf` of a long key that is held by an
+// `Interner`. `buf` isn't used for anything else on long keys, and is
+// zeroed on keys that have their own allocation.
+const INTERNED: u8 = 1;
 static NULL: JsonValue = JsonValue::Null;
 
 // FNV-1a implementation
@@ -40,7 +48,7 @@ static NULL: JsonValue = JsonValue::Null;
 // 15043799550796757486  <-- 3rd
 // ```
 #[inline]
-fn hash_key(key: &[u8]) -> u64 {
+pub(crate) fn hash_key(key: &[u8]) -> u64 {
     let mut hash: u64 = 0xcbf29ce484222325;
     for byte in key {
         hash ^= *byte as u64;
@@ -51,7 +59,8 @@ fn hash_key(key: &[u8]) -> u64 {
 
 struct Key {
     // Internal buffer to store keys that fit within `KEY_BUF_LEN`,
-    // otherwise this field will contain garbage.
+    // otherwise this field will contain garbage, save for the `INTERNED`
+    // marker.
     pub buf: [u8; KEY_BUF_LEN],
 
     // Length of the key in bytes.
@@ -95,8 +104,11 @@ impl Key {
     // is already on the `Vec`'s heap (along with the `Node`).
     // For that reason it's not set in `Key::new` but only after
     // the `Node` is created and allocated.
+    //
+    // Long keys that come from an `Interner` point to the shared copy,
+    // short ones are just as cheap to copy.
     #[inline]
-    fn attach(&mut self, key: &[u8]) {
+    fn attach(&mut self, key: &[u8], interned: Option<&InternedKey>) {
         if self.len <= KEY_BUF_LEN {
             unsafe {
                 ptr::copy_nonoverlapping(
@@ -106,6 +118,9 @@ impl Key {
                 );
             }
             self.ptr = self.buf.as_mut_ptr();
+        } else if let Some(interned) = interned {
+            self.ptr = Arc::into_raw(interned.text.clone()) as *mut u8;
+            self.buf[0] = INTERNED;
         } else {
             let mut heap = key.to_vec();
             self.ptr = heap.as_mut_ptr();
@@ -113,6 +128,41 @@ impl Key {
         }
     }
 
+    #[inline]
+    fn is_interned(&self) -> bool {
+        self.len > KEY_BUF_LEN && self.buf[0] == INTERNED
+    }
+
+    // Raw pointer to the shared copy of an interned key, as it came out of
+    // `Arc::into_raw`.
+    #[inline]
+    fn shared(&self) -> *const str {
+        ptr::slice_from_raw_parts(self.ptr as *const u8, self.len) as *const str
+    }
+
+    // Handle to the shared copy of this key, if it has one.
+    fn interned(&self) -> Option<InternedKey> {
+        if !self.is_interned() {
+            return None;
+        }
+
+        unsafe {
+            Arc::increment_strong_count(self.shared());
+
+            Some(InternedKey {
+                text: Arc::from_raw(self.shared()),
+                hash: self.hash,
+            })
+        }
+    }
+
+    // Keys from the same interner are the same if they point to the same
+    // place, there's no need to look at the bytes.
+    #[inline]
+    fn matches(&self, key: &[u8]) -> bool {
+        key.len() == self.len && (ptr::eq(key.as_ptr(), self.ptr) || key == self.as_bytes())
+    }
+
     // Since we store `Node`s on a vector, it will suffer from reallocation.
     // Whenever that happens, `key.ptr` for short keys will turn into dangling
     // pointers and will need to be re-cached.
@@ -134,7 +184,10 @@ unsafe impl Send for Key {}
 impl Drop for Key {
     fn drop(&mut self) {
         unsafe {
-            if self.len > KEY_BUF_LEN {
+            if self.is_interned() {
+                // Let go of the shared copy.
+                drop(Arc::from_raw(self.shared()));
+            } else if self.len > KEY_BUF_LEN {
                 // Construct a `Vec` out of the `key_ptr`. Since the key is
                 // always allocated from a slice, the capacity is equal to length.
                 let heap = Vec::from_raw_parts(
@@ -154,7 +207,16 @@ impl Drop for Key {
 // for the fact that key _can_ be separately heap allocated.
 impl Clone for Key {
     fn clone(&self) -> Self {
-        if self.len > KEY_BUF_LEN {
+        if self.is_interned() {
+            unsafe { Arc::increment_strong_count(self.shared()) };
+
+            Key {
+                buf: self.buf,
+                len: self.len,
+                ptr: self.ptr,
+                hash: self.hash,
+            }
+        } else if self.len > KEY_BUF_LEN {
             let mut heap = self.as_bytes().to_vec();
             let ptr = heap.as_mut_ptr();
             mem::forget(heap);
@@ -221,6 +283,196 @@ impl Node {
     }
 }
 
+/// A key shared through an `Interner`, see `Interner::intern`. Cloning it
+/// only bumps a reference count, and its hash is worked out up front, so
+/// it can be used to look up the same key in many objects.
+#[derive(Clone)]
+pub struct InternedKey {
+    text: Arc<str>,
+    hash: u64,
+}
+
+impl InternedKey {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Deref for InternedKey {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl PartialEq for InternedKey {
+    fn eq(&self, other: &InternedKey) -> bool {
+        Arc::ptr_eq(&self.text, &other.text) || self.text == other.text
+    }
+}
+
+impl Eq for InternedKey {}
+
+impl fmt::Debug for InternedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+// Keys in the interner are already hashed with `hash_key`, there's no need
+// to hash them again.
+#[derive(Default)]
+struct Prehashed(u64);
+
+impl Hasher for Prehashed {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = hash_key(bytes);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, hash: u64) {
+        self.0 = hash;
+    }
+}
+
+type Keys = HashMap<u64, Arc<str>, BuildHasherDefault<Prehashed>>;
+
+/// A set of object keys that can be shared between objects, and between the
+/// documents they come from. When lots of documents are made of the same
+/// keys, like log records are, each one is only stored once, and objects
+/// keep handles to it instead of copies of their own.
+///
+/// Keys up to 32 bytes are stored within the entries of an `Object` either
+/// way, which is just as cheap as a handle, so only longer ones are shared.
+/// Interning a short key still gives a handle that is hashed up front.
+///
+/// Cloning an `Interner` gives another handle to the same set, which can be
+/// used from other threads. Keys are never removed, so unless documents come
+/// from a trusted source, it's best to put a cap on the number of keys with
+/// `Interner::with_limit`.
+///
+/// ```
+/// use json::ParserOptions;
+/// use json::object::Interner;
+///
+/// let interner = Interner::new();
+/// let options = ParserOptions::new().interner(&interner);
+/// let key = interner.intern("a_rather_long_key_that_would_need_its_own_allocation");
+///
+/// for source in &[
+///     r#"{ "a_rather_long_key_that_would_need_its_own_allocation": 1 }"#,
+///     r#"{ "a_rather_long_key_that_would_need_its_own_allocation": 2 }"#,
+/// ] {
+///     let value = json::parse_with(source, &options).unwrap();
+///
+///     if let json::JsonValue::Object(object) = value {
+///         assert!(object.get_interned(&key).is_some());
+///     }
+/// }
+///
+/// assert_eq!(interner.len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct Interner {
+    keys: Arc<Mutex<Keys>>,
+    limit: usize,
+}
+
+impl Interner {
+    /// Create an empty interner that takes any number of keys.
+    pub fn new() -> Self {
+        Interner::with_limit(usize::MAX)
+    }
+
+    /// Create an empty interner that takes up to `limit` keys. Handles to
+    /// keys past that are made as usual, but the keys aren't kept, and
+    /// objects store them as they would without an interner.
+    pub fn with_limit(limit: usize) -> Self {
+        Interner {
+            keys: Arc::new(Mutex::new(Keys::default())),
+            limit,
+        }
+    }
+
+    /// Get the handle for `key`, adding it to the set if it's not there yet.
+    ///
+    /// ```
+    /// use json::object::Interner;
+    ///
+    /// let interner = Interner::new();
+    ///
+    /// assert_eq!(interner.intern("foo"), interner.intern("foo"));
+    /// assert_eq!(&*interner.intern("foo"), "foo");
+    /// ```
+    pub fn intern(&self, key: &str) -> InternedKey {
+        let hash = hash_key(key.as_bytes());
+        let mut keys = self.keys();
+
+        if let Some(text) = keys.get(&hash) {
+            // Keys that happen to have the same hash as one that's already
+            // there are left out, they can still be looked up by bytes
+            if &**text == key {
+                return InternedKey { text: text.clone(), hash };
+            }
+        }
+
+        let text: Arc<str> = key.into();
+
+        if keys.len() < self.limit && !keys.contains_key(&hash) {
+            keys.insert(hash, text.clone());
+        }
+
+        InternedKey { text, hash }
+    }
+
+    /// Number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.keys().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn keys(&self) -> MutexGuard<'_, Keys> {
+        // Nothing can panic while the lock is held, but it costs nothing
+        // to carry on if it does
+        self.keys.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Interner::new()
+    }
+}
+
+// Interners are the same if they share the same set of keys.
+impl PartialEq for Interner {
+    fn eq(&self, other: &Interner) -> bool {
+        Arc::ptr_eq(&self.keys, &other.keys)
+    }
+}
+
+impl Eq for Interner {}
+
+impl fmt::Debug for Interner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Interner")
+            .field("len", &self.len())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
 /// A binary tree implementation of a string -> `JsonValue` map. You normally don't
 /// have to interact with instances of `Object`, much more likely you will be
 /// using the `JsonValue::Object` variant, which wraps around this struct.
@@ -254,7 +506,7 @@ impl Object {
     }
 
     #[inline(always)]
-    fn add_node(&mut self, key: &[u8], value: JsonValue, hash: u64) -> usize {
+    fn add_node(&mut self, key: &[u8], interned: Option<&InternedKey>, value: JsonValue, hash: u64) -> usize {
         let index = self.store.len();
 
         if index < self.store.capacity() {
@@ -279,11 +531,11 @@ impl Object {
                 mem::forget(node);
             }
 
-            unsafe { self.store.get_unchecked_mut(index).key.attach(key) };
+            unsafe { self.store.get_unchecked_mut(index).key.attach(key, interned) };
         } else {
             self.store.push(Node::new(value, hash, key.len()));
 
-            unsafe { self.store.get_unchecked_mut(index).key.attach(key) };
+            unsafe { self.store.get_unchecked_mut(index).key.attach(key, interned) };
 
             // Index up to the index (old length), we don't need to fix
             // anything on the Node that just got pushed.
@@ -306,11 +558,42 @@ impl Object {
 
     pub(crate) fn insert_index(&mut self, key: &str, value: JsonValue) -> usize {
         let key = key.as_bytes();
-        let hash = hash_key(key);
 
+        self.insert_node(key, hash_key(key), None, value)
+    }
+
+    /// Insert a new entry, or override an existing one, with a key from an
+    /// `Interner`. Keys longer than a few dozen bytes are not copied, the
+    /// entry shares the interned one instead.
+    ///
+    /// ```
+    /// # #[macro_use] extern crate json;
+    /// # fn main() {
+    /// use json::object::{ Interner, Object };
+    ///
+    /// let interner = Interner::new();
+    /// let key = interner.intern("name");
+    /// let mut object = Object::new();
+    ///
+    /// object.insert_interned(&key, "Joe".into());
+    ///
+    /// assert_eq!(object.get("name"), Some(&json::from("Joe")));
+    /// assert_eq!(object.get_interned(&key), Some(&json::from("Joe")));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn insert_interned(&mut self, key: &InternedKey, value: JsonValue) {
+        self.insert_interned_index(key, value);
+    }
+
+    pub(crate) fn insert_interned_index(&mut self, key: &InternedKey, value: JsonValue) -> usize {
+        self.insert_node(key.as_bytes(), key.hash, Some(key), value)
+    }
+
+    fn insert_node(&mut self, key: &[u8], hash: u64, interned: Option<&InternedKey>, value: JsonValue) -> usize {
         if self.store.len() == 0 {
             self.store.push(Node::new(value, hash, key.len()));
-            self.store[0].key.attach(key);
+            self.store[0].key.attach(key, interned);
             return 0;
         }
 
@@ -318,7 +601,7 @@ impl Object {
         let mut parent = 0;
 
         loop {
-            if hash == node.key.hash && key == node.key.as_bytes() {
+            if hash == node.key.hash && node.key.matches(key) {
                 node.value = value;
                 return parent;
             } else if hash < node.key.hash {
@@ -327,7 +610,7 @@ impl Object {
                     node = unsafe { &mut *self.node_at_index_mut(node.left) };
                     continue;
                 }
-                let index = self.add_node(key, value, hash);
+                let index = self.add_node(key, interned, value, hash);
                 self.store[parent].left = index;
 
                 return index;
@@ -337,7 +620,7 @@ impl Object {
                     node = unsafe { &mut *self.node_at_index_mut(node.right) };
                     continue;
                 }
-                let index = self.add_node(key, value, hash);
+                let index = self.add_node(key, interned, value, hash);
                 self.store[parent].right = index;
 
                 return index;
@@ -386,6 +669,59 @@ impl Object {
     }
 
     pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        let index = self.index_of(key)?;
+
+        let node = unsafe { self.store.get_unchecked_mut(index) };
+
+        Some(&mut node.value)
+    }
+
+    /// Same as `get`, but with a key from an `Interner`, which has been
+    /// hashed already. Entries that share it are found by comparing pointers
+    /// rather than bytes.
+    pub fn get_interned(&self, key: &InternedKey) -> Option<&JsonValue> {
+        let index = self.index_of_interned(key)?;
+
+        Some(unsafe { &self.store.get_unchecked(index).value })
+    }
+
+    pub fn get_interned_mut(&mut self, key: &InternedKey) -> Option<&mut JsonValue> {
+        let index = self.index_of_interned(key)?;
+
+        Some(unsafe { &mut self.store.get_unchecked_mut(index).value })
+    }
+
+    fn index_of_interned(&self, key: &InternedKey) -> Option<usize> {
+        if self.store.is_empty() {
+            return None;
+        }
+
+        let hash = key.hash;
+        let key = key.as_bytes();
+
+        let mut index = 0;
+        let mut node = unsafe { self.store.get_unchecked(0) };
+
+        loop {
+            if hash == node.key.hash && node.key.matches(key) {
+                return Some(index);
+            } else if hash < node.key.hash {
+                if node.left == 0 {
+                    return None;
+                }
+                index = node.left;
+                node = unsafe { self.store.get_unchecked(node.left) };
+            } else {
+                if node.right == 0 {
+                    return None;
+                }
+                index = node.right;
+                node = unsafe { self.store.get_unchecked(node.right) };
+            }
+        }
+    }
+
+    pub(crate) fn index_of(&self, key: &str) -> Option<usize> {
         if self.store.len() == 0 {
             return None;
         }
@@ -394,31 +730,30 @@ impl Object {
         let hash = hash_key(key);
 
         let mut index = 0;
-        {
-            let mut node = unsafe { self.store.get_unchecked(0) };
+        let mut node = unsafe { self.store.get_unchecked(0) };
 
-            loop {
-                if hash == node.key.hash && key == node.key.as_bytes() {
-                    break;
-                } else if hash < node.key.hash {
-                    if node.left == 0 {
-                        return None;
-                    }
-                    index = node.left;
-                    node = unsafe { self.store.get_unchecked(node.left) };
-                } else {
-                    if node.right == 0 {
-                        return None;
-                    }
-                    index = node.right;
-                    node = unsafe { self.store.get_unchecked(node.right) };
+        loop {
+            if hash == node.key.hash && key == node.key.as_bytes() {
+                return Some(index);
+            } else if hash < node.key.hash {
+                if node.left == 0 {
+                    return None;
                 }
+                index = node.left;
+                node = unsafe { self.store.get_unchecked(node.left) };
+            } else {
+                if node.right == 0 {
+                    return None;
+                }
+                index = node.right;
+                node = unsafe { self.store.get_unchecked(node.right) };
             }
         }
+    }
 
-        let node = unsafe { self.store.get_unchecked_mut(index) };
-
-        Some(&mut node.value)
+    #[inline]
+    pub(crate) fn value_at_mut(&mut self, index: usize) -> &mut JsonValue {
+        &mut self.store[index].value
     }
 
     /// Attempts to remove the value behind `key`, if successful
@@ -469,8 +804,9 @@ impl Object {
                 removed = Some(mem::replace(&mut node.value, JsonValue::Null));
             } else {
                 let value = mem::replace(&mut node.value, JsonValue::Null);
+                let interned = node.key.interned();
 
-                new_object.insert(node.key.as_str(), value);
+                new_object.insert_node(node.key.as_bytes(), node.key.hash, interned.as_ref(), value);
             }
         }
 
@@ -489,6 +825,12 @@ impl Object {
         self.store.is_empty()
     }
 
+    /// Number of entries the `Object` can hold without reallocating.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.store.capacity()
+    }
+
     /// Wipe the `Object` clear. The capacity will remain untouched.
     pub fn clear(&mut self) {
         self.store.clear();
@@ -0,0 +1,76 @@
+// Parsing from `futures_io::AsyncRead`, behind the `async` feature. The
+// regular parser pulls bytes from the source whenever it runs out, which
+// an async source can't do without blocking, so instead chunks are read as
+// they come and handed to a `PushParser`, which can stop at any byte and
+// pick up again once the next chunk is in. It's set up to take a single
+// document, so results are the same as for `parse_reader`.
+
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+
+use futures_io::AsyncRead;
+
+use crate::{ Error, JsonValue, Result };
+use super::push::{ PushParser, Status };
+use super::read::CHUNK_SIZE;
+use super::{ ParserOptions, DEFAULT_OPTIONS };
+
+/// Parse a JSON document from an implementor of `futures_io::AsyncRead`,
+/// such as a socket or a request body. The source is parsed chunk by chunk
+/// as it comes in, it's never kept in memory as a whole. Only available
+/// with the `async` feature.
+///
+/// The result, including error positions, is the same as parsing the
+/// source with `parse_reader`. Errors from the reader itself are reported
+/// as `Error::Io`.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// # let block_on = |future| futures_executor::block_on(future);
+/// let source: &[u8] = br#"{"foo":[1,2,3]}"#;
+///
+/// assert_eq!(block_on(json::parse_async(source)).unwrap(), object!{ foo: [1, 2, 3] });
+/// # }
+/// ```
+pub async fn parse_async<R: AsyncRead + Unpin>(reader: R) -> Result<JsonValue> {
+    parse_async_with(reader, &DEFAULT_OPTIONS).await
+}
+
+/// Parse a JSON document from an implementor of `futures_io::AsyncRead`
+/// while enforcing the limits set on `options`.
+pub async fn parse_async_with<R: AsyncRead + Unpin>(mut reader: R, options: &ParserOptions) -> Result<JsonValue> {
+    // The size of the whole source is limited, not just the value
+    let mut parser = PushParser::document(&options.clone().size_limit(usize::MAX));
+    let mut chunk = vec![0; CHUNK_SIZE];
+    let mut size = 0;
+    let mut value = None;
+
+    loop {
+        let read = match poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut chunk)).await {
+            Ok(0)    => break,
+            Ok(read) => read,
+            Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        };
+
+        size += read;
+
+        if size > options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        if let Status::Complete(complete) = parser.feed(&chunk[..read])? {
+            value = Some(complete);
+
+            // Check whatever came after it right away
+            parser.feed(&[])?;
+        }
+    }
+
+    match parser.finish()?.or(value) {
+        Some(value) => Ok(value),
+        None        => Err(Error::UnexpectedEndOfJson),
+    }
+}
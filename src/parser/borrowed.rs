@@ -0,0 +1,233 @@
+// Parsing into `JsonValueRef`. The source is walked through the same way as
+// for everything else, the difference is in what happens to strings:
+// `expect_string!` hands out a slice of the source for anything without
+// escapes, and only those with escapes are decoded into the buffer. Slices
+// of the source are borrowed for as long as the source lives, the rest is
+// copied out of the buffer.
+//
+// Objects keep their entries in a vector, which is searched through when a
+// key is added, to deal with duplicates. That's fine for small objects, but
+// not for large ones, so past a few entries they get a hash map to find keys
+// in.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::{ str, slice };
+
+use crate::{ Error, Expected, JsonValueRef, Result };
+use super::{ Parser, ParserOptions, DuplicateKeys, ALLOWED, DEFAULT_OPTIONS };
+use super::walk::{ Walk, Step, Scalar };
+
+// Objects with more entries than that get a map of their keys.
+const INDEXED: usize = 16;
+
+// An array or object that's still being parsed.
+enum Open<'a> {
+    Array(Vec<JsonValueRef<'a>>),
+    Object(OpenObject<'a>),
+}
+
+struct OpenObject<'a> {
+    entries: Vec<(Cow<'a, str>, JsonValueRef<'a>)>,
+
+    // Position of every key in `entries`, once there are enough of them
+    index: HashMap<Cow<'a, str>, usize>,
+
+    // Entries that have been turned into arrays for `DuplicateKeys::Collect`
+    collected: Vec<usize>,
+
+    // Where the value of the current entry goes
+    slot: Slot,
+}
+
+#[derive(Clone, Copy)]
+enum Slot {
+    // Last entry, which is new
+    Last,
+
+    // Entry of a key that's been seen before
+    Duplicate(usize),
+}
+
+impl<'a> OpenObject<'a> {
+    fn new() -> Self {
+        OpenObject {
+            entries: Vec::with_capacity(3),
+            index: HashMap::new(),
+            collected: Vec::new(),
+            slot: Slot::Last,
+        }
+    }
+
+    fn find(&self, key: &str) -> Option<usize> {
+        if self.entries.len() > INDEXED {
+            return self.index.get(key).cloned();
+        }
+
+        self.entries.iter().position(|entry| entry.0 == key)
+    }
+
+    // Add the `key` read from the source. Returns `false` if it's a duplicate
+    // that isn't allowed.
+    fn insert(&mut self, key: Cow<'a, str>, policy: DuplicateKeys) -> bool {
+        self.slot = match self.find(&key) {
+            None => {
+                if self.entries.len() >= INDEXED {
+                    if self.index.is_empty() {
+                        for (at, entry) in self.entries.iter().enumerate() {
+                            self.index.insert(entry.0.clone(), at);
+                        }
+                    }
+                    self.index.insert(key.clone(), self.entries.len());
+                }
+
+                self.entries.push((key, JsonValueRef::Null));
+                Slot::Last
+            },
+            Some(_) if policy == DuplicateKeys::Error => return false,
+            Some(at) => {
+                if policy == DuplicateKeys::Collect && !self.collected.contains(&at) {
+                    let value = &mut self.entries[at].1;
+                    let first = std::mem::replace(value, JsonValueRef::Null);
+
+                    *value = JsonValueRef::Array(vec![first]);
+                    self.collected.push(at);
+                }
+
+                Slot::Duplicate(at)
+            },
+        };
+
+        true
+    }
+
+    // Set the value of the current entry. Values collected into an array
+    // are held to `limit`, same as elements of any other.
+    fn set(&mut self, value: JsonValueRef<'a>, policy: DuplicateKeys, limit: usize) -> Result<()> {
+        let at = match self.slot {
+            Slot::Last          => self.entries.len() - 1,
+            Slot::Duplicate(at) => at,
+        };
+
+        let entry = &mut self.entries[at].1;
+
+        match (self.slot, policy) {
+            (Slot::Duplicate(_), DuplicateKeys::KeepFirst) => {},
+            (Slot::Duplicate(_), DuplicateKeys::Collect)   => {
+                if let JsonValueRef::Array(ref mut values) = *entry {
+                    if values.len() >= limit {
+                        return Err(Error::ExceededArrayLengthLimit);
+                    }
+
+                    values.push(value);
+                }
+            },
+            _ => *entry = value,
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a document into a `JsonValueRef`, which borrows strings without
+/// escapes from the `source` instead of copying them.
+///
+/// The value is the same one `parse` would return, only borrowed. Use
+/// `JsonValueRef::into_owned` to turn it into that.
+pub fn parse_borrowed(source: &str) -> Result<JsonValueRef<'_>> {
+    parse_borrowed_with(source, &DEFAULT_OPTIONS)
+}
+
+/// Parse a document into a `JsonValueRef` while enforcing the limits set on
+/// `options`.
+pub fn parse_borrowed_with<'a>(source: &'a str, options: &ParserOptions) -> Result<JsonValueRef<'a>> {
+    Parser::new(source, options).parse_borrowed(source)
+}
+
+// Borrow the `string` from the `source` if it's a part of it, or copy it
+// if it's been decoded into the buffer.
+pub(super) fn borrow<'a>(source: &'a str, string: &str) -> Cow<'a, str> {
+    let start = (string.as_ptr() as usize).wrapping_sub(source.as_ptr() as usize);
+
+    match source.get(start..start.wrapping_add(string.len())) {
+        Some(slice) if slice.as_ptr() == string.as_ptr() => Cow::Borrowed(slice),
+        _                                                => Cow::Owned(string.to_owned()),
+    }
+}
+
+impl<'p> Parser<'p> {
+    fn parse_borrowed<'a>(&mut self, source: &'a str) -> Result<JsonValueRef<'a>> {
+        if self.length > self.options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        self.find_structurals();
+
+        let policy = self.options.duplicate_keys;
+        let mut walk = Walk::new(self.options);
+        let mut stack = Vec::with_capacity(3);
+        let mut root = JsonValueRef::Null;
+
+        loop {
+            let value = match self.step(&mut walk)? {
+                Step::Open(false) => {
+                    stack.push(Open::Array(Vec::with_capacity(2)));
+                    continue;
+                },
+                Step::Open(true) => {
+                    stack.push(Open::Object(OpenObject::new()));
+                    continue;
+                },
+                Step::Key(ch, expected) => {
+                    if let Some(Open::Object(object)) = stack.last_mut() {
+                        self.borrowed_key(source, &mut walk, object, ch, expected)?;
+                    }
+                    continue;
+                },
+                Step::Close(_) => match stack.pop() {
+                    Some(Open::Array(array))   => JsonValueRef::Array(array),
+                    Some(Open::Object(object)) => JsonValueRef::Object(object.entries),
+                    None                       => unreachable!(),
+                },
+                Step::Scalar(ch) => match self.read_scalar(ch)? {
+                    Scalar::String(string)   => JsonValueRef::String(borrow(source, string)),
+                    Scalar::Number(number)   => JsonValueRef::Number(number),
+                    Scalar::Decimal(decimal) => JsonValueRef::Decimal(decimal),
+                    Scalar::Boolean(value)   => JsonValueRef::Boolean(value),
+                    Scalar::Null             => JsonValueRef::Null,
+                },
+                Step::End => return Ok(root),
+            };
+
+            match stack.last_mut() {
+                None                       => root = value,
+                Some(Open::Array(array))   => array.push(value),
+                Some(Open::Object(object)) => object.set(value, policy, self.options.array_length_limit)?,
+            }
+        }
+    }
+
+    // Read a key starting with `ch` and add it to the `object`.
+    fn borrowed_key<'a>(
+        &mut self,
+        source: &'a str,
+        walk: &mut Walk,
+        object: &mut OpenObject<'a>,
+        ch: u8,
+        expected: Expected,
+    ) -> Result<()> {
+        self.pin = self.index - 1;
+
+        let key = expect_key!(self, ch, expected);
+        let len = object.entries.len();
+
+        if !object.insert(borrow(source, key), self.options.duplicate_keys) {
+            return self.duplicate_key(key);
+        }
+
+        self.count_entry(walk, object.entries.len() > len)?;
+
+        self.pin = usize::MAX;
+        Ok(())
+    }
+}
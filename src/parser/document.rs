@@ -0,0 +1,249 @@
+// Parsing into a `Document`. The source is walked through the same way
+// `Parser::parse_borrowed` does it, except values don't get built up into
+// vectors of their own. Everything that's been parsed but isn't in an array
+// or object yet is kept on a single stack of nodes, with the members of each
+// open array, or keys and values of each open object, on top of the ones
+// before them. Once an array or object is closed, all of that is moved into
+// the arena of the document at once, and it's replaced with a single node.
+//
+// Duplicate keys are looked for among the entries on the stack, objects with
+// lots of entries get a map of key hashes to find them.
+
+use std::collections::HashMap;
+use std::{ str, slice };
+
+use crate::{ Error, Expected, Result };
+use crate::document::{ Document, Node, Builder };
+use crate::object::hash_key;
+use super::{ Parser, ParserOptions, DuplicateKeys, ALLOWED, DEFAULT_OPTIONS };
+use super::walk::{ Walk, Step, Scalar };
+
+// Objects with more entries than that get a map of their keys.
+const INDEXED: usize = 16;
+
+// An array or object that's still being parsed, with where its contents
+// start on the stack.
+enum Open {
+    Array(usize),
+    Object(OpenObject),
+}
+
+struct OpenObject {
+    start: usize,
+
+    // Number of entries so far
+    len: usize,
+
+    // Hashes of keys to the entry with that key, once there are enough
+    // of them. Should two keys have the same hash only the first one is
+    // here, the rest are looked for the slow way.
+    index: HashMap<u64, usize>,
+
+    // Values of entries that are collected for `DuplicateKeys::Collect`
+    collected: Vec<(usize, Vec<Node>)>,
+
+    // Entry the current value goes to, and whether it's a duplicate
+    slot: usize,
+    duplicate: bool,
+}
+
+impl OpenObject {
+    fn new(start: usize) -> Self {
+        OpenObject {
+            start,
+            len: 0,
+            index: HashMap::new(),
+            collected: Vec::new(),
+            slot: 0,
+            duplicate: false,
+        }
+    }
+
+    fn key<'b>(&self, builder: &'b Builder, nodes: &[Node], at: usize) -> &'b str {
+        builder.str_of(&nodes[self.start + at * 2])
+    }
+
+    fn find(&self, builder: &Builder, nodes: &[Node], key: &str, hash: u64) -> Option<usize> {
+        if self.len > INDEXED {
+            match self.index.get(&hash) {
+                None                                             => return None,
+                Some(&at) if self.key(builder, nodes, at) == key => return Some(at),
+                Some(_)                                          => {},
+            }
+        }
+
+        (0..self.len).find(|&at| self.key(builder, nodes, at) == key)
+    }
+
+    // Add the `key` read from the source. Returns `false` if it's a duplicate
+    // that isn't allowed.
+    fn insert(&mut self, builder: &mut Builder, nodes: &mut Vec<Node>, key: &str, policy: DuplicateKeys) -> bool {
+        let hash = hash_key(key.as_bytes());
+
+        match self.find(builder, nodes, key, hash) {
+            None => {
+                if self.len >= INDEXED {
+                    if self.index.is_empty() {
+                        for at in 0..self.len {
+                            let hash = hash_key(self.key(builder, nodes, at).as_bytes());
+
+                            self.index.entry(hash).or_insert(at);
+                        }
+                    }
+                    self.index.entry(hash).or_insert(self.len);
+                }
+
+                nodes.push(builder.string(key));
+                nodes.push(Node::null());
+
+                self.slot = self.len;
+                self.duplicate = false;
+                self.len += 1;
+            },
+            Some(_) if policy == DuplicateKeys::Error => return false,
+            Some(at) => {
+                if policy == DuplicateKeys::Collect && !self.collected.iter().any(|entry| entry.0 == at) {
+                    let first = std::mem::replace(&mut nodes[self.start + at * 2 + 1], Node::null());
+
+                    self.collected.push((at, vec![first]));
+                }
+
+                self.slot = at;
+                self.duplicate = true;
+            },
+        }
+
+        true
+    }
+
+    // Set the value of the current entry, failing once more values have
+    // been collected for it than `limit` allows.
+    fn set(&mut self, nodes: &mut [Node], value: Node, policy: DuplicateKeys, limit: usize) -> Result<()> {
+        if !self.duplicate {
+            nodes[self.start + self.slot * 2 + 1] = value;
+            return Ok(());
+        }
+
+        match policy {
+            DuplicateKeys::KeepFirst => {},
+            DuplicateKeys::Collect   => {
+                let slot = self.slot;
+
+                if let Some(entry) = self.collected.iter_mut().find(|entry| entry.0 == slot) {
+                    if entry.1.len() >= limit {
+                        return Err(Error::ExceededArrayLengthLimit);
+                    }
+
+                    entry.1.push(value);
+                }
+            },
+            _ => nodes[self.start + self.slot * 2 + 1] = value,
+        }
+
+        Ok(())
+    }
+
+    // Turn the entries on top of the stack into an object node.
+    fn close(self, builder: &mut Builder, nodes: &mut Vec<Node>) -> Node {
+        for (at, mut values) in self.collected {
+            nodes[self.start + at * 2 + 1] = builder.array(&mut values, 0);
+        }
+
+        builder.object(nodes, self.start)
+    }
+}
+
+/// Parse a document into a single arena owned by the returned `Document`,
+/// instead of allocating every array, object and string on its own.
+///
+/// The document reads the same as the `JsonValue` that `parse` would return,
+/// use `to_json_value` on it to get that.
+pub fn parse_document(source: &str) -> Result<Document> {
+    parse_document_with(source, &DEFAULT_OPTIONS)
+}
+
+/// Parse a document into a single arena while enforcing the limits set on
+/// `options`.
+pub fn parse_document_with(source: &str, options: &ParserOptions) -> Result<Document> {
+    Parser::new(source, options).parse_document()
+}
+
+impl<'a> Parser<'a> {
+    fn parse_document(&mut self) -> Result<Document> {
+        if self.length > self.options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        self.find_structurals();
+
+        let policy = self.options.duplicate_keys;
+        let mut builder = Builder::new();
+        let mut nodes = Vec::new();
+        let mut walk = Walk::new(self.options);
+        let mut stack = Vec::with_capacity(3);
+        let mut root = Node::null();
+
+        loop {
+            let value = match self.step(&mut walk)? {
+                Step::Open(false) => {
+                    stack.push(Open::Array(nodes.len()));
+                    continue;
+                },
+                Step::Open(true) => {
+                    stack.push(Open::Object(OpenObject::new(nodes.len())));
+                    continue;
+                },
+                Step::Key(ch, expected) => {
+                    if let Some(Open::Object(object)) = stack.last_mut() {
+                        self.document_key(&mut builder, &mut nodes, &mut walk, object, ch, expected)?;
+                    }
+                    continue;
+                },
+                Step::Close(_) => match stack.pop() {
+                    Some(Open::Array(start))   => builder.array(&mut nodes, start),
+                    Some(Open::Object(object)) => object.close(&mut builder, &mut nodes),
+                    None                       => unreachable!(),
+                },
+                Step::Scalar(ch) => match self.read_scalar(ch)? {
+                    Scalar::String(string)   => builder.string(string),
+                    Scalar::Number(number)   => Node::number(number),
+                    Scalar::Decimal(decimal) => builder.decimal(decimal.as_str()),
+                    Scalar::Boolean(value)   => Node::boolean(value),
+                    Scalar::Null             => Node::null(),
+                },
+                Step::End => return Ok(builder.finish(root)),
+            };
+
+            match stack.last_mut() {
+                None                       => root = value,
+                Some(Open::Array(_))       => nodes.push(value),
+                Some(Open::Object(object)) => object.set(&mut nodes, value, policy, self.options.array_length_limit)?,
+            }
+        }
+    }
+
+    // Read a key starting with `ch` and add it to the `object`.
+    fn document_key(
+        &mut self,
+        builder: &mut Builder,
+        nodes: &mut Vec<Node>,
+        walk: &mut Walk,
+        object: &mut OpenObject,
+        ch: u8,
+        expected: Expected,
+    ) -> Result<()> {
+        self.pin = self.index - 1;
+
+        let key = expect_key!(self, ch, expected);
+        let len = object.len;
+
+        if !object.insert(builder, nodes, key, self.options.duplicate_keys) {
+            return self.duplicate_key(key);
+        }
+
+        self.count_entry(walk, object.len > len)?;
+
+        self.pin = usize::MAX;
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+// Keys that show up more than once in the same object. By default the last
+// value wins, which is what `Object::insert` does anyway, so that's kept as
+// cheap as it was. Other policies need to know whether a key is already there
+// before inserting it, and values of repeated keys don't simply override what
+// is in the entry. Entries of those keys are marked with `DUPLICATE` in the
+// index on the stack, so that `set` can tell them apart.
+//
+// This is also where keys make it into objects, so it's where they go
+// through the interner, if the options have one.
+
+use crate::object::{ Object, Interner, KEY_BUF_LEN };
+use crate::{ Error, JsonValue, Result };
+use super::ParserOptions;
+
+// Flag on an entry index for a key that was already in the object.
+const DUPLICATE: usize = !(usize::MAX >> 1);
+
+/// What to do when an object has the same key more than once.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// use json::{ DuplicateKeys, ParserOptions, Error };
+///
+/// let source = r#"{ "a": 1, "b": 2, "a": 3 }"#;
+///
+/// let parse = |policy| json::parse_with(source, &ParserOptions::new().duplicate_keys(policy));
+///
+/// assert_eq!(parse(DuplicateKeys::KeepLast), Ok(object!{ a: 3, b: 2 }));
+/// assert_eq!(parse(DuplicateKeys::KeepFirst), Ok(object!{ a: 1, b: 2 }));
+/// assert_eq!(parse(DuplicateKeys::Collect), Ok(object!{ a: [1, 3], b: 2 }));
+/// assert_eq!(parse(DuplicateKeys::Error), Err(Error::DuplicateKey {
+///     key: "a".into(),
+///     line: 1,
+///     column: 19,
+/// }));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep the value of the last occurrence, in the place of the first one.
+    /// This is the default.
+    KeepLast,
+
+    /// Keep the value of the first occurrence and ignore all others.
+    KeepFirst,
+
+    /// Put the values of all occurrences into an array, in the order they
+    /// appear in. Keys that only appear once are left as they are. Those
+    /// arrays are held to `ParserOptions::array_length_limit` like any other.
+    Collect,
+
+    /// Fail with `Error::DuplicateKey`, pointing at the second occurrence.
+    Error,
+}
+
+pub(super) struct Duplicates {
+    policy: DuplicateKeys,
+
+    // Entries that have been turned into arrays for `DuplicateKeys::Collect`,
+    // as the depth of their object on the stack and their index in it.
+    collected: Vec<(usize, usize)>,
+
+    // Longest any of those arrays can get
+    array_length_limit: usize,
+
+    interner: Option<Interner>,
+}
+
+impl Duplicates {
+    pub fn new(options: &ParserOptions) -> Self {
+        Duplicates {
+            policy: options.duplicate_keys,
+            collected: Vec::new(),
+            array_length_limit: options.array_length_limit,
+            interner: options.interner.clone(),
+        }
+    }
+
+    // A new object is put at `depth` on the stack, anything collected for
+    // an object that was there before is stale.
+    #[inline]
+    pub fn open(&mut self, depth: usize) {
+        if !self.collected.is_empty() {
+            self.collected.retain(|&(at, _)| at < depth);
+        }
+    }
+
+    // Add `key` to `object`, which is at `depth` on the stack. Returns the
+    // index of the entry its value goes to, which has to be set with `set`,
+    // or `None` if the key is a duplicate that isn't allowed.
+    #[inline]
+    pub fn insert(&mut self, object: &mut Object, depth: usize, key: &str) -> Option<usize> {
+        if self.policy == DuplicateKeys::KeepLast {
+            return Some(self.add(object, key));
+        }
+
+        let index = match object.index_of(key) {
+            Some(index) => index,
+            None        => return Some(self.add(object, key)),
+        };
+
+        match self.policy {
+            DuplicateKeys::Error   => return None,
+            // Second time around, make room for the rest
+            DuplicateKeys::Collect if !self.collected.contains(&(depth, index)) => {
+                let value = object.value_at_mut(index);
+                let first = value.take();
+
+                *value = JsonValue::Array(vec![first]);
+                self.collected.push((depth, index));
+            },
+            _ => {}
+        }
+
+        Some(index | DUPLICATE)
+    }
+
+    #[inline]
+    fn add(&self, object: &mut Object, key: &str) -> usize {
+        match self.interner {
+            // Short keys aren't shared, no need to take the lock for them
+            Some(ref interner) if key.len() > KEY_BUF_LEN => {
+                object.insert_interned_index(&interner.intern(key), JsonValue::Null)
+            },
+            _ => object.insert_index(key, JsonValue::Null),
+        }
+    }
+
+    // Set the value of the entry at `index`, as returned from `insert`.
+    #[inline]
+    pub fn set(&self, object: &mut Object, index: usize, value: JsonValue) -> Result<()> {
+        if index & DUPLICATE == 0 {
+            object.override_at(index, value);
+            return Ok(());
+        }
+
+        if self.policy == DuplicateKeys::Collect {
+            if let JsonValue::Array(ref mut values) = *object.value_at_mut(index ^ DUPLICATE) {
+                if values.len() >= self.array_length_limit {
+                    return Err(Error::ExceededArrayLengthLimit);
+                }
+
+                values.push(value);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+// Raw bytes in whatever encoding they come in. RFC 8259 wants JSON that's
+// passed between systems to be UTF-8, but RFC 4627 allowed UTF-16 and
+// UTF-32 as well, told apart by where the zero bytes are in the first four,
+// since the first two characters are always ASCII. Windows also likes to
+// start files with a byte order mark, which RFC 8259 allows parsers to
+// ignore.
+//
+// UTF-8 is parsed in place, anything else is transcoded to UTF-8 first.
+// Positions in errors coming from the parser are then in the transcoded
+// text, lines and columns are the same either way, but offsets are mapped
+// back to the bytes they came from.
+
+use std::char::decode_utf16;
+use std::fmt;
+
+use crate::{ Error, JsonValue, Result };
+use super::{ Parser, ParserOptions, DEFAULT_OPTIONS };
+
+/// Encodings that `parse_bytes_detect` can tell apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, with or without a byte order mark.
+    Utf8,
+    /// UTF-16, little endian.
+    Utf16Le,
+    /// UTF-16, big endian.
+    Utf16Be,
+    /// UTF-32, little endian.
+    Utf32Le,
+    /// UTF-32, big endian.
+    Utf32Be,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Encoding::*;
+
+        match *self {
+            Utf8    => f.write_str("UTF-8"),
+            Utf16Le => f.write_str("UTF-16LE"),
+            Utf16Be => f.write_str("UTF-16BE"),
+            Utf32Le => f.write_str("UTF-32LE"),
+            Utf32Be => f.write_str("UTF-32BE"),
+        }
+    }
+}
+
+impl Encoding {
+    /// Work out the encoding of a JSON document, along with the length of
+    /// the byte order mark it starts with, if any.
+    ///
+    /// ```
+    /// use json::Encoding;
+    ///
+    /// assert_eq!(Encoding::detect(b"\xEF\xBB\xBF[1]"), (Encoding::Utf8, 3));
+    /// assert_eq!(Encoding::detect(b"[\x001\x00]\x00"), (Encoding::Utf16Le, 0));
+    /// assert_eq!(Encoding::detect(b"\x00\x00\x00["), (Encoding::Utf32Be, 0));
+    /// assert_eq!(Encoding::detect(b"[1]"), (Encoding::Utf8, 0));
+    /// ```
+    pub fn detect(source: &[u8]) -> (Encoding, usize) {
+        use Encoding::*;
+
+        match *source {
+            [0xEF, 0xBB, 0xBF, ..]       => (Utf8, 3),
+            [0x00, 0x00, 0xFE, 0xFF, ..] => (Utf32Be, 4),
+            // Could also be UTF-16LE followed by a NUL, which JSON can't start with
+            [0xFF, 0xFE, 0x00, 0x00, ..] => (Utf32Le, 4),
+            [0xFE, 0xFF, ..]             => (Utf16Be, 2),
+            [0xFF, 0xFE, ..]             => (Utf16Le, 2),
+
+            // No byte order mark, RFC 4627 section 3
+            [0x00, 0x00, 0x00, _, ..]    => (Utf32Be, 0),
+            [_, 0x00, 0x00, 0x00, ..]    => (Utf32Le, 0),
+            [0x00, _, ..]                => (Utf16Be, 0),
+            [_, 0x00, ..]                => (Utf16Le, 0),
+            _                            => (Utf8, 0),
+        }
+    }
+
+    // Bytes taken up by a single code unit
+    fn unit(self) -> usize {
+        match self {
+            Encoding::Utf8                        => 1,
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            Encoding::Utf32Le | Encoding::Utf32Be => 4,
+        }
+    }
+
+    // Transcode `bytes` to UTF-8. They come after a byte order mark of
+    // `bom` bytes, which offsets in errors take into account.
+    fn decode(self, bytes: &[u8], bom: usize) -> Result<String> {
+        let invalid = |offset| Error::InvalidEncoding { encoding: self, offset };
+        let chunks = bytes.chunks_exact(self.unit());
+        let rest = chunks.remainder().len();
+        let mut text = String::with_capacity(bytes.len());
+        let mut offset = bom;
+
+        match self {
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let units = chunks.map(|unit| match self {
+                    Encoding::Utf16Le => u16::from_le_bytes([unit[0], unit[1]]),
+                    _                 => u16::from_be_bytes([unit[0], unit[1]]),
+                });
+
+                for ch in decode_utf16(units) {
+                    let ch = ch.map_err(|_| invalid(offset))?;
+
+                    text.push(ch);
+                    offset += ch.len_utf16() * 2;
+                }
+            },
+            _ => {
+                for unit in chunks {
+                    let unit = [unit[0], unit[1], unit[2], unit[3]];
+                    let code = match self {
+                        Encoding::Utf32Le => u32::from_le_bytes(unit),
+                        _                 => u32::from_be_bytes(unit),
+                    };
+
+                    text.push(std::char::from_u32(code).ok_or_else(|| invalid(offset))?);
+                    offset += 4;
+                }
+            },
+        }
+
+        // Half a code unit at the end
+        if rest != 0 {
+            return Err(invalid(offset));
+        }
+
+        Ok(text)
+    }
+
+    // Offset in the source of the byte at `at` in the transcoded `text`.
+    fn source_offset(self, text: &str, bom: usize, at: usize) -> usize {
+        let units: usize = text
+            .char_indices()
+            .take_while(|&(index, _)| index < at)
+            .map(|(_, ch)| match self {
+                Encoding::Utf16Le | Encoding::Utf16Be => ch.len_utf16(),
+                _                                     => 1,
+            })
+            .sum();
+
+        bom + units * self.unit()
+    }
+}
+
+/// Parse a JSON document from raw bytes in any of the encodings RFC 4627
+/// allows, UTF-8, UTF-16 or UTF-32, in either byte order. The encoding is
+/// detected from the byte order mark the source starts with, which is
+/// skipped, or failing that, from the zero bytes in the first two
+/// characters, which are always ASCII in JSON.
+///
+/// Anything that isn't UTF-8 is transcoded to it first, and bytes that
+/// aren't valid in the detected encoding are reported as
+/// `Error::InvalidEncoding`. Offsets in all errors point into `source`.
+///
+/// ```
+/// # use json::{ Encoding, Error };
+/// let utf16: Vec<u8> = "\u{FEFF}{\"ok\": \"✓\"}".encode_utf16().flat_map(u16::to_le_bytes).collect();
+///
+/// assert_eq!(json::parse_bytes_detect(&utf16).unwrap()["ok"], "✓");
+/// assert_eq!(json::parse_bytes_detect(b"\xEF\xBB\xBF[1]").unwrap()[0], 1);
+///
+/// assert_eq!(json::parse_bytes_detect(b"[\x00\x00\xD8]\x00"), Err(Error::InvalidEncoding {
+///     encoding: Encoding::Utf16Le,
+///     offset: 2,
+/// }));
+/// ```
+pub fn parse_bytes_detect(source: &[u8]) -> Result<JsonValue> {
+    parse_bytes_detect_with(source, &DEFAULT_OPTIONS)
+}
+
+/// Parse a JSON document from raw bytes in any encoding RFC 4627 allows,
+/// while enforcing the limits set on `options`.
+pub fn parse_bytes_detect_with(source: &[u8], options: &ParserOptions) -> Result<JsonValue> {
+    let (encoding, bom) = Encoding::detect(source);
+    let bytes = &source[bom..];
+
+    if encoding == Encoding::Utf8 {
+        return Parser::from_bytes(bytes, options, true).parse().map_err(|error| {
+            map_offset(error, |at| bom + at)
+        });
+    }
+
+    let text = encoding.decode(bytes, bom)?;
+
+    Parser::new(&text, options).parse().map_err(|error| {
+        map_offset(error, |at| encoding.source_offset(&text, bom, at))
+    })
+}
+
+fn map_offset<F: FnOnce(usize) -> usize>(mut error: Error, map: F) -> Error {
+    match error {
+        Error::UnexpectedCharacter { ref mut offset, .. } |
+        Error::InvalidUtf8 { ref mut offset, .. } |
+        Error::LoneSurrogate { ref mut offset, .. } => *offset = map(*offset),
+        _ => {},
+    }
+
+    error
+}
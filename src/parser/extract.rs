@@ -0,0 +1,270 @@
+// Getting a few values out of a document by their JSON Pointers, in a single
+// pass. Arrays and objects that some pointer goes into are walked member by
+// member and entry by entry, with a single walk through the whole source,
+// everything else is only validated on the way, and values the pointers
+// lead to are handed over to `Parser::parse_value`. The document is checked as a whole all the same,
+// so whatever `parse` would fail with, this fails with too.
+//
+// Pointers that go into an array or object which turns out to be something
+// else, or which is missing what they point to, just don't find anything.
+//
+// Duplicate keys follow the policy in the options. For `KeepLast` a later
+// entry starts over whatever was found in an earlier one. `Collect` is the
+// odd one out, it's only known once the object is done whether the values
+// of a key end up in an array, so for keys that pointers go through the
+// whole values are parsed, and pointers are followed into them afterwards.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::{ str, slice };
+
+use crate::{ Error, Expected, JsonValue, Result };
+use super::{ Parser, ParserOptions, DuplicateKeys, ALLOWED, DEFAULT_OPTIONS };
+use super::spans::{ unescape, array_index };
+use super::walk::{ Walk, Step };
+
+// Reference tokens of each pointer
+type Pointers<'p> = [Vec<Cow<'p, str>>];
+
+/// Get the values at the given JSON Pointers out of a document, in the order
+/// the pointers are in. `None` for pointers that don't lead to anything.
+///
+/// The document is gone through only once, and only the values that are
+/// asked for are built, everything else is skipped over. It's still checked
+/// in full, a document that `parse` fails on fails here the same way.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// let record = r#"{
+///     "user": { "id": 42, "name": "Ann" },
+///     "items": [{ "price": 9.99 }, { "price": 0.5 }],
+///     "padding": ["imagine", "lots", "of", "data", "here"]
+/// }"#;
+///
+/// let values = json::extract(record, &["/user/id", "/items/0", "/items/9"]).unwrap();
+///
+/// assert_eq!(values, [Some(42.into()), Some(object!{ price: 9.99 }), None]);
+/// # }
+/// ```
+pub fn extract(source: &str, pointers: &[&str]) -> Result<Vec<Option<JsonValue>>> {
+    extract_with(source, pointers, &DEFAULT_OPTIONS)
+}
+
+/// Get the values at the given JSON Pointers out of a document, while
+/// enforcing the limits set on `options`.
+pub fn extract_with(source: &str, pointers: &[&str], options: &ParserOptions) -> Result<Vec<Option<JsonValue>>> {
+    Parser::new(source, options).extract(pointers)
+}
+
+// Follow the `tokens` into a value that's been parsed already.
+fn lookup<'v>(value: &'v JsonValue, tokens: &[Cow<str>]) -> Option<&'v JsonValue> {
+    tokens.iter().try_fold(value, |value, token| match *value {
+        JsonValue::Object(ref object) => object.get(token),
+        JsonValue::Array(ref array)   => array.get(array_index(token)?),
+        _                             => None,
+    })
+}
+
+// Part of the sorted `wanted` pointers with `token` after following `depth`
+// of their tokens.
+fn matching<'w>(tokens: &Pointers, wanted: &'w [usize], depth: usize, token: &str) -> &'w [usize] {
+    let start = wanted.partition_point(|&at| &*tokens[at][depth] < token);
+    let len = wanted[start..].partition_point(|&at| tokens[at][depth] == token);
+
+    &wanted[start..start + len]
+}
+
+impl<'a> Parser<'a> {
+    fn extract(&mut self, pointers: &[&str]) -> Result<Vec<Option<JsonValue>>> {
+        if self.length > self.options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        self.find_structurals();
+
+        let mut tokens = Vec::with_capacity(pointers.len());
+        let mut wanted = Vec::with_capacity(pointers.len());
+
+        for (at, pointer) in pointers.iter().enumerate() {
+            // Anything but an empty pointer has to start with `/`
+            match pointer.strip_prefix('/') {
+                Some(pointer) => tokens.push(pointer.split('/').map(unescape).collect()),
+                None          => tokens.push(Vec::new()),
+            }
+
+            if pointer.is_empty() || pointer.starts_with('/') {
+                wanted.push(at);
+            }
+        }
+
+        // Sorted, so that pointers going the same way are next to each other
+        wanted.sort_by(|&a, &b| tokens[a].cmp(&tokens[b]));
+
+        let mut found = vec![None; pointers.len()];
+        let mut walk = Walk::new(self.options);
+        let step = self.step(&mut walk)?;
+
+        self.extract_value(&mut walk, step, &tokens, &wanted, 0, &mut found)?;
+
+        // Whatever is left is only checked
+        self.validate_rest(&mut walk)?;
+
+        Ok(found)
+    }
+
+    // Go through the value that `step` of the `walk` starts, which the
+    // `wanted` pointers lead to or into, after following `depth` of their
+    // tokens. `wanted` is sorted by the tokens that are left.
+    fn extract_value(
+        &mut self,
+        walk: &mut Walk,
+        step: Step,
+        tokens: &Pointers,
+        wanted: &[usize],
+        depth: usize,
+        found: &mut [Option<JsonValue>],
+    ) -> Result<()> {
+        // The value itself is wanted, might as well take all of it
+        if wanted.iter().any(|&at| tokens[at].len() == depth) {
+            self.hand_over(walk);
+
+            let value = self.parse_value(depth)?;
+
+            for &at in wanted {
+                found[at] = lookup(&value, &tokens[at][depth..]).cloned();
+            }
+
+            return Ok(());
+        }
+
+        match step {
+            Step::Open(false) => self.extract_array(walk, tokens, wanted, depth, found),
+            Step::Open(true)  => self.extract_object(walk, tokens, wanted, depth, found),
+            // Nothing to go into
+            step              => self.validate_member(walk, step),
+        }
+    }
+
+    fn extract_array(
+        &mut self,
+        walk: &mut Walk,
+        tokens: &Pointers,
+        wanted: &[usize],
+        depth: usize,
+        found: &mut [Option<JsonValue>],
+    ) -> Result<()> {
+        // Pointers going into each member, in order of the members
+        let mut members = Vec::new();
+        let mut rest = wanted;
+
+        while let Some(&first) = rest.first() {
+            let inside = matching(tokens, rest, depth, &tokens[first][depth]);
+
+            if let Some(index) = array_index(&tokens[first][depth]) {
+                members.push((index, inside));
+            }
+
+            rest = &rest[inside.len()..];
+        }
+
+        members.sort_by_key(|member| member.0);
+
+        let mut members = members.into_iter().peekable();
+        let mut len = 0;
+
+        loop {
+            let step = match self.step(walk)? {
+                Step::Close(_) => return Ok(()),
+                step           => step,
+            };
+
+            match members.next_if(|member| member.0 == len) {
+                Some((_, inside)) => self.extract_value(walk, step, tokens, inside, depth + 1, found)?,
+                None              => self.validate_member(walk, step)?,
+            }
+
+            len += 1;
+        }
+    }
+
+    fn extract_object(
+        &mut self,
+        walk: &mut Walk,
+        tokens: &Pointers,
+        wanted: &[usize],
+        depth: usize,
+        found: &mut [Option<JsonValue>],
+    ) -> Result<()> {
+        let policy = self.options.duplicate_keys;
+
+        // Keys pointers went through, for `DuplicateKeys::KeepFirst` when
+        // the walk doesn't keep them
+        let mut followed = HashSet::new();
+
+        // Values of keys pointers go through, for `DuplicateKeys::Collect`
+        let mut collected: Vec<(String, Vec<JsonValue>)> = Vec::new();
+
+        // Anything but a key is the end of the object
+        while let Step::Key(ch, expected) = self.step(walk)? {
+            self.pin = self.index - 1;
+
+            let key = expect_key!(self, ch, expected);
+            let inside = matching(tokens, wanted, depth, key);
+            let new = self.count_key(walk, key)?;
+            let seen = match policy {
+                DuplicateKeys::KeepFirst if !inside.is_empty() && !self.options.keeps_keys() => {
+                    !followed.insert(key.to_owned())
+                },
+                _ => !new,
+            };
+
+            let key = if policy == DuplicateKeys::Collect && !inside.is_empty() {
+                Some(key.to_owned())
+            } else {
+                None
+            };
+
+            self.pin = usize::MAX;
+
+            let step = self.step(walk)?;
+
+            match key {
+                _ if inside.is_empty() || (seen && policy == DuplicateKeys::KeepFirst) => {
+                    self.validate_member(walk, step)?;
+                },
+                Some(key) => {
+                    self.hand_over(walk);
+
+                    let value = self.parse_value(depth + 1)?;
+
+                    match collected.iter_mut().find(|entry| entry.0 == key) {
+                        Some(entry) => entry.1.push(value),
+                        None        => collected.push((key, vec![value])),
+                    }
+                },
+                None => {
+                    // Whatever an earlier entry had is gone
+                    for &at in inside {
+                        found[at] = None;
+                    }
+
+                    self.extract_value(walk, step, tokens, inside, depth + 1, found)?;
+                },
+            }
+        }
+
+        for (key, mut values) in collected {
+            let value = match values.len() {
+                1 => values.pop().expect("Must have a value"),
+                _ => JsonValue::Array(values),
+            };
+
+            for &at in matching(tokens, wanted, depth, &key) {
+                found[at] = lookup(&value, &tokens[at][depth + 1..]).cloned();
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,408 @@
+// Lazy documents. The whole source is validated up front, without building
+// anything, after which every value is just the text it was parsed from.
+// Arrays and objects are only split up into their members and entries once
+// something asks for them, which takes skipping over each of them, and
+// values are only parsed into a `JsonValue` once they're asked for. Both
+// are kept, so that references to them can be handed out.
+//
+// Splitting up walks through the array or object one step at a time, but
+// skipping over its members doesn't check anything, the source is known to
+// be fine by then. It only has to find where each value ends.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Index;
+use std::sync::OnceLock;
+use std::{ fmt, mem, str, slice };
+
+use crate::number::Number;
+use crate::{ Error, Expected, JsonValue, Result };
+use super::{ Parser, ParserOptions, DuplicateKeys, ALLOWED, DEFAULT_OPTIONS };
+use super::borrowed::borrow;
+use super::walk::{ Walk, Step };
+
+/// A value in a document that's only parsed as far as it's looked into.
+///
+/// Reading a few fields out of a large document only splits up the objects
+/// and arrays on the way to them, and only parses the fields themselves,
+/// everything else is skipped over without building anything. Anything
+/// that's been read is kept, so reading it again is free.
+///
+/// Indexing works the same way it does for `JsonValue`, anything missing
+/// is `null`.
+///
+/// ```
+/// let body = r#"{
+///     "user": { "id": 42, "name": "Ann", "roles": ["admin"] },
+///     "items": [{ "price": 9.99 }, { "price": 0.5 }],
+///     "padding": "imagine lots of data here"
+/// }"#;
+///
+/// let data = json::parse_lazy(body).unwrap();
+///
+/// assert_eq!(data["user"]["id"].as_u64(), Some(42));
+/// assert_eq!(data["items"][1]["price"].as_f64(), Some(0.5));
+/// assert!(data["nope"][3].is_null());
+///
+/// // Values are the text they came from until asked for
+/// assert_eq!(data["user"]["roles"].raw(), r#"["admin"]"#);
+/// ```
+pub struct LazyValue<'a> {
+    // Text of the value, without any whitespace around it. Empty for arrays
+    // made up of values collected for `DuplicateKeys::Collect`.
+    source: &'a str,
+    options: &'a ParserOptions,
+
+    // Members or entries, once split up
+    children: OnceLock<Children<'a>>,
+
+    // The value itself, once parsed
+    value: OnceLock<JsonValue>,
+
+    // What indexing returns for anything that's not there
+    missing: OnceLock<Box<LazyValue<'a>>>,
+}
+
+enum Children<'a> {
+    None,
+    Members(Vec<LazyValue<'a>>),
+
+    // Keys are unique, duplicates are dealt with the same way they are for
+    // `JsonValue`. Each key is indexed, so that looking one up doesn't take
+    // going through all of them.
+    Entries(Vec<(Cow<'a, str>, LazyValue<'a>)>, HashMap<Cow<'a, str>, usize>),
+}
+
+/// Parse a document lazily, see `LazyValue`. The whole `source` is checked
+/// right away, any errors are the same ones `parse` would return.
+pub fn parse_lazy(source: &str) -> Result<LazyValue<'_>> {
+    parse_lazy_with(source, &DEFAULT_OPTIONS)
+}
+
+/// Parse a document lazily while enforcing the limits set on `options`.
+pub fn parse_lazy_with<'a>(source: &'a str, options: &'a ParserOptions) -> Result<LazyValue<'a>> {
+    Parser::new(source, options).validate(true)?;
+
+    // Whitespace and comments around the value are left out
+    let mut parser = Parser::new(source, options);
+    let mut walk = Walk::value(options, 0);
+    let step = parser.step(&mut walk)?;
+    let source = parser.skip_lazy(source, &mut walk, step)?;
+
+    Ok(LazyValue::new(source, options))
+}
+
+impl<'a> LazyValue<'a> {
+    fn new(source: &'a str, options: &'a ParserOptions) -> Self {
+        LazyValue {
+            source,
+            options,
+            children: OnceLock::new(),
+            value: OnceLock::new(),
+            missing: OnceLock::new(),
+        }
+    }
+
+    fn collected(members: Vec<LazyValue<'a>>, options: &'a ParserOptions) -> Self {
+        LazyValue {
+            source: "",
+            options,
+            children: OnceLock::from(Children::Members(members)),
+            value: OnceLock::new(),
+            missing: OnceLock::new(),
+        }
+    }
+
+    /// The text of the value, as found in the source.
+    ///
+    /// Values collected from duplicate keys, with `DuplicateKeys::Collect`,
+    /// have no text of their own, and are an empty string.
+    pub fn raw(&self) -> &'a str {
+        self.source
+    }
+
+    /// Parse the value, or get it if it's been parsed already.
+    pub fn value(&self) -> &JsonValue {
+        self.value.get_or_init(|| {
+            if self.source.is_empty() {
+                return JsonValue::Array(self.members().map(|member| member.value().clone()).collect());
+            }
+
+            super::parse_with(self.source, self.options).unwrap_or(JsonValue::Null)
+        })
+    }
+
+    fn first(&self) -> u8 {
+        self.source.as_bytes().first().cloned().unwrap_or(b'[')
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self.first(), b'"' | b'\'')
+    }
+
+    pub fn is_number(&self) -> bool {
+        self.options.starts_number(self.first())
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        matches!(self.first(), b't' | b'f')
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.first() == b'n'
+    }
+
+    pub fn is_object(&self) -> bool {
+        self.first() == b'{'
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.first() == b'['
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        if !self.is_string() {
+            return None;
+        }
+
+        self.value().as_str()
+    }
+
+    pub fn as_number(&self) -> Option<Number> {
+        if !self.is_number() {
+            return None;
+        }
+
+        self.value().as_number()
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(Into::into)
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        if !self.is_number() {
+            return None;
+        }
+
+        self.value().as_u64()
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        if !self.is_number() {
+            return None;
+        }
+
+        self.value().as_i64()
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.first() {
+            b't' => Some(true),
+            b'f' => Some(false),
+            _    => None,
+        }
+    }
+
+    /// Get the value of the entry with the `key`, if this is an object that
+    /// has it.
+    pub fn get(&self, key: &str) -> Option<&LazyValue<'a>> {
+        match *self.children() {
+            Children::Entries(ref entries, ref index) => index.get(key).map(|&at| &entries[at].1),
+            _                                         => None,
+        }
+    }
+
+    /// Checks that self is an object and that it has an entry with the `key`.
+    pub fn has_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns length of array or object (number of keys), defaults to `0` for
+    /// other types.
+    pub fn len(&self) -> usize {
+        match *self.children() {
+            Children::Members(ref members)    => members.len(),
+            Children::Entries(ref entries, _) => entries.len(),
+            Children::None                    => 0,
+        }
+    }
+
+    /// Checks whether the value is empty, same as `JsonValue::is_empty`.
+    pub fn is_empty(&self) -> bool {
+        // Arrays and objects don't need to be parsed for that
+        match self.first() {
+            b'[' | b'{' => self.len() == 0,
+            _           => self.value().is_empty(),
+        }
+    }
+
+    /// Works on arrays - returns an iterator over members. Will return an
+    /// empty iterator if called on non-array types.
+    pub fn members(&self) -> slice::Iter<'_, LazyValue<'a>> {
+        match *self.children() {
+            Children::Members(ref members) => members.iter(),
+            _                              => [].iter(),
+        }
+    }
+
+    /// Works on objects - returns an iterator over key value pairs. Will
+    /// return an empty iterator if called on non-object types.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &LazyValue<'a>)> {
+        let entries: &[_] = match *self.children() {
+            Children::Entries(ref entries, _) => entries,
+            _                                 => &[],
+        };
+
+        entries.iter().map(|entry| (entry.0.as_ref(), &entry.1))
+    }
+
+    fn children(&self) -> &Children<'a> {
+        self.children.get_or_init(|| {
+            if !matches!(self.first(), b'[' | b'{') {
+                return Children::None;
+            }
+
+            Parser::new(self.source, self.options)
+                .split_lazy(self.source)
+                .map(|children| self.dedupe(children))
+                .unwrap_or(Children::None)
+        })
+    }
+
+    // Deal with duplicate keys the same way `parse` does, and index the
+    // keys that are left.
+    fn dedupe(&self, children: Children<'a>) -> Children<'a> {
+        let split = match children {
+            Children::Entries(split, _) => split,
+            children                    => return children,
+        };
+
+        let policy = self.options.duplicate_keys;
+        let mut index = HashMap::with_capacity(split.len());
+        let mut entries: Vec<(Cow<'a, str>, LazyValue<'a>)> = Vec::with_capacity(split.len());
+
+        for (key, value) in split {
+            let at = match index.get(&key) {
+                Some(&at) => at,
+                None      => {
+                    index.insert(key.clone(), entries.len());
+                    entries.push((key, value));
+                    continue;
+                }
+            };
+
+            let entry = &mut entries[at].1;
+
+            match policy {
+                DuplicateKeys::KeepFirst => {},
+                DuplicateKeys::Collect   => {
+                    // Validating the source has already held these to the
+                    // limit on arrays
+                    if !entry.source.is_empty() {
+                        let first = mem::replace(entry, LazyValue::collected(Vec::new(), self.options));
+
+                        entry.push_collected(first);
+                    }
+
+                    entry.push_collected(value);
+                },
+                _ => *entry = value,
+            }
+        }
+
+        Children::Entries(entries, index)
+    }
+
+    fn missing(&self) -> &LazyValue<'a> {
+        self.missing.get_or_init(|| Box::new(LazyValue::new("null", self.options)))
+    }
+
+    fn push_collected(&mut self, value: LazyValue<'a>) {
+        if let Some(Children::Members(members)) = self.children.get_mut() {
+            members.push(value);
+        }
+    }
+}
+
+impl<'a> fmt::Debug for LazyValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value.get() {
+            Some(value) => fmt::Debug::fmt(value, f),
+            None        => write!(f, "LazyValue({})", self.source),
+        }
+    }
+}
+
+/// Members of arrays can be read by their index, anything out of bounds
+/// is `null`.
+impl<'a> Index<usize> for LazyValue<'a> {
+    type Output = LazyValue<'a>;
+
+    fn index(&self, index: usize) -> &LazyValue<'a> {
+        self.members().nth(index).unwrap_or_else(|| self.missing())
+    }
+}
+
+/// Values of object entries can be read by their key, anything missing is
+/// `null`.
+impl<'a> Index<&str> for LazyValue<'a> {
+    type Output = LazyValue<'a>;
+
+    fn index(&self, key: &str) -> &LazyValue<'a> {
+        self.get(key).unwrap_or_else(|| self.missing())
+    }
+}
+
+impl<'a> Parser<'a> {
+    // Split the array or object at the start of the `source` up into its
+    // members or entries.
+    fn split_lazy(&mut self, source: &'a str) -> Result<Children<'a>> {
+        let options = self.options;
+        let mut walk = Walk::value(options, 0);
+        let object = self.step(&mut walk)? == Step::Open(true);
+
+        let mut members = Vec::new();
+        let mut entries = Vec::new();
+        let mut key = None;
+
+        loop {
+            match self.step(&mut walk)? {
+                Step::Key(ch, expected) => key = Some(borrow(source, expect_key!(self, ch, expected))),
+                Step::Close(_)          => break,
+                step                    => {
+                    let value = LazyValue::new(self.skip_lazy(source, &mut walk, step)?, options);
+
+                    match key.take() {
+                        Some(key) => entries.push((key, value)),
+                        None      => members.push(value),
+                    }
+                },
+            }
+        }
+
+        // Indexed once they've been deduplicated
+        if object {
+            Ok(Children::Entries(entries, HashMap::new()))
+        } else {
+            Ok(Children::Members(members))
+        }
+    }
+
+    // Skip over the value the last `step` of the `walk` started, and get
+    // its text.
+    fn skip_lazy(&mut self, source: &'a str, walk: &mut Walk, step: Step) -> Result<&'a str> {
+        match step {
+            Step::Open(_)            => self.skip_open(walk)?,
+            Step::Scalar(ch @ b'"')  => self.skip_string(ch)?,
+            Step::Scalar(ch @ b'\'') => self.skip_string(ch)?,
+            _ => {
+                while !self.is_eof() && !matches!(self.read_byte(), b',' | b']' | b'}' | b'/' | 9 ..= 13 | b' ') {
+                    self.bump();
+                }
+            },
+        }
+
+        Ok(&source[walk.start()..self.index])
+    }
+}
@@ -0,0 +1,1938 @@
+// HERE BE DRAGONS!
+// ================
+//
+// Making a fast parser is hard. This is a _not so naive_ implementation of
+// recursive descent that does almost nothing. _There is no backtracking_, the
+// whole parsing is 100% predictive, even though it's not BNF, and will have
+// linear performance based on the length of the source!
+//
+// There is a lot of macros here! Like, woah! This is mostly due to the fact
+// that Rust isn't very cool about optimizing inlined functions that return
+// a `Result` type. Since different functions will have different `Result`
+// signatures, the `try!` macro will always have to repackage our results.
+// With macros those issues don't exist, the macro will return an unpackaged
+// result - whatever it is - and if we ever stumble upon the error, we can
+// return an `Err` without worrying about the exact signature of `Result`.
+//
+// This makes for some ugly code, but it is faster. Hopefully in the future
+// with MIR support the compiler will get smarter about this.
+
+use std::{str, slice, io};
+use std::marker::PhantomData;
+use crate::object::{ Object, Interner };
+use crate::number::{ Number, Decimal };
+use crate::{JsonValue, Error, Expected, Result};
+
+// This is not actual max precision, but a threshold at which number parsing
+// kicks into checked math.
+const MAX_PRECISION: u64 = 576460752303423500;
+
+
+// How many nested Objects/Arrays are allowed to be parsed by default
+const DEPTH_LIMIT: usize = 512;
+
+
+// Options used by `parse`, all limits except depth are disabled
+static DEFAULT_OPTIONS: ParserOptions = ParserOptions::new();
+
+
+/// Options for `parse_with`, used to put a cap on the resources that parsing
+/// an untrusted document can consume. Each limit that is exceeded produces
+/// its own `Error` variant.
+///
+/// Options can also allow for relaxed syntax, see `ParserOptions::json5`.
+/// Each extension can be switched on by itself, none are allowed by default.
+///
+/// ```
+/// # use json::{ ParserOptions, Error };
+/// let options = ParserOptions::new()
+///     .depth_limit(2)
+///     .array_length_limit(3);
+///
+/// assert!(json::parse_with("[[1, 2, 3]]", &options).is_ok());
+/// assert_eq!(json::parse_with("[[[1]]]", &options), Err(Error::ExceededDepthLimit));
+/// assert_eq!(json::parse_with("[1, 2, 3, 4]", &options), Err(Error::ExceededArrayLengthLimit));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    depth_limit: usize,
+    size_limit: usize,
+    string_length_limit: usize,
+    object_entries_limit: usize,
+    array_length_limit: usize,
+    comments: bool,
+    trailing_commas: bool,
+    single_quotes: bool,
+    unquoted_keys: bool,
+    hex_numbers: bool,
+    leading_plus: bool,
+    leading_decimal_point: bool,
+    lossless_numbers: bool,
+    duplicate_keys: DuplicateKeys,
+    lone_surrogates: LoneSurrogates,
+    interner: Option<Interner>,
+}
+
+impl ParserOptions {
+    /// Create options matching the behavior of `parse`: nesting is limited
+    /// to 512 levels, nothing else is limited.
+    pub const fn new() -> Self {
+        ParserOptions {
+            depth_limit: DEPTH_LIMIT,
+            size_limit: usize::MAX,
+            string_length_limit: usize::MAX,
+            object_entries_limit: usize::MAX,
+            array_length_limit: usize::MAX,
+            comments: false,
+            trailing_commas: false,
+            single_quotes: false,
+            unquoted_keys: false,
+            hex_numbers: false,
+            leading_plus: false,
+            leading_decimal_point: false,
+            lossless_numbers: false,
+            duplicate_keys: DuplicateKeys::KeepLast,
+            lone_surrogates: LoneSurrogates::Error,
+            interner: None,
+        }
+    }
+
+    /// Create options that allow all of the relaxed syntax supported, which
+    /// covers the parts of JSON5 most commonly found in hand written files.
+    /// Limits are the same as with `new`.
+    ///
+    /// ```
+    /// # #[macro_use] extern crate json;
+    /// # fn main() {
+    /// let source = "
+    ///     // Comments are fine
+    ///     {
+    ///         unquoted: 'single quoted',
+    ///         hex: 0xFF,
+    ///         numbers: [+1, .5, -.5,],
+    ///     }
+    /// ";
+    ///
+    /// assert_eq!(json::parse_with(source, &json::ParserOptions::json5()).unwrap(), object!{
+    ///     unquoted: "single quoted",
+    ///     hex: 255,
+    ///     numbers: [1, 0.5, -0.5],
+    /// });
+    /// # }
+    /// ```
+    pub const fn json5() -> Self {
+        let mut options = ParserOptions::new();
+
+        options.comments = true;
+        options.trailing_commas = true;
+        options.single_quotes = true;
+        options.unquoted_keys = true;
+        options.hex_numbers = true;
+        options.leading_plus = true;
+        options.leading_decimal_point = true;
+        options
+    }
+
+    /// How many nested objects and arrays are allowed.
+    pub fn depth_limit(mut self, limit: usize) -> Self {
+        self.depth_limit = limit;
+        self
+    }
+
+    /// Maximum size of the whole document in bytes.
+    pub fn size_limit(mut self, limit: usize) -> Self {
+        self.size_limit = limit;
+        self
+    }
+
+    /// Maximum length of a string, including object keys, in bytes after
+    /// escapes have been decoded.
+    pub fn string_length_limit(mut self, limit: usize) -> Self {
+        self.string_length_limit = limit;
+        self
+    }
+
+    /// Maximum number of entries in a single object.
+    pub fn object_entries_limit(mut self, limit: usize) -> Self {
+        self.object_entries_limit = limit;
+        self
+    }
+
+    /// Maximum number of elements in a single array.
+    pub fn array_length_limit(mut self, limit: usize) -> Self {
+        self.array_length_limit = limit;
+        self
+    }
+
+    /// Allow `// line` and `/* block */` comments anywhere whitespace
+    /// is allowed.
+    pub fn comments(mut self, allow: bool) -> Self {
+        self.comments = allow;
+        self
+    }
+
+    /// Allow a comma after the last element of an array or object.
+    ///
+    /// ```
+    /// # #[macro_use] extern crate json;
+    /// # fn main() {
+    /// // JSONC, as used by some editors for their configuration files
+    /// let options = json::ParserOptions::new()
+    ///     .comments(true)
+    ///     .trailing_commas(true);
+    ///
+    /// let source = r#"{
+    ///     /* Nothing fancy */
+    ///     "files": ["a.txt", "b.txt",], // trailing
+    /// }"#;
+    ///
+    /// assert_eq!(json::parse_with(source, &options).unwrap(), object!{
+    ///     files: ["a.txt", "b.txt"],
+    /// });
+    /// assert!(json::parse_with("{ key: 1 }", &options).is_err());
+    /// # }
+    /// ```
+    pub fn trailing_commas(mut self, allow: bool) -> Self {
+        self.trailing_commas = allow;
+        self
+    }
+
+    /// Allow strings, including keys, in single quotes. Double quotes don't
+    /// need to be escaped inside of them, and `\'` is a valid escape.
+    pub fn single_quotes(mut self, allow: bool) -> Self {
+        self.single_quotes = allow;
+        self
+    }
+
+    /// Allow object keys without quotes, made of ASCII letters, digits,
+    /// `_` and `$`, not starting with a digit.
+    pub fn unquoted_keys(mut self, allow: bool) -> Self {
+        self.unquoted_keys = allow;
+        self
+    }
+
+    /// Allow hexadecimal numbers such as `0xFF`.
+    pub fn hex_numbers(mut self, allow: bool) -> Self {
+        self.hex_numbers = allow;
+        self
+    }
+
+    /// Allow numbers to start with an explicit `+` sign.
+    pub fn leading_plus(mut self, allow: bool) -> Self {
+        self.leading_plus = allow;
+        self
+    }
+
+    /// Allow numbers to start with a decimal point, such as `.5`.
+    pub fn leading_decimal_point(mut self, allow: bool) -> Self {
+        self.leading_decimal_point = allow;
+        self
+    }
+
+    /// Keep all digits of every number as a `JsonValue::Decimal`, instead of
+    /// rounding them to fit in a `Number`. Numbers written with any of the
+    /// relaxed syntax are turned into plain JSON ones, `+.5` becomes `0.5`.
+    ///
+    /// ```
+    /// # #[macro_use] extern crate json;
+    /// # fn main() {
+    /// let source = "[18446744073709551616,0.1000000000000000000001]";
+    /// let options = json::ParserOptions::new().lossless_numbers(true);
+    ///
+    /// assert_eq!(json::parse_with(source, &options).unwrap().dump(), source);
+    /// assert_ne!(json::parse(source).unwrap().dump(), source);
+    /// # }
+    /// ```
+    pub fn lossless_numbers(mut self, keep: bool) -> Self {
+        self.lossless_numbers = keep;
+        self
+    }
+
+    /// What to do about keys that appear more than once in the same object,
+    /// see `DuplicateKeys`.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// What to do about `\u` escapes of surrogates that don't make up a
+    /// pair, see `LoneSurrogates`.
+    pub fn lone_surrogates(mut self, policy: LoneSurrogates) -> Self {
+        self.lone_surrogates = policy;
+        self
+    }
+
+    /// Share object keys through `interner`, across all documents parsed
+    /// with these options, or any others it's been given to. See
+    /// `object::Interner`.
+    pub fn interner(mut self, interner: &Interner) -> Self {
+        self.interner = Some(interner.clone());
+        self
+    }
+
+    // Whether keys have to be kept for every object, to find duplicates, to
+    // count entries, or to count the values collected for a key.
+    fn keeps_keys(&self) -> bool {
+        self.duplicate_keys == DuplicateKeys::Error
+            || self.object_entries_limit != usize::MAX
+            || (self.duplicate_keys == DuplicateKeys::Collect && self.array_length_limit != usize::MAX)
+    }
+
+    // Whether `ch` can start a number.
+    fn starts_number(&self, ch: u8) -> bool {
+        match ch {
+            b'0' ..= b'9' | b'-' => true,
+            b'+' => self.leading_plus,
+            b'.' => self.leading_decimal_point,
+            _    => false,
+        }
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions::new()
+    }
+}
+
+
+// The `Parser` struct keeps track of indexing over our buffer. All niceness
+// has been abandoned in favor of raw pointer magic. Does that make you feel
+// dirty? _Good._
+struct Parser<'a> {
+    // Helper buffer for parsing strings that can't be just memcopied from
+    // the original source (escaped characters)
+    buffer: Vec<u8>,
+
+    // Byte pointer to the source, or to the current window of it when
+    // reading from a `Refill`
+    byte_ptr: *const u8,
+
+    // Current index
+    index: usize,
+
+    // Length of the source (or the window)
+    length: usize,
+
+    // Limits to enforce while parsing
+    options: &'a ParserOptions,
+
+    // Set when the source didn't come from a `&str`, in which case every
+    // string needs to be checked for valid UTF-8 once it's been read.
+    validate_utf8: bool,
+
+    // Where to get more bytes from once the window is exhausted, `None`
+    // when parsing a slice that's entirely in memory.
+    reader: Option<&'a mut dyn Refill>,
+
+    // Number of bytes that have been dropped from the front of the window.
+    offset: usize,
+
+    // Lines and columns in the dropped bytes.
+    dropped: LineCounter,
+
+    // Error encountered while refilling the window. The parser only sees
+    // the end of the source, the actual error is reported once it's done.
+    refill_error: Option<Error>,
+
+    // Start of the key being read, nothing from there on is dropped from
+    // the window so that it can be pointed at if it's a duplicate.
+    // `usize::MAX` when not reading a key.
+    pin: usize,
+
+    // Deals with duplicate keys
+    duplicates: Duplicates,
+
+    // Bytes to stop at, found by a first pass over sources that are
+    // entirely in memory, see `structural.rs`
+    structurals: Option<Structurals>,
+
+    // Containers to fill before allocating new ones, see `recycle.rs`
+    recycled: Recycled,
+
+    _marker: PhantomData<&'a [u8]>,
+}
+
+
+// Something that can supply the parser with more bytes, such as `io::Read`.
+trait Refill {
+    // Read more bytes at the end of the window and drop `consumed` bytes
+    // from its front. A window that hasn't grown means there is nothing left
+    // to read. On error nothing should be dropped.
+    fn refill(&mut self, consumed: usize) -> io::Result<()>;
+
+    // All bytes currently in the window.
+    fn window(&self) -> &[u8];
+}
+
+
+// Counts lines and columns the same way `str::lines` would split them,
+// but can be fed bytes in pieces.
+#[derive(Clone, Copy, Default)]
+struct LineCounter {
+    // Number of `\n` bytes
+    newlines: usize,
+
+    // Characters since the last `\n`
+    column: usize,
+
+    // Characters in the line before the last `\n`, without `\r`
+    previous: usize,
+
+    // Last byte was `\r`
+    carriage_return: bool,
+
+    // Last byte was `\n`
+    newline: bool,
+}
+
+impl LineCounter {
+    fn advance(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            match byte {
+                b'\n' => {
+                    self.newlines += 1;
+                    self.previous = self.column - self.carriage_return as usize;
+                    self.column = 0;
+                },
+                // UTF-8 continuation bytes don't start a character
+                0x80 ..= 0xBF => {},
+                _ => self.column += 1,
+            }
+            self.carriage_return = byte == b'\r';
+            self.newline = byte == b'\n';
+        }
+    }
+
+    // Line and column of the next character, both starting at 1.
+    fn position(&self) -> (usize, usize) {
+        // `str::lines` doesn't produce an empty line after a trailing
+        // `\n`, so the position is reported at the end of that line.
+        if self.newline {
+            (self.newlines, self.previous + 1)
+        } else {
+            (self.newlines + 1, self.column + 1)
+        }
+    }
+}
+
+
+// Read a byte from the source.
+// Will return an error if there are no more bytes.
+macro_rules! expect_byte {
+    ($parser:ident) => ({
+        if $parser.is_eof() {
+            return Err(Error::UnexpectedEndOfJson);
+        }
+
+        let ch = $parser.read_byte();
+        $parser.bump();
+        ch
+    })
+}
+
+
+// Expect a sequence of specific bytes in specific order, error otherwise.
+// This is useful for reading the 3 JSON identifiers:
+//
+// - "t" has to be followed by "rue"
+// - "f" has to be followed by "alse"
+// - "n" has to be followed by "ull"
+//
+// Anything else is an error, reported as not being `$expected`.
+macro_rules! expect_sequence {
+    ($parser:ident, $expected:expr, $( $ch:pat ),*) => {
+        $(
+            match expect_byte!($parser) {
+                $ch => {},
+                _   => return $parser.unexpected_character($expected),
+            }
+        )*
+    }
+}
+
+
+// A drop in macro for when we expect to read a byte, but we don't care
+// about any whitespace characters that might occur before it. Comments
+// count as whitespace if the options allow them.
+macro_rules! expect_byte_ignore_whitespace {
+    ($parser:ident) => ({
+        let mut ch = expect_byte!($parser);
+
+        // Don't go straight for the loop, assume we are in the clear first.
+        match ch {
+            // whitespace
+            9 ..= 13 | 32 | b'/' => {
+                loop {
+                    match ch {
+                        9 ..= 13 | 32                    => $parser.skip_plain(),
+                        b'/' if $parser.options.comments => $parser.skip_comment()?,
+                        _                                => break,
+                    }
+                    ch = expect_byte!($parser);
+                }
+            },
+            _ => {}
+        }
+
+        ch
+    })
+}
+
+// Expect to find EOF or just whitespaces leading to EOF after a JSON value
+macro_rules! expect_eof {
+    ($parser:ident) => ({
+        while !$parser.is_eof() {
+            match $parser.read_byte() {
+                9 ..= 13 | 32 => $parser.bump(),
+                b'/' if $parser.options.comments => {
+                    $parser.bump();
+                    $parser.skip_comment()?;
+                },
+                _             => {
+                    $parser.bump();
+                    return $parser.unexpected_character(Expected::EndOfJson);
+                }
+            }
+        }
+    })
+}
+
+// Expect a particular byte to be next. Also available with a variant
+// creates a `match` expression just to ease some pain.
+macro_rules! expect {
+    ($parser:ident, $byte:expr, $expected:expr) => ({
+        let ch = expect_byte_ignore_whitespace!($parser);
+
+        if ch != $byte {
+            return $parser.unexpected_character($expected)
+        }
+    });
+
+    {$parser:ident, $expected:expr $(, $byte:pat => $then:expr )*} => ({
+        let ch = expect_byte_ignore_whitespace!($parser);
+
+        match ch {
+            $(
+                $byte => $then,
+            )*
+            _ => return $parser.unexpected_character($expected)
+        }
+
+    })
+}
+
+
+// Look up table that marks which characters are allowed in their raw
+// form in a string.
+const QU: bool = false;  // double quote       0x22
+const BS: bool = false;  // backslash          0x5C
+const CT: bool = false;  // control character  0x00 ..= 0x1F
+const __: bool = true;
+
+static ALLOWED: [bool; 256] = [
+// 0   1   2   3   4   5   6   7   8   9   A   B   C   D   E   F
+  CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, // 0
+  CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, // 1
+  __, __, QU, __, __, __, __, __, __, __, __, __, __, __, __, __, // 2
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 3
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 4
+  __, __, __, __, __, __, __, __, __, __, __, __, BS, __, __, __, // 5
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 6
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 7
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 8
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 9
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // A
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // B
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // C
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // D
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // E
+  __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // F
+];
+
+
+// Expect a string. This is called after encountering, and consuming, a
+// double quote character. This macro has a happy path variant where it
+// does almost nothing as long as all characters are allowed (as described
+// in the look up table above). If it encounters a closing quote without
+// any escapes, it will use a slice straight from the source, avoiding
+// unnecessary buffering.
+//
+// When reading from a `Refill`, the window is refilled here by hand so that
+// the string so far stays in it, and `start` remains usable.
+macro_rules! expect_string {
+    ($parser:ident) => ({
+        let result: &str;
+        let mut start = $parser.index;
+
+        loop {
+            $parser.skip_plain();
+
+            if $parser.index == $parser.length {
+                let index = $parser.index;
+
+                if !$parser.refill(start) {
+                    return Err(Error::UnexpectedEndOfJson);
+                }
+                start -= index - $parser.index;
+            }
+
+            let ch = $parser.read_byte();
+            $parser.bump();
+
+            if ALLOWED[ch as usize] {
+                continue;
+            }
+            if ch == b'"' {
+                let len = $parser.index - 1 - start;
+
+                if len > $parser.options.string_length_limit {
+                    return Err(Error::ExceededStringLengthLimit);
+                }
+
+                if $parser.validate_utf8 {
+                    $parser.check_utf8(start, $parser.index - 1)?;
+                }
+
+                unsafe {
+                    let ptr = $parser.byte_ptr.offset(start as isize);
+                    result = str::from_utf8_unchecked(slice::from_raw_parts(ptr, len));
+                }
+                break;
+            }
+            if ch == b'\\' {
+                result = $parser.read_complex_string(start, b'"')?;
+                break;
+            }
+
+            if $parser.validate_utf8 {
+                $parser.check_utf8(start, $parser.index - 1)?;
+            }
+
+            return $parser.unexpected_character(Expected::StringCharacter);
+        }
+
+        result
+    })
+}
+
+
+// Expect an object key starting with `$ch`. With the right options it can
+// also be in single quotes, or not quoted at all. Anything else is reported
+// as not being `$expected`.
+macro_rules! expect_key {
+    ($parser:ident, $ch:expr, $expected:expr) => ({
+        match $ch {
+            b'"' => expect_string!($parser),
+            b'\'' if $parser.options.single_quotes => {
+                $parser.read_single_quoted_string()?
+            },
+            b'a' ..= b'z' | b'A' ..= b'Z' | b'_' | b'$' if $parser.options.unquoted_keys => {
+                $parser.read_identifier()?
+            },
+            _ => return $parser.unexpected_character($expected)
+        }
+    })
+}
+
+
+// Read a key starting with `$ch` and add it to `$object`, which is at
+// `$depth` on the stack. Evaluates to the index of its entry.
+macro_rules! insert_key {
+    ($parser:ident, $object:expr, $depth:expr, $ch:ident, $expected:expr) => ({
+        $parser.pin = $parser.index - 1;
+
+        let key = expect_key!($parser, $ch, $expected);
+        let index = match $parser.duplicates.insert($object, $depth, key) {
+            Some(index) => index,
+            None        => return $parser.duplicate_key(key),
+        };
+
+        $parser.pin = usize::MAX;
+        index
+    })
+}
+
+
+// Expect a number. Of some kind.
+macro_rules! expect_number {
+    ($parser:ident, $first:ident) => ({
+        let mut num = ($first - b'0') as u64;
+
+        let result: Number;
+
+        // Cap on how many iterations we do while reading to u64
+        // in order to avoid an overflow.
+        loop {
+            if num >= MAX_PRECISION {
+                result = $parser.read_big_number(num)?;
+                break;
+            }
+
+            if $parser.is_eof() {
+                result = num.into();
+                break;
+            }
+
+            let ch = $parser.read_byte();
+
+            match ch {
+                b'0' ..= b'9' => {
+                    $parser.bump();
+                    num = num * 10 + (ch - b'0') as u64;
+                },
+                _             => {
+                    let mut e = 0;
+                    result = allow_number_extensions!($parser, num, e, ch);
+                    break;
+                }
+            }
+        }
+
+        result
+    })
+}
+
+
+// Invoked after parsing an integer, this will account for fractions and/or
+// `e` notation.
+macro_rules! allow_number_extensions {
+    ($parser:ident, $num:ident, $e:ident, $ch:ident) => ({
+        match $ch {
+            b'.'        => {
+                $parser.bump();
+                expect_fraction!($parser, $num, $e)
+            },
+            b'e' | b'E' => {
+                $parser.bump();
+                $parser.expect_exponent($num, $e)?
+            },
+            _  => $num.into()
+        }
+    });
+
+    // Alternative variant that defaults everything to 0. This is actually
+    // quite handy as the only number that can begin with zero, has to have
+    // a zero mantissa. Leading zeroes are illegal in JSON!
+    ($parser:ident) => ({
+        if $parser.is_eof() {
+            0.into()
+        } else {
+            let mut num = 0;
+            let mut e = 0;
+            let ch = $parser.read_byte();
+
+            match ch {
+                b'x' | b'X' if $parser.options.hex_numbers => {
+                    $parser.bump();
+                    $parser.read_hex_number()?
+                },
+                _ => allow_number_extensions!($parser, num, e, ch)
+            }
+        }
+    })
+}
+
+
+// Expect the rest of a number after the sign, if there was one.
+macro_rules! expect_unsigned_number {
+    ($parser:ident, $ch:ident) => ({
+        match $ch {
+            b'0' => allow_number_extensions!($parser),
+            b'1' ..= b'9' => expect_number!($parser, $ch),
+            b'.' if $parser.options.leading_decimal_point => {
+                let mut num = 0;
+                let mut e = 0;
+                expect_fraction!($parser, num, e)
+            },
+            _    => return $parser.unexpected_character(Expected::Digit)
+        }
+    })
+}
+
+
+// If a dot `b"."` byte has been read, start reading the decimal fraction
+// of the number.
+macro_rules! expect_fraction {
+    ($parser:ident, $num:ident, $e:ident) => ({
+        let result: Number;
+
+        let ch = expect_byte!($parser);
+
+        match ch {
+            b'0' ..= b'9' => {
+                if $num < MAX_PRECISION {
+                    $num = $num * 10 + (ch - b'0') as u64;
+                    $e -= 1;
+                } else {
+                    match $num.checked_mul(10).and_then(|num| {
+                        num.checked_add((ch - b'0') as u64)
+                    }) {
+                        Some(result) => {
+                            $num = result;
+                            $e -= 1;
+                        },
+                        None => {}
+                    }
+                }
+            },
+            _ => return $parser.unexpected_character(Expected::Digit)
+        }
+
+        loop {
+            if $parser.is_eof() {
+                result = unsafe { Number::from_parts_unchecked(true, $num, $e) };
+                break;
+            }
+            let ch = $parser.read_byte();
+
+            match ch {
+                b'0' ..= b'9' => {
+                    $parser.bump();
+                    if $num < MAX_PRECISION {
+                        $num = $num * 10 + (ch - b'0') as u64;
+                        $e -= 1;
+                    } else {
+                        match $num.checked_mul(10).and_then(|num| {
+                            num.checked_add((ch - b'0') as u64)
+                        }) {
+                            Some(result) => {
+                                $num = result;
+                                $e -= 1;
+                            },
+                            None => {}
+                        }
+                    }
+                },
+                b'e' | b'E' => {
+                    $parser.bump();
+                    result = $parser.expect_exponent($num, $e)?;
+                    break;
+                }
+                _ => {
+                    result = unsafe { Number::from_parts_unchecked(true, $num, $e) };
+                    break;
+                }
+            }
+        }
+
+        result
+    })
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(source: &'a str, options: &'a ParserOptions) -> Self {
+        Parser::from_bytes(source.as_bytes(), options, false)
+    }
+
+    pub fn from_bytes(source: &'a [u8], options: &'a ParserOptions, validate_utf8: bool) -> Self {
+        Parser {
+            // Only strings with escapes need it, nothing is allocated until
+            // there is one
+            buffer: Vec::new(),
+            byte_ptr: source.as_ptr(),
+            index: 0,
+            length: source.len(),
+            options,
+            validate_utf8,
+            reader: None,
+            offset: 0,
+            dropped: LineCounter::default(),
+            refill_error: None,
+            pin: usize::MAX,
+            duplicates: Duplicates::new(options),
+            structurals: None,
+            recycled: Recycled::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    // Used by the push parser to decode the tokens it collects. `offset`
+    // and `lines` are where the token starts in the document, so that any
+    // errors are reported at the right position.
+    fn from_token(
+        token: &'a [u8],
+        options: &'a ParserOptions,
+        offset: usize,
+        lines: LineCounter,
+    ) -> Self {
+        let mut parser = Parser::from_bytes(token, options, true);
+        parser.offset = offset;
+        parser.dropped = lines;
+        parser
+    }
+
+    // Start with an empty window, filled on first read.
+    pub fn from_refill(reader: &'a mut dyn Refill, options: &'a ParserOptions) -> Self {
+        let mut parser = Parser::from_bytes(&[], options, true);
+        parser.reader = Some(reader);
+        parser
+    }
+
+    // Do the first pass over the source, if it's all in memory and big
+    // enough for that to pay off.
+    fn find_structurals(&mut self) {
+        if self.reader.is_none() && self.length >= structural::MIN_LENGTH {
+            let source = unsafe { slice::from_raw_parts(self.byte_ptr, self.length) };
+
+            self.structurals = Some(Structurals::new(source));
+        }
+    }
+
+    // Skip ahead to the next byte found by the first pass. Only ever called
+    // after reading a whitespace byte, or inside a string, where everything
+    // until then is of no interest.
+    #[inline(always)]
+    fn skip_plain(&mut self) {
+        if let Some(ref structurals) = self.structurals {
+            self.index = structurals.next(self.index);
+        }
+    }
+
+    // Check if we are at the end of the source.
+    #[inline(always)]
+    fn is_eof(&mut self) -> bool {
+        self.index == self.length && !self.refill(self.index)
+    }
+
+    // Out of bytes in the window, get some more from the reader, keeping
+    // everything from `keep` (or `pin`) onwards. Returns `false` if there
+    // is nothing more to read, which is always the case for slices.
+    #[cold]
+    #[inline(never)]
+    fn refill(&mut self, keep: usize) -> bool {
+        if self.reader.is_none() || self.refill_error.is_some() {
+            return false;
+        }
+
+        let keep = keep.min(self.pin);
+
+        let mut dropped = self.dropped;
+        dropped.advance(&self.source()[..keep]);
+
+        let remaining = self.length - keep;
+        let reader = match self.reader {
+            Some(ref mut reader) => reader,
+            None                 => return false,
+        };
+
+        let result = reader.refill(keep);
+
+        // Even on error the window might have been moved around in memory
+        let window = reader.window();
+        self.byte_ptr = window.as_ptr();
+        self.length = window.len();
+
+        if let Err(error) = result {
+            self.refill_error = Some(error.into());
+            return false;
+        }
+
+        self.index -= keep;
+        self.offset += keep;
+        self.dropped = dropped;
+
+        if self.pin != usize::MAX {
+            self.pin -= keep;
+        }
+
+        if self.offset + self.length > self.options.size_limit {
+            self.refill_error = Some(Error::ExceededSizeLimit);
+            return false;
+        }
+
+        self.length > remaining
+    }
+
+    // The source, or the current window of it. The window is only good
+    // until the next refill, keep that in mind when holding on to it.
+    #[inline]
+    fn source(&self) -> &'a [u8] {
+        unsafe { slice::from_raw_parts(self.byte_ptr, self.length) }
+    }
+
+    // Read a byte from the source. Note that this does not increment
+    // the index. In few cases (all of them related to number parsing)
+    // we want to peek at the byte before doing anything. This will,
+    // very very rarely, lead to a situation where the same byte is read
+    // twice, but since this operation is using a raw pointer, the cost
+    // is virtually irrelevant.
+    #[inline(always)]
+    fn read_byte(&mut self) -> u8 {
+        debug_assert!(self.index < self.length, "Reading out of bounds");
+
+        unsafe { *self.byte_ptr.offset(self.index as isize) }
+    }
+
+    // Manually increment the index. Calling `read_byte` and then `bump`
+    // is equivalent to consuming a byte on an iterator.
+    #[inline(always)]
+    fn bump(&mut self) {
+        self.index = self.index.wrapping_add(1);
+    }
+
+    // So we got an unexpected character, now what? Well, figure out where
+    // it is, and throw an error! `expected` is whatever would have been fine
+    // in its place.
+    fn unexpected_character<T: Sized>(&mut self, expected: Expected) -> Result<T> {
+        // A multi-byte character can be cut off by the end of the window
+        while self.length - self.index < 3 && self.refill(self.index - 1) {}
+
+        let at = self.index - 1;
+        let tail = &self.source()[at..self.length.min(at + 4)];
+        let tail = match str::from_utf8(tail) {
+            Ok(tail) => tail,
+            Err(error) if error.valid_up_to() > 0 => {
+                unsafe { str::from_utf8_unchecked(&tail[..error.valid_up_to()]) }
+            },
+            Err(_) => return self.invalid_utf8(at),
+        };
+
+        let ch = tail.chars().next().expect("Must have a character");
+        let (line, column) = self.position(at);
+
+        Err(Error::UnexpectedCharacter {
+            ch,
+            line,
+            column,
+            offset: self.offset + at,
+            expected,
+        })
+    }
+
+    // Bytes are not valid UTF-8 starting at `at`.
+    #[cold]
+    fn invalid_utf8<T: Sized>(&self, at: usize) -> Result<T> {
+        let (line, column) = self.position(at);
+
+        Err(Error::InvalidUtf8 {
+            offset: self.offset + at,
+            line,
+            column,
+        })
+    }
+
+    // The key that was just read is already in the object, and that's not
+    // allowed. It starts at `pin`.
+    #[cold]
+    fn duplicate_key<T: Sized>(&self, key: &str) -> Result<T> {
+        let (line, column) = self.position(self.pin);
+
+        Err(Error::DuplicateKey {
+            key: key.into(),
+            line,
+            column,
+        })
+    }
+
+    // Line and column of the byte at `at`, which must be preceded only by
+    // valid UTF-8.
+    fn position(&self, at: usize) -> (usize, usize) {
+        let mut lines = self.dropped;
+
+        lines.advance(&self.source()[..at]);
+        lines.position()
+    }
+
+    // Validate the raw bytes of a string between `start` and `end`. Escapes
+    // are all ASCII, so this works the same for strings with and without them.
+    fn check_utf8(&self, start: usize, end: usize) -> Result<()> {
+        match str::from_utf8(&self.source()[start .. end]) {
+            Ok(_)      => Ok(()),
+            Err(error) => self.invalid_utf8(start + error.valid_up_to()),
+        }
+    }
+
+    // Having read the first byte of a multi-byte character in a string,
+    // check it along with the rest of its bytes, and push them onto the
+    // buffer.
+    fn read_utf8_char(&mut self, first: u8) -> Result<()> {
+        let bytes = self.skip_utf8_char(first)?;
+
+        self.buffer.extend_from_slice(bytes);
+
+        Ok(())
+    }
+
+    // Same as above, except the character is only skipped over. Returns
+    // its bytes, which are good until the next refill.
+    fn skip_utf8_char(&mut self, first: u8) -> Result<&'a [u8]> {
+        let width = match first {
+            0xC2 ..= 0xDF => 2,
+            0xE0 ..= 0xEF => 3,
+            0xF0 ..= 0xF4 => 4,
+            _             => return self.invalid_utf8(self.index - 1),
+        };
+
+        // Make sure the whole character is in the window
+        while self.length - self.index < width - 1 && self.refill(self.index - 1) {}
+
+        let at = self.index - 1;
+        let bytes = &self.source()[at .. self.length.min(at + width)];
+
+        match str::from_utf8(bytes) {
+            Ok(_) => {},
+            Err(error) if error.error_len().is_none() => {
+                return Err(Error::UnexpectedEndOfJson);
+            },
+            Err(_) => return self.invalid_utf8(at),
+        }
+
+        self.index += width - 1;
+
+        Ok(bytes)
+    }
+
+    // Skip over a comment, the leading `/` has already been read. Line
+    // comments can also end with the source.
+    fn skip_comment(&mut self) -> Result<()> {
+        match expect_byte!(self) {
+            b'/' => {
+                while !self.is_eof() {
+                    let ch = self.read_byte();
+                    self.bump();
+
+                    match ch {
+                        b'\n' => break,
+                        0x80 ..= 0xFF if self.validate_utf8 => {
+                            self.skip_utf8_char(ch)?;
+                        },
+                        _ => {}
+                    }
+                }
+            },
+            b'*' => {
+                let mut ch = expect_byte!(self);
+
+                loop {
+                    match ch {
+                        b'*' => {
+                            ch = expect_byte!(self);
+
+                            if ch == b'/' {
+                                break;
+                            }
+                            continue;
+                        },
+                        0x80 ..= 0xFF if self.validate_utf8 => {
+                            self.skip_utf8_char(ch)?;
+                        },
+                        _ => {}
+                    }
+                    ch = expect_byte!(self);
+                }
+            },
+            _ => return self.unexpected_character(Expected::Comment)
+        }
+
+        Ok(())
+    }
+
+    // Boring
+    fn read_hexdec_digit(&mut self) -> Result<u16> {
+        let ch = expect_byte!(self);
+        Ok(match ch {
+            b'0' ..= b'9' => (ch - b'0'),
+            b'a' ..= b'f' => (ch + 10 - b'a'),
+            b'A' ..= b'F' => (ch + 10 - b'A'),
+            _             => return self.unexpected_character(Expected::HexDigit),
+        } as u16)
+    }
+
+    // Boring
+    fn read_hexdec_codepoint(&mut self) -> Result<u16> {
+        Ok(
+            self.read_hexdec_digit()? << 12 |
+            self.read_hexdec_digit()? << 8  |
+            self.read_hexdec_digit()? << 4  |
+            self.read_hexdec_digit()?
+        )
+    }
+
+    // Oh look, some action. This method reads an escaped unicode
+    // sequence such as `\uDEAD` from the string. Except `DEAD` is
+    // not a valid codepoint, so it also needs to handle errors...
+    fn read_codepoint(&mut self) -> Result<()> {
+        let mut buf = [0; 8];
+        let unicode = self.read_escaped(&mut buf)?;
+
+        self.buffer.extend_from_slice(unicode.as_bytes());
+
+        Ok(())
+    }
+
+    // What's so complex about strings you may ask? Not that much really.
+    // This method is called if the `expect_string!` macro encounters an
+    // escape. The added complexity is that it will have to use an internal
+    // buffer to read all the escaped characters into, before finally
+    // producing a usable slice. What it means it that parsing "foo\bar"
+    // is whole lot slower than parsing "foobar", as the former suffers from
+    // having to be read from source to a buffer and then from a buffer to
+    // our target string. Nothing to be done about this, really.
+    //
+    // The string ends with `quote`, which is `'` for strings in single quotes.
+    // Those don't go through `expect_string!` at all, and start here with
+    // their first byte already read.
+    fn read_complex_string<'b>(&mut self, start: usize, quote: u8) -> Result<&'b str> {
+        // Since string slices are returned by this function that are created via pointers into `self.buffer`
+        // we shouldn't be clearing or modifying the buffer in consecutive calls to this function. Instead
+        // we continuously append bytes to `self.buffer` and keep track of the starting offset of the buffer on each
+        // call to this function. Later when creating string slices that point to the contents of this buffer
+        // we use this starting offset to make sure that newly created slices point only to the bytes that were
+        // appended in the most recent call to this function.
+        //
+        // Failing to do this can result in the StackBlock `key` values being modified in place later.
+        let len = self.buffer.len();
+        //self.buffer.clear();
+        let mut ch = self.source()[self.index - 1];
+
+        if self.validate_utf8 {
+            self.check_utf8(start, self.index - 1)?;
+        }
+
+        // TODO: Use fastwrite here as well
+        self.buffer.extend_from_slice(&self.source()[start .. self.index - 1]);
+
+        loop {
+            // Whatever's been decoded so far, before decoding any more
+            if self.buffer.len() - len > self.options.string_length_limit {
+                return Err(Error::ExceededStringLengthLimit);
+            }
+            if ALLOWED[ch as usize] && ch != quote {
+                if ch < 0x80 || !self.validate_utf8 {
+                    self.buffer.push(ch);
+                } else {
+                    self.read_utf8_char(ch)?;
+                }
+                ch = expect_byte!(self);
+                continue;
+            }
+            match ch {
+                _ if ch == quote => break,
+                // Only when in single quotes
+                b'"'  => self.buffer.push(ch),
+                b'\\' => {
+                    let escaped = expect_byte!(self);
+                    let escaped = match escaped {
+                        b'u'  => {
+                            self.read_codepoint()?;
+                            ch = expect_byte!(self);
+                            continue;
+                        },
+                        b'"'  |
+                        b'\\' |
+                        b'/'  => escaped,
+                        b'b'  => 0x8,
+                        b'f'  => 0xC,
+                        b't'  => b'\t',
+                        b'r'  => b'\r',
+                        b'n'  => b'\n',
+                        b'\'' if self.options.single_quotes => escaped,
+                        _     => return self.unexpected_character(Expected::Escape)
+                    };
+                    self.buffer.push(escaped);
+                },
+                _ => return self.unexpected_character(Expected::StringCharacter)
+            }
+            ch = expect_byte!(self);
+        }
+
+        // Since the original source is valid UTF-8 (or has been checked to
+        // be), and `\` cannot occur in front of a codepoint > 127, this is safe.
+        Ok(unsafe {
+            str::from_utf8_unchecked(
+                // Because the buffer is stored on the parser, returning it
+                // as a slice here freaks out the borrow checker. The compiler
+                // can't know that the buffer isn't used till the result
+                // of this function is long used and irrelevant. To avoid
+                // issues here, we construct a new slice from raw parts, which
+                // then has lifetime bound to the outer function scope instead
+                // of the parser itself.
+                slice::from_raw_parts(self.buffer[len .. ].as_ptr(), self.buffer.len() - len)
+            )
+        })
+    }
+
+    // Strings in single quotes are rare enough to always take the slow path.
+    fn read_single_quoted_string<'b>(&mut self) -> Result<&'b str> {
+        expect_byte!(self);
+
+        self.read_complex_string(self.index - 1, b'\'')
+    }
+
+    // Read an unquoted object key, its first byte has already been read.
+    // Like strings, it has to stay in the window until it's done.
+    fn read_identifier<'b>(&mut self) -> Result<&'b str> {
+        let mut start = self.index - 1;
+
+        loop {
+            if self.index == self.length {
+                let index = self.index;
+
+                if !self.refill(start) {
+                    break;
+                }
+                start -= index - self.index;
+            }
+
+            match self.read_byte() {
+                b'a' ..= b'z' |
+                b'A' ..= b'Z' |
+                b'0' ..= b'9' |
+                b'_' | b'$'   => self.bump(),
+                _             => break,
+            }
+        }
+
+        let len = self.index - start;
+
+        if len > self.options.string_length_limit {
+            return Err(Error::ExceededStringLengthLimit);
+        }
+
+        // Nothing but ASCII in there
+        Ok(unsafe {
+            str::from_utf8_unchecked(slice::from_raw_parts(self.byte_ptr.add(start), len))
+        })
+    }
+
+    // Read a hexadecimal number, `0x` has already been read. Anything that
+    // doesn't fit in u64 ends up as a float.
+    fn read_hex_number(&mut self) -> Result<Number> {
+        let mut num = self.read_hexdec_digit()? as u64;
+        let mut big: Option<f64> = None;
+
+        loop {
+            if self.is_eof() {
+                break;
+            }
+
+            let ch = self.read_byte();
+            let digit = match ch {
+                b'0' ..= b'9' => ch - b'0',
+                b'a' ..= b'f' => ch + 10 - b'a',
+                b'A' ..= b'F' => ch + 10 - b'A',
+                _             => break,
+            } as u64;
+
+            self.bump();
+
+            match big {
+                Some(ref mut big)     => *big = *big * 16.0 + digit as f64,
+                None if num >> 60 == 0 => num = num << 4 | digit,
+                None                  => big = Some(num as f64 * 16.0 + digit as f64),
+            }
+        }
+
+        Ok(match big {
+            Some(big) => big.into(),
+            None      => num.into(),
+        })
+    }
+
+    // Big numbers! If the `expect_number!` reaches a point where the decimal
+    // mantissa could have overflown the size of u64, it will switch to this
+    // control path instead. This method will pick up where the macro started,
+    // but instead of continuing to read into the mantissa, it will increment
+    // the exponent. Note that no digits are actually read here, as we already
+    // exceeded the precision range of f64 anyway.
+    fn read_big_number(&mut self, mut num: u64) -> Result<Number> {
+        let mut e = 0i16;
+        loop {
+            if self.is_eof() {
+                return Ok(unsafe { Number::from_parts_unchecked(true, num, e) });
+            }
+            let ch = self.read_byte();
+            match ch {
+                b'0' ..= b'9' => {
+                    self.bump();
+                    match num.checked_mul(10).and_then(|num| {
+                        num.checked_add((ch - b'0') as u64)
+                    }) {
+                        Some(result) => num = result,
+                        None         => e = e.checked_add(1).ok_or_else(|| Error::ExceededDepthLimit)?,
+                    }
+                },
+                b'.' => {
+                    self.bump();
+                    return Ok(expect_fraction!(self, num, e));
+                },
+                b'e' | b'E' => {
+                    self.bump();
+                    return self.expect_exponent(num, e);
+                }
+                _  => break
+            }
+        }
+
+        Ok(unsafe { Number::from_parts_unchecked(true, num, e) })
+    }
+
+    // Called in the rare case that a number with `e` notation has been
+    // encountered. This is pretty straight forward, I guess.
+    fn expect_exponent(&mut self, num: u64, big_e: i16) -> Result<Number> {
+        let mut ch = expect_byte!(self);
+        let sign = match ch {
+            b'-' => {
+                ch = expect_byte!(self);
+                -1
+            },
+            b'+' => {
+                ch = expect_byte!(self);
+                1
+            },
+            _    => 1
+        };
+
+        let mut e = match ch {
+            b'0' ..= b'9' => (ch - b'0') as i16,
+            _ => return self.unexpected_character(Expected::Digit),
+        };
+
+        loop {
+            if self.is_eof() {
+                break;
+            }
+            let ch = self.read_byte();
+            match ch {
+                b'0' ..= b'9' => {
+                    self.bump();
+                    e = e.saturating_mul(10).saturating_add((ch - b'0') as i16);
+                },
+                _  => break
+            }
+        }
+
+        Ok(unsafe { Number::from_parts_unchecked(true, num, big_e.saturating_add(e * sign)) })
+    }
+
+    // Read a single string, the opening quote has already been consumed.
+    fn read_string(&mut self) -> Result<&str> {
+        Ok(expect_string!(self))
+    }
+
+    // Read a single number as a value, keeping all of its digits if the
+    // options ask for that.
+    fn read_number_value(&mut self) -> Result<JsonValue> {
+        if !self.options.lossless_numbers {
+            return self.read_number().map(JsonValue::Number);
+        }
+
+        // Pinned so that the digits stay in the window
+        self.pin = self.index;
+
+        let number = self.read_number();
+        let start = self.pin;
+
+        self.pin = usize::MAX;
+
+        number?;
+
+        let text = unsafe { str::from_utf8_unchecked(&self.source()[start..self.index]) };
+        let text = text.strip_prefix('+').unwrap_or(text);
+
+        // Relaxed syntax has to go, hexadecimal numbers are written out in
+        // decimal, every digit of them
+        if let Some(x) = text.find(['x', 'X']) {
+            return Ok(JsonValue::Decimal(Decimal::from_valid(hex_to_decimal(&text[..x - 1], &text[x + 1..]))));
+        }
+
+        let text = if let Some(fraction) = text.strip_prefix('.') {
+            format!("0.{}", fraction)
+        } else if let Some(fraction) = text.strip_prefix("-.") {
+            format!("-0.{}", fraction)
+        } else {
+            text.to_owned()
+        };
+
+        Ok(JsonValue::Decimal(Decimal::from_valid(text)))
+    }
+
+    // Read a single number, starting with its first byte.
+    fn read_number(&mut self) -> Result<Number> {
+        let ch = expect_byte!(self);
+
+        Ok(match ch {
+            b'-' => {
+                let ch = expect_byte!(self);
+                - expect_unsigned_number!(self, ch)
+            },
+            b'+' if self.options.leading_plus => {
+                let ch = expect_byte!(self);
+                expect_unsigned_number!(self, ch)
+            },
+            _ => expect_unsigned_number!(self, ch)
+        })
+    }
+
+    // Parse away!
+    fn parse(&mut self) -> Result<JsonValue> {
+        if self.length > self.options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        self.find_structurals();
+
+        let value = self.parse_value(0)?;
+
+        expect_eof!(self);
+
+        Ok(value)
+    }
+
+    // Parse a single value, leaving whatever comes after it alone. `depth`
+    // is the number of arrays and objects it's in, for the depth limit.
+    fn parse_value(&mut self, depth: usize) -> Result<JsonValue> {
+        let depth_limit = self.options.depth_limit - depth;
+        let mut stack = self.new_stack();
+        let mut ch = expect_byte_ignore_whitespace!(self);
+
+        'parsing: loop {
+            let mut value = match ch {
+                b'[' => {
+                    ch = expect_byte_ignore_whitespace!(self);
+
+                    if ch != b']' {
+                        if stack.len() == depth_limit {
+                            return Err(Error::ExceededDepthLimit);
+                        }
+
+                        stack.push(StackBlock(JsonValue::Array(self.new_array()), 0));
+                        continue 'parsing;
+                    }
+
+                    JsonValue::Array(Vec::new())
+                },
+                b'{' => {
+                    ch = expect_byte_ignore_whitespace!(self);
+
+                    if ch != b'}' {
+                        if stack.len() == depth_limit {
+                            return Err(Error::ExceededDepthLimit);
+                        }
+
+                        let mut object = self.new_object();
+
+                        self.duplicates.open(stack.len());
+
+                        let index = insert_key!(self, &mut object, stack.len(), ch, Expected::KeyOrEndOfObject);
+
+                        if self.options.object_entries_limit == 0 {
+                            return Err(Error::ExceededObjectEntriesLimit);
+                        }
+
+                        expect!(self, b':', Expected::Colon);
+
+                        stack.push(StackBlock(JsonValue::Object(object), index));
+
+                        ch = expect_byte_ignore_whitespace!(self);
+
+                        continue 'parsing;
+                    }
+
+                    JsonValue::Object(Object::new())
+                },
+                b'"' => {
+                    let string = expect_string!(self);
+                    self.new_string(string)
+                },
+                _ if self.options.lossless_numbers && self.options.starts_number(ch) => {
+                    self.index -= 1;
+                    self.read_number_value()?
+                },
+                b'0' => JsonValue::Number(allow_number_extensions!(self)),
+                b'1' ..= b'9' => {
+                    JsonValue::Number(expect_number!(self, ch))
+                },
+                b'-' => {
+                    let ch = expect_byte!(self);
+                    JsonValue::Number(- expect_unsigned_number!(self, ch))
+                }
+                b'+' if self.options.leading_plus => {
+                    let ch = expect_byte!(self);
+                    JsonValue::Number(expect_unsigned_number!(self, ch))
+                },
+                b'.' if self.options.leading_decimal_point => {
+                    JsonValue::Number(expect_unsigned_number!(self, ch))
+                },
+                b'\'' if self.options.single_quotes => {
+                    let string = self.read_single_quoted_string()?;
+                    self.new_string(string)
+                },
+                b't' => {
+                    expect_sequence!(self, Expected::Literal("true"), b'r', b'u', b'e');
+                    JsonValue::Boolean(true)
+                },
+                b'f' => {
+                    expect_sequence!(self, Expected::Literal("false"), b'a', b'l', b's', b'e');
+                    JsonValue::Boolean(false)
+                },
+                b'n' => {
+                    expect_sequence!(self, Expected::Literal("null"), b'u', b'l', b'l');
+                    JsonValue::Null
+                },
+                _    => return self.unexpected_character(Expected::Value)
+            };
+
+            'popping: loop {
+                let depth = stack.len().wrapping_sub(1);
+
+                match stack.last_mut() {
+                    None => {
+                        self.put_stack(stack);
+                        return Ok(value);
+                    },
+
+                    Some(&mut StackBlock(JsonValue::Array(ref mut array), _)) => {
+                        if array.len() == self.options.array_length_limit {
+                            return Err(Error::ExceededArrayLengthLimit);
+                        }
+
+                        array.push(value);
+
+                        ch = expect_byte_ignore_whitespace!(self);
+
+                        match ch {
+                            b',' => {
+                                ch = expect_byte_ignore_whitespace!(self);
+
+                                if ch != b']' || !self.options.trailing_commas {
+                                    continue 'parsing;
+                                }
+                            },
+                            b']' => {},
+                            _    => return self.unexpected_character(Expected::CommaOrEndOfArray)
+                        }
+                    },
+
+                    Some(&mut StackBlock(JsonValue::Object(ref mut object), ref mut index )) => {
+                        self.duplicates.set(object, *index, value)?;
+
+                        ch = expect_byte_ignore_whitespace!(self);
+
+                        match ch {
+                            b',' => {
+                                ch = expect_byte_ignore_whitespace!(self);
+
+                                if ch != b'}' || !self.options.trailing_commas {
+                                    *index = insert_key!(self, object, depth, ch, Expected::Key);
+
+                                    if object.len() > self.options.object_entries_limit {
+                                        return Err(Error::ExceededObjectEntriesLimit);
+                                    }
+
+                                    expect!(self, b':', Expected::Colon);
+
+                                    ch = expect_byte_ignore_whitespace!(self);
+
+                                    continue 'parsing;
+                                }
+                            },
+                            b'}' => {},
+                            _    => return self.unexpected_character(Expected::CommaOrEndOfObject)
+                        }
+                    },
+
+                    _ => unreachable!(),
+                }
+
+                value = match stack.pop() {
+                    Some(StackBlock(value, _)) => value,
+                    None                       => break 'popping
+                }
+            }
+        }
+    }
+}
+
+struct StackBlock(JsonValue, usize);
+
+mod read;
+mod duplicates;
+mod recover;
+mod borrowed;
+mod document;
+mod structural;
+mod validate;
+mod walk;
+mod extract;
+mod surrogates;
+mod encoding;
+mod recycle;
+mod truncated;
+#[cfg(feature = "async")]
+mod async_read;
+pub mod push;
+pub mod pull;
+pub mod stream;
+pub mod lazy;
+pub mod spans;
+
+pub use self::read::{ parse_reader, parse_reader_with };
+pub use self::recover::{ parse_recover, parse_recover_with };
+pub use self::borrowed::{ parse_borrowed, parse_borrowed_with };
+pub use self::document::{ parse_document, parse_document_with };
+pub use self::lazy::{ parse_lazy, parse_lazy_with };
+pub use self::spans::{ parse_spanned, parse_spanned_with };
+pub use self::extract::{ extract, extract_with };
+pub use self::validate::{ validate, validate_with, validate_bytes, validate_bytes_with };
+pub use self::duplicates::DuplicateKeys;
+pub use self::surrogates::LoneSurrogates;
+pub use self::encoding::{ parse_bytes_detect, parse_bytes_detect_with, Encoding };
+pub use self::recycle::{ parse_into, parse_into_with, ReusableParser };
+pub use self::truncated::{ parse_truncated, parse_truncated_with, Truncated, Completion, Incomplete };
+#[cfg(feature = "async")]
+pub use self::async_read::{ parse_async, parse_async_with };
+pub(crate) use self::surrogates::{ PRESERVED, SURROGATE_MARK };
+
+use self::duplicates::Duplicates;
+use self::structural::Structurals;
+use self::recycle::Recycled;
+
+// Read a lone number, which has to be strict JSON. Used by `Decimal` to
+// check its digits and to turn them into a `Number`.
+pub(crate) fn parse_number(source: &str) -> Result<Number> {
+    let mut parser = Parser::new(source, &DEFAULT_OPTIONS);
+    let number = parser.read_number()?;
+
+    // Not even whitespace
+    if parser.index < parser.length {
+        parser.bump();
+        return parser.unexpected_character(Expected::EndOfJson);
+    }
+
+    Ok(number)
+}
+
+// Write out the hexadecimal `digits` in decimal, following `sign`, which
+// is either empty or a minus. Done in chunks of nine decimal digits, least
+// significant first, so that a number of any length comes out exact.
+fn hex_to_decimal(sign: &str, digits: &str) -> String {
+    const CHUNK: u64 = 1_000_000_000;
+
+    let mut chunks: Vec<u64> = vec![0];
+
+    for ch in digits.chars() {
+        let mut carry = ch.to_digit(16).unwrap_or(0) as u64;
+
+        for chunk in chunks.iter_mut() {
+            let value = *chunk * 16 + carry;
+
+            *chunk = value % CHUNK;
+            carry = value / CHUNK;
+        }
+
+        if carry > 0 {
+            chunks.push(carry);
+        }
+    }
+
+    let mut text = String::from(sign);
+    let mut chunks = chunks.iter().rev();
+
+    if let Some(first) = chunks.next() {
+        text.push_str(&first.to_string());
+    }
+
+    for chunk in chunks {
+        text.push_str(&format!("{:09}", chunk));
+    }
+
+    text
+}
+
+// All that hard work, and in the end it's just a single function in the API.
+#[inline]
+pub fn parse(source: &str) -> Result<JsonValue> {
+    Parser::new(source, &DEFAULT_OPTIONS).parse()
+}
+
+/// Parse a JSON document while enforcing the limits set on `options`.
+#[inline]
+pub fn parse_with(source: &str, options: &ParserOptions) -> Result<JsonValue> {
+    Parser::new(source, options).parse()
+}
+
+/// Parse a JSON document from raw bytes. The bytes are checked to be valid
+/// UTF-8 as they are being parsed, there is no need to convert them to a
+/// `&str` first. Invalid bytes are reported with `Error::InvalidUtf8`.
+///
+/// ```
+/// # use json::Error;
+/// assert_eq!(json::parse_bytes(b"[\"foo\"]").unwrap()[0], "foo");
+///
+/// assert_eq!(json::parse_bytes(b"[\n\"fo\xFF\"]"), Err(Error::InvalidUtf8 {
+///     offset: 5,
+///     line: 2,
+///     column: 4,
+/// }));
+/// ```
+#[inline]
+pub fn parse_bytes(source: &[u8]) -> Result<JsonValue> {
+    Parser::from_bytes(source, &DEFAULT_OPTIONS, true).parse()
+}
+
+/// Parse a JSON document from raw bytes while enforcing the limits set
+/// on `options`.
+#[inline]
+pub fn parse_bytes_with(source: &[u8], options: &ParserOptions) -> Result<JsonValue> {
+    Parser::from_bytes(source, options, true).parse()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stringify;
+    use crate::JsonValue;
+
+    use crate::object;
+    use crate::array;
+
+    use std::fs::File;
+    use std::io::prelude::*;
+
+    #[test]
+    fn line_counter_should_match_str_lines() {
+        let sources = [
+            "", "a", "\n", "\n\n", "ab\ncd", "ab\n", "ab\r\n", "ab\r\ncd",
+            "\r", "ab\r", "a\rb", "\r\n\r\n", "zażółć\ngęślą", "🦄\n🦄🦄",
+        ];
+
+        for source in sources.iter() {
+            let (lineno, col) = source.lines().enumerate().last().unwrap_or((0, ""));
+            let expected = (lineno + 1, col.chars().count() + 1);
+
+            let mut lines = LineCounter::default();
+            lines.advance(source.as_bytes());
+            assert_eq!(lines.position(), expected, "{:?}", source);
+
+            // Feeding the same bytes in pieces yields the same result
+            let mut lines = LineCounter::default();
+            for byte in source.as_bytes() {
+                lines.advance(&[*byte]);
+            }
+            assert_eq!(lines.position(), expected, "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn it_should_parse_escaped_forward_slashes_with_quotes() {
+        // used to get around the fact that rust strings don't escape forward slashes
+        let mut file = File::open("tests/test_json_slashes_quotes").unwrap();
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).unwrap();
+
+        let actual = parse(&contents).unwrap();
+        let serialized = stringify(actual.clone());
+
+        assert_eq!(serialized, contents);
+    }
+
+    #[test]
+    fn it_should_parse_escaped_quotes() {
+        let contents = String::from("{\"ab\":\"c\\\"d\\\"e\"}");
+
+        let actual = parse(&contents).unwrap();
+        let serialized = stringify(actual.clone());
+
+        assert_eq!(serialized, contents);
+    }
+
+    #[test]
+    fn it_should_parse_basic_json_values() {
+        let s = "{\"a\":1,\"b\":true,\"c\":false,\"d\":null,\"e\":2}";
+        let actual = parse(s).unwrap();
+        let mut expected = object! {
+            a: 1,
+            b: true,
+            c: false,
+            e: 2,
+        };
+        expected["d"] = JsonValue::Null;
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn it_should_parse_json_arrays() {
+        let s = "{\"a\":1,\"b\":true,\"c\":false,\"d\":null,\"e\":2,\"f\":[1,2,3,false,true,[],{}]}";
+        let actual = parse(s).unwrap();
+        let mut expected = object! {
+            a: 1,
+            b: true,
+            c: false,
+            e: 2,
+        };
+        expected["d"] = JsonValue::Null;
+        expected["f"] = array![
+            1,2,3,
+            false,
+            true,
+            [],
+            {},
+        ];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn it_should_parse_json_nested_object() {
+        let s = "{\"a\":1,\"b\":{\"c\":2,\"d\":{\"e\":{\"f\":{\"g\":3,\"h\":[]}}},\"i\":4,\"j\":[],\"k\":{\"l\":5,\"m\":{}}}}";
+        let actual = parse(s).unwrap();
+        let expected = object! {
+            a: 1,
+            b: {
+                c: 2,
+                d: {
+                    e: {
+                        f: {
+                            g: 3,
+                            h: []
+                        }
+                    }
+                },
+                i: 4,
+                j: [],
+                k: {
+                    l: 5,
+                    m: {}
+                }
+            }
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn it_should_parse_json_complex_object() {
+        let s = "{\"a\":1,\"b\":{\"c\":2,\"d\":{\"e\":{\"f\":{\"g\":3,\"h\":[{\"z\":1},{\"y\":2,\"x\":[{},{}]}]}}},\"i\":4,\"j\":[],\"k\":{\"l\":5,\"m\":{}}}}";
+        let actual = parse(s).unwrap();
+        let expected = object! {
+            a: 1,
+            b: {
+                c: 2,
+                d: {
+                    e: {
+                        f: {
+                            g: 3,
+                            h: [
+                                { z: 1 },
+                                { y: 2, x: [{}, {}]}
+                            ]
+                        }
+                    }
+                },
+                i: 4,
+                j: [],
+                k: {
+                    l: 5,
+                    m: {}
+                }
+            }
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,189 @@
+// Pull parsing. Instead of building a `JsonValue`, the document is handed
+// out one event at a time, as the caller asks for them. Every event is a
+// step of the walk through the source, with strings, keys and literals read
+// the same way `Parser::parse` reads them. Keys that are already in an
+// object don't count towards the limit on entries, as there's only ever one
+// entry for each key in a parsed object, so with a limit set the keys are
+// kept too.
+//
+// Skipping doesn't decode anything, it only looks for brackets outside of
+// strings (and comments), which makes it a lot cheaper than reading events
+// just to throw them away.
+
+use std::{ str, slice };
+
+use crate::number::Number;
+use crate::{ Error, Expected, Result };
+use super::{ Parser, ParserOptions, ALLOWED, DEFAULT_OPTIONS };
+use super::walk::{ Walk, Step, Scalar };
+
+/// A single step through a document, as returned from
+/// `PullParser::next_event`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+
+    /// Key of an object entry. Its value is the next event.
+    Key(&'a str),
+
+    String(&'a str),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+/// A parser that goes through a document one event at a time, without
+/// building a `JsonValue`. Useful when only a small part of the document
+/// is needed, or when it's only scanned, to count records for example.
+///
+/// Parsing follows the same rules as `parse_with`, except for
+/// `DuplicateKeys`, as there are no objects for keys to be duplicated in,
+/// and `lossless_numbers`, as numbers are always a `Number`.
+/// Once an error is returned, every call after that will return it as well.
+///
+/// ```
+/// # fn main() -> json::Result<()> {
+/// use json::pull::{ PullParser, Event };
+///
+/// let source = r#"[
+///     { "name": "Ann", "tags": ["admin", "ops"], "active": true },
+///     { "name": "Bob", "tags": [], "active": false }
+/// ]"#;
+///
+/// let mut parser = PullParser::new(source);
+/// let mut names = Vec::new();
+///
+/// while let Some(event) = parser.next_event()? {
+///     match event {
+///         Event::Key("name") => {
+///             if let Some(Event::String(name)) = parser.next_event()? {
+///                 names.push(name.to_owned());
+///             }
+///         },
+///         Event::Key("tags") => {
+///             // Skip over the array, `[` first
+///             parser.next_event()?;
+///             parser.skip()?;
+///         },
+///         _ => {}
+///     }
+/// }
+///
+/// assert_eq!(names, ["Ann", "Bob"]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct PullParser<'a> {
+    parser: Parser<'a>,
+
+    // Where the parser is in the document
+    walk: Walk,
+
+    // Once something is wrong, it stays wrong
+    error: Option<Error>,
+}
+
+impl<'a> PullParser<'a> {
+    /// Create a new `PullParser` for the `source`, with the default options.
+    pub fn new(source: &'a str) -> Self {
+        PullParser::with_options(source, &DEFAULT_OPTIONS)
+    }
+
+    /// Create a new `PullParser` for the `source`, that enforces the limits
+    /// set on `options`.
+    pub fn with_options(source: &'a str, options: &'a ParserOptions) -> Self {
+        PullParser {
+            parser: Parser::new(source, options),
+            walk: Walk::new(options),
+            error: None,
+        }
+    }
+
+    /// Read the next event, or `None` once the document is done. Strings
+    /// and keys are borrowed either from the source, or from the parser
+    /// if they had escapes in them.
+    pub fn next_event(&mut self) -> Result<Option<Event<'_>>> {
+        if let Some(ref error) = self.error {
+            return Err(error.clone());
+        }
+
+        match self.parser.pull(&mut self.walk) {
+            Err(error) => {
+                self.error = Some(error.clone());
+                Err(error)
+            },
+            event => event,
+        }
+    }
+
+    /// Skip the rest of the innermost container that's still open, up to
+    /// and including its end. Called right after `StartObject` or
+    /// `StartArray`, that skips the whole container. Does nothing if there
+    /// is no container open.
+    ///
+    /// Nothing is decoded while skipping. Only brackets are checked to
+    /// match up, strings and numbers are not checked at all.
+    pub fn skip(&mut self) -> Result<()> {
+        if let Some(ref error) = self.error {
+            return Err(error.clone());
+        }
+
+        match self.parser.skip_open(&mut self.walk) {
+            Err(error) => {
+                self.error = Some(error.clone());
+                Err(error)
+            },
+            ok => ok,
+        }
+    }
+
+    /// Number of containers that are still open.
+    pub fn depth(&self) -> usize {
+        self.walk.depth()
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn pull<'e>(&mut self, walk: &mut Walk) -> Result<Option<Event<'e>>> {
+        if self.length > self.options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        // The last event is gone, and with it any strings from the buffer
+        self.buffer.clear();
+
+        let event = match self.step(walk)? {
+            Step::Open(false)  => Event::StartArray,
+            Step::Open(true)   => Event::StartObject,
+            Step::Close(false) => Event::EndArray,
+            Step::Close(true)  => Event::EndObject,
+            Step::Key(ch, expected) => {
+                let key = expect_key!(self, ch, expected);
+
+                // Keys are only kept to tell whether they're new, for the
+                // limit, duplicates are otherwise left to the caller
+                let new = match walk.open() {
+                    Some(open) if self.options.object_entries_limit != usize::MAX => open.insert(key) == 1,
+                    _                                                              => true,
+                };
+
+                self.count_entry(walk, new)?;
+
+                Event::Key(key)
+            },
+            Step::Scalar(ch) => match self.read_scalar(ch)? {
+                Scalar::String(string)   => Event::String(string),
+                Scalar::Number(number)   => Event::Number(number),
+                Scalar::Decimal(decimal) => Event::Number(decimal.to_number()),
+                Scalar::Boolean(value)   => Event::Bool(value),
+                Scalar::Null             => Event::Null,
+            },
+            Step::End => return Ok(None),
+        };
+
+        Ok(Some(event))
+    }
+}
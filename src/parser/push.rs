@@ -0,0 +1,914 @@
+// Push parsing. Instead of pulling bytes from the source like the other
+// parsers do, this one is handed chunks of it whenever they arrive, and has to
+// be able to stop at any byte and pick up where it left off later. All of the
+// state lives in the `PushParser` itself: containers are built on the same
+// stack of `StackBlock`s that `Parser::parse` uses, and `State` says what
+// is expected next.
+//
+// Strings, numbers, comments and unexpected characters can span any number
+// of chunks. Their bytes are collected into a token, which is then handed
+// over to a regular `Parser` to decode once complete. That way escapes,
+// surrogate pairs and number edge cases are all dealt with by the same code
+// as everywhere else, and errors end up at the same positions too.
+
+use std::mem;
+
+use crate::object::Object;
+use crate::{ JsonValue, Error, Expected, Result };
+use super::{ Parser, ParserOptions, LineCounter, StackBlock, Duplicates };
+
+/// Result of feeding a `PushParser`.
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    /// All input so far has been consumed, but it doesn't make for a
+    /// complete value yet.
+    Incomplete,
+
+    /// A value has been parsed. There might be more input left after it,
+    /// which will be parsed on the next call to `feed`.
+    Complete(JsonValue),
+}
+
+// What the parser is looking at.
+#[derive(Clone, Copy)]
+enum State {
+    // Any value, this is where a document starts
+    Value,
+
+    // Past the value of a document, nothing but whitespace and comments
+    // can follow
+    End,
+
+    // Right after `[`, either a value or `]`
+    ArrayStart,
+
+    // Value after `,` in an array, which could also be `]` if trailing
+    // commas are allowed
+    Element,
+
+    // Right after `{`, either a key or `}`
+    ObjectStart,
+
+    // Key after `,` in an object
+    Key,
+
+    // `:` after a key
+    Colon,
+
+    // `,` or the end of the current container
+    Next,
+
+    // Inside of a string, `escaped` if the last byte was a backslash
+    String { key: bool, quote: u8, escaped: bool },
+
+    // Inside of an unquoted key
+    Identifier,
+
+    // Inside of a number
+    Number,
+
+    // Inside of `true`, `false` or `null`
+    Literal { literal: &'static [u8], matched: usize },
+
+    // Collecting a character that's not allowed, `width` bytes long, where
+    // `expected` would have been
+    Unexpected { width: usize, expected: Expected },
+
+    // Inside of a comment, after which parsing resumes in `resume`
+    Comment(Comment),
+}
+
+// Which part of a comment the parser is in.
+#[derive(Clone, Copy)]
+enum Comment {
+    // Just the `/`, the next byte says what kind of comment it is
+    Slash,
+
+    // `//` until the end of the line
+    Line,
+
+    // `/*` until `*/`
+    Block,
+
+    // `*` inside of a block comment, which could be its end
+    BlockStar,
+}
+
+// Push a finished value onto the stack. If it's the top level value,
+// return it, with the rest of the input starting at `$end`.
+macro_rules! push_value {
+    ($parser:ident, $bytes:ident, $end:expr, $value:expr) => {
+        if let Some(value) = $parser.push_value($value)? {
+            return $parser.complete($bytes, $end, value);
+        }
+    }
+}
+
+/// A parser that is fed the source in chunks of any size, such as they
+/// come in from a non-blocking socket. Chunks can be split anywhere, even
+/// in the middle of a string, an escape sequence or a multi-byte character.
+///
+/// Once a value is complete, it's returned from `feed` straight away. Any
+/// input that was fed after that value is kept for the next one, so a single
+/// parser can be used for a stream of values. Call `feed` with an empty
+/// chunk to get them out.
+///
+/// Parsing follows the same rules as `parse_bytes`, except for `size_limit`,
+/// which applies to every value separately. Positions of errors are counted
+/// from the very first byte fed. Once an error is returned, every call after
+/// that will return it as well.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// use json::push::{ PushParser, Status };
+///
+/// let mut parser = PushParser::new();
+///
+/// assert_eq!(parser.feed(b"[1, \"fo").unwrap(), Status::Incomplete);
+/// assert_eq!(parser.feed(b"o\"] {\"bar\"").unwrap(), Status::Complete(array![1, "foo"]));
+/// assert_eq!(parser.feed(b": 10} 4").unwrap(), Status::Complete(object!{ bar: 10 }));
+/// assert_eq!(parser.feed(b"2").unwrap(), Status::Incomplete);
+///
+/// // There is no telling if 42 is done until there is no more input
+/// assert_eq!(parser.finish().unwrap(), Some(42.into()));
+/// # }
+/// ```
+pub struct PushParser {
+    // Limits to enforce while parsing
+    options: ParserOptions,
+
+    // What to expect next
+    state: State,
+
+    // What to expect after a comment
+    resume: State,
+
+    // Containers that are still open
+    stack: Vec<StackBlock>,
+
+    // Deals with duplicate keys
+    duplicates: Duplicates,
+
+    // Lines and columns before the current key, for when it's a duplicate
+    key_lines: LineCounter,
+
+    // Bytes of the current string, key, number, comment, or unexpected
+    // character
+    token: Vec<u8>,
+
+    // Offset of the token in the source
+    token_offset: usize,
+
+    // Lines and columns before the token
+    token_lines: LineCounter,
+
+    // Where the token starts in the chunk being processed, 0 if it started
+    // in one of the previous chunks.
+    from: usize,
+
+    // Offset of the chunk being processed in the source
+    offset: usize,
+
+    // Lines and columns up to `synced` bytes into the chunk. These are only
+    // counted when needed, so that bytes can be skipped over in bulk.
+    lines: LineCounter,
+    synced: usize,
+
+    // Offset at which the current value started, for `size_limit`
+    start: usize,
+
+    // Input left over after the last complete value. Everything before
+    // `skip` has already been parsed.
+    pending: Vec<u8>,
+    skip: usize,
+
+    // Where the input after a completed value starts in the chunk
+    rest: usize,
+
+    // Once something is wrong, it stays wrong
+    error: Option<Error>,
+
+    // Only a single value is allowed
+    document: bool,
+}
+
+impl PushParser {
+    /// Create a new `PushParser` with the default options.
+    pub fn new() -> Self {
+        PushParser::with_options(&ParserOptions::new())
+    }
+
+    /// Create a new `PushParser` that enforces the limits set on `options`.
+    pub fn with_options(options: &ParserOptions) -> Self {
+        PushParser {
+            options: options.clone(),
+            state: State::Value,
+            resume: State::Value,
+            stack: Vec::with_capacity(3),
+            duplicates: Duplicates::new(options),
+            key_lines: LineCounter::default(),
+            token: Vec::with_capacity(30),
+            token_offset: 0,
+            token_lines: LineCounter::default(),
+            from: 0,
+            offset: 0,
+            lines: LineCounter::default(),
+            synced: 0,
+            start: 0,
+            pending: Vec::new(),
+            skip: 0,
+            rest: 0,
+            error: None,
+            document: false,
+        }
+    }
+
+    // Create a `PushParser` for a single document, like `parse_bytes`
+    // would parse. The value is still returned from `feed` as soon as it's
+    // complete, but anything other than whitespace or comments after it
+    // is an error.
+    #[cfg(feature = "async")]
+    pub(super) fn document(options: &ParserOptions) -> Self {
+        PushParser {
+            document: true,
+            ..PushParser::with_options(options)
+        }
+    }
+
+    /// Feed the next chunk of input to the parser. Returns a value as soon
+    /// as it's complete. Whatever input is left after it stays in the parser,
+    /// call `feed` with an empty chunk to continue with it.
+    ///
+    /// ```
+    /// use json::push::{ PushParser, Status };
+    ///
+    /// let mut parser = PushParser::new();
+    /// let mut values = Vec::new();
+    ///
+    /// for mut chunk in vec![&b"[1] [2"[..], b"] [3] [4] ", b"[5]"] {
+    ///     while let Status::Complete(value) = parser.feed(chunk).unwrap() {
+    ///         values.push(value);
+    ///         chunk = &[];
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(values.len(), 5);
+    /// ```
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<Status> {
+        if let Some(ref error) = self.error {
+            return Err(error.clone());
+        }
+
+        let result = if self.skip == self.pending.len() {
+            self.pending.clear();
+            self.skip = 0;
+
+            let result = self.process(bytes);
+
+            if let Ok(Status::Complete(_)) = result {
+                self.pending.extend_from_slice(&bytes[self.rest..]);
+            }
+
+            result
+        } else {
+            let mut pending = mem::take(&mut self.pending);
+
+            pending.extend_from_slice(bytes);
+
+            let result = self.process(&pending[self.skip..]);
+
+            self.skip = match result {
+                Ok(Status::Complete(_)) => self.skip + self.rest,
+                _                       => pending.len(),
+            };
+            self.pending = pending;
+
+            result
+        };
+
+        if let Err(ref error) = result {
+            self.error = Some(error.clone());
+        }
+
+        result
+    }
+
+    /// Signal the end of input. Returns the last value if there is one,
+    /// which can be a number that wasn't followed by anything, or `None`
+    /// if there is nothing but whitespace left. Anything else that is left
+    /// unfinished is an error.
+    pub fn finish(&mut self) -> Result<Option<JsonValue>> {
+        if let Status::Complete(value) = self.feed(&[])? {
+            return Ok(Some(value));
+        }
+
+        let result = self.end();
+
+        if let Err(ref error) = result {
+            self.error = Some(error.clone());
+        }
+
+        result
+    }
+
+    fn end(&mut self) -> Result<Option<JsonValue>> {
+        match self.state {
+            State::Value if self.stack.is_empty() => Ok(None),
+            State::End => Ok(None),
+            State::Number => {
+                if let Some(number) = self.read_number(false)? {
+                    if let Some(value) = self.push_value(number)? {
+                        self.start = self.offset;
+
+                        return Ok(Some(value));
+                    }
+                }
+
+                Err(Error::UnexpectedEndOfJson)
+            },
+            // Running out of input isn't necessarily the first thing that's
+            // wrong with the string, the `Parser` will know.
+            State::String { .. } => self.read_string().and(Err(Error::UnexpectedEndOfJson)),
+            State::Unexpected { expected, .. } => self.unexpected(expected),
+            // Only a line comment can end with the input
+            State::Comment(_) => {
+                self.read_comment()?;
+                self.state = self.resume;
+                self.end()
+            },
+            _ => Err(Error::UnexpectedEndOfJson),
+        }
+    }
+
+    fn process(&mut self, bytes: &[u8]) -> Result<Status> {
+        let mut index = 0;
+
+        while index < bytes.len() {
+            let byte = bytes[index];
+
+            match self.state {
+                State::Value => match byte {
+                    9 ..= 13 | 32 => {},
+                    b'/' if self.options.comments => self.begin_comment(bytes, index)?,
+                    b'[' => self.state = State::ArrayStart,
+                    b'{' => self.state = State::ObjectStart,
+                    b'"' => self.begin_string(bytes, index, false)?,
+                    b'\'' if self.options.single_quotes => self.begin_string(bytes, index, false)?,
+                    b'0' ..= b'9' | b'-' => {
+                        self.begin(bytes, index, State::Number)?;
+                        continue;
+                    },
+                    b'+' if self.options.leading_plus => {
+                        self.begin(bytes, index, State::Number)?;
+                        continue;
+                    },
+                    b'.' if self.options.leading_decimal_point => {
+                        self.begin(bytes, index, State::Number)?;
+                        continue;
+                    },
+                    b't' => {
+                        self.state = State::Literal { literal: b"true", matched: 0 };
+                        continue;
+                    },
+                    b'f' => {
+                        self.state = State::Literal { literal: b"false", matched: 0 };
+                        continue;
+                    },
+                    b'n' => {
+                        self.state = State::Literal { literal: b"null", matched: 0 };
+                        continue;
+                    },
+                    _ => {
+                        self.unexpected_at(bytes, index, Expected::Value)?;
+                        continue;
+                    }
+                },
+
+                State::End => match byte {
+                    9 ..= 13 | 32 => {},
+                    b'/' if self.options.comments => self.begin_comment(bytes, index)?,
+                    _ => {
+                        self.unexpected_at(bytes, index, Expected::EndOfJson)?;
+                        continue;
+                    }
+                },
+
+                State::ArrayStart => match byte {
+                    9 ..= 13 | 32 => {},
+                    b'/' if self.options.comments => self.begin_comment(bytes, index)?,
+                    b']' => push_value!(self, bytes, index + 1, JsonValue::Array(Vec::new())),
+                    _ => {
+                        if self.stack.len() == self.options.depth_limit {
+                            return Err(Error::ExceededDepthLimit);
+                        }
+
+                        self.stack.push(StackBlock(JsonValue::Array(Vec::with_capacity(2)), 0));
+                        self.state = State::Value;
+                        continue;
+                    }
+                },
+
+                State::Element => match byte {
+                    9 ..= 13 | 32 => {},
+                    b'/' if self.options.comments => self.begin_comment(bytes, index)?,
+                    b']' if self.options.trailing_commas => {
+                        let value = self.pop();
+
+                        push_value!(self, bytes, index + 1, value);
+                    },
+                    _ => {
+                        self.state = State::Value;
+                        continue;
+                    }
+                },
+
+                State::ObjectStart => match byte {
+                    9 ..= 13 | 32 => {},
+                    b'/' if self.options.comments => self.begin_comment(bytes, index)?,
+                    b'}' => push_value!(self, bytes, index + 1, JsonValue::Object(Object::new())),
+                    _ => {
+                        if self.stack.len() == self.options.depth_limit {
+                            return Err(Error::ExceededDepthLimit);
+                        }
+
+                        // Index is set once the key has been read
+                        self.duplicates.open(self.stack.len());
+                        self.stack.push(StackBlock(JsonValue::Object(Object::with_capacity(3)), 0));
+                        self.state = State::Key;
+                        continue;
+                    }
+                },
+
+                State::Key => match byte {
+                    9 ..= 13 | 32 => {},
+                    b'/' if self.options.comments => self.begin_comment(bytes, index)?,
+                    b'"' => self.begin_string(bytes, index, true)?,
+                    b'\'' if self.options.single_quotes => self.begin_string(bytes, index, true)?,
+                    b'a' ..= b'z' |
+                    b'A' ..= b'Z' |
+                    b'_' | b'$' if self.options.unquoted_keys => {
+                        self.begin(bytes, index, State::Identifier)?;
+                        self.key_lines = self.lines;
+                    },
+                    b'}' if self.options.trailing_commas => {
+                        let value = self.pop();
+
+                        push_value!(self, bytes, index + 1, value);
+                    },
+                    _ => {
+                        // The object is empty until its first key is in
+                        let expected = match self.stack.last() {
+                            Some(&StackBlock(JsonValue::Object(ref object), _)) if object.is_empty() => {
+                                Expected::KeyOrEndOfObject
+                            },
+                            _ => Expected::Key,
+                        };
+
+                        self.unexpected_at(bytes, index, expected)?;
+                        continue;
+                    }
+                },
+
+                State::Colon => match byte {
+                    9 ..= 13 | 32 => {},
+                    b'/' if self.options.comments => self.begin_comment(bytes, index)?,
+                    b':' => self.state = State::Value,
+                    _ => {
+                        self.unexpected_at(bytes, index, Expected::Colon)?;
+                        continue;
+                    }
+                },
+
+                State::Next => {
+                    let (next, closing, expected) = match self.stack.last() {
+                        Some(&StackBlock(JsonValue::Object(_), _)) => {
+                            (State::Key, b'}', Expected::CommaOrEndOfObject)
+                        },
+                        _ => (State::Element, b']', Expected::CommaOrEndOfArray),
+                    };
+
+                    match byte {
+                        9 ..= 13 | 32 => {},
+                        b'/' if self.options.comments => self.begin_comment(bytes, index)?,
+                        b',' => self.state = next,
+                        _ if byte == closing => {
+                            let value = self.pop();
+
+                            push_value!(self, bytes, index + 1, value);
+                        },
+                        _ => {
+                            self.unexpected_at(bytes, index, expected)?;
+                            continue;
+                        }
+                    }
+                },
+
+                State::String { key, quote, mut escaped } => {
+                    // Only look for the closing quote here, the bytes are
+                    // checked once the whole string is there.
+                    let mut closed = false;
+
+                    while index < bytes.len() {
+                        let ch = bytes[index];
+                        index += 1;
+
+                        if ch < 0x20 {
+                            // Not allowed even if escaped, but there might
+                            // be something wrong before it.
+                            self.take_token(bytes, index);
+                            return self.read_string().and(Err(Error::UnexpectedEndOfJson));
+                        }
+
+                        if escaped {
+                            escaped = false;
+                            continue;
+                        }
+
+                        match ch {
+                            b'\\' => escaped = true,
+                            _ if ch == quote => {
+                                closed = true;
+                                break;
+                            },
+                            _     => {}
+                        }
+                    }
+
+                    if !closed {
+                        self.state = State::String { key, quote, escaped };
+                        continue;
+                    }
+
+                    self.take_token(bytes, index);
+
+                    if key {
+                        let mut parser = Parser::from_token(
+                            &self.token,
+                            &self.options,
+                            self.token_offset,
+                            self.token_lines,
+                        );
+                        let key = match quote {
+                            b'"' => parser.read_string()?,
+                            _    => parser.read_single_quoted_string()?,
+                        };
+
+                        if !insert_key(&mut self.stack, &mut self.duplicates, &self.options, key)? {
+                            return self.duplicate_key(key);
+                        }
+
+                        self.state = State::Colon;
+                    } else {
+                        let value = self.read_string()?;
+
+                        push_value!(self, bytes, index, value);
+                    }
+
+                    continue;
+                },
+
+                State::Identifier => match byte {
+                    b'a' ..= b'z' |
+                    b'A' ..= b'Z' |
+                    b'0' ..= b'9' |
+                    b'_' | b'$'   => {},
+                    _ => {
+                        self.take_token(bytes, index);
+
+                        let mut parser = Parser::from_token(
+                            &self.token,
+                            &self.options,
+                            self.token_offset,
+                            self.token_lines,
+                        );
+
+                        parser.bump();
+
+                        let key = parser.read_identifier()?;
+
+                        if !insert_key(&mut self.stack, &mut self.duplicates, &self.options, key)? {
+                            return self.duplicate_key(key);
+                        }
+
+                        self.state = State::Colon;
+                        continue;
+                    }
+                },
+
+                State::Number => match byte {
+                    b'0' ..= b'9' | b'.' | b'e' | b'E' | b'+' | b'-' => {},
+                    b'x' | b'X' |
+                    b'a' ..= b'f' |
+                    b'A' ..= b'F' if self.options.hex_numbers => {},
+                    _ => {
+                        // Whatever comes after the number is handled in
+                        // the next state, so it's not consumed here.
+                        self.take_token(bytes, index);
+
+                        // Incomplete numbers are missing a digit, unless
+                        // they are hexadecimal and only have the `0x`.
+                        match self.read_number(true)? {
+                            Some(number) => push_value!(self, bytes, index, number),
+                            None         => {
+                                let expected = match self.token.last() {
+                                    Some(b'x') | Some(b'X') => Expected::HexDigit,
+                                    _                       => Expected::Digit,
+                                };
+
+                                self.unexpected_at(bytes, index, expected)?;
+                            },
+                        }
+
+                        continue;
+                    }
+                },
+
+                State::Literal { literal, matched } => {
+                    if byte != literal[matched] {
+                        let expected = match literal[0] {
+                            b't' => Expected::Literal("true"),
+                            b'f' => Expected::Literal("false"),
+                            _    => Expected::Literal("null"),
+                        };
+
+                        self.unexpected_at(bytes, index, expected)?;
+                        continue;
+                    }
+
+                    if matched + 1 < literal.len() {
+                        self.state = State::Literal { literal, matched: matched + 1 };
+                    } else {
+                        let value = match literal[0] {
+                            b't' => JsonValue::Boolean(true),
+                            b'f' => JsonValue::Boolean(false),
+                            _    => JsonValue::Null,
+                        };
+
+                        push_value!(self, bytes, index + 1, value);
+                    }
+                },
+
+                State::Unexpected { width, expected } => {
+                    index += 1;
+
+                    if self.token.len() + index - self.from >= width {
+                        self.take_token(bytes, index);
+                        return self.unexpected(expected);
+                    }
+
+                    continue;
+                },
+
+                State::Comment(comment) => {
+                    let comment = match (comment, byte) {
+                        (Comment::Slash, b'/')     => Comment::Line,
+                        (Comment::Slash, b'*')     => Comment::Block,
+                        (Comment::Slash, _)        => {
+                            self.unexpected_at(bytes, index, Expected::Comment)?;
+                            continue;
+                        },
+                        (Comment::Line, b'\n')     |
+                        (Comment::BlockStar, b'/') => {
+                            // Check the comment for valid UTF-8
+                            self.take_token(bytes, index + 1);
+                            self.read_comment()?;
+                            self.state = self.resume;
+                            index += 1;
+                            continue;
+                        },
+                        (Comment::Line, _)         => Comment::Line,
+                        (_, b'*')                  => Comment::BlockStar,
+                        (_, _)                     => Comment::Block,
+                    };
+
+                    self.state = State::Comment(comment);
+                },
+            }
+
+            index += 1;
+        }
+
+        if let State::String { .. } | State::Identifier | State::Number | State::Unexpected { .. } | State::Comment(_) = self.state {
+            self.take_token(bytes, bytes.len());
+        }
+
+        self.sync(bytes, bytes.len())?;
+        self.offset += bytes.len();
+        self.synced = 0;
+        self.from = 0;
+
+        Ok(Status::Incomplete)
+    }
+
+    // The top level value is done, the rest of the chunk starts at `end`.
+    fn complete(&mut self, bytes: &[u8], end: usize, value: JsonValue) -> Result<Status> {
+        self.sync(bytes, end)?;
+        self.offset += end;
+        self.synced = 0;
+        self.from = 0;
+        self.start = self.offset;
+        self.rest = end;
+
+        Ok(Status::Complete(value))
+    }
+
+    // Take the container on top of the stack off of it, it's done.
+    fn pop(&mut self) -> JsonValue {
+        match self.stack.pop() {
+            Some(StackBlock(value, _)) => value,
+            None                       => unreachable!(),
+        }
+    }
+
+    // Put a finished value into the container it belongs to. If there is
+    // no container, it's the top level value and it's given back.
+    fn push_value(&mut self, value: JsonValue) -> Result<Option<JsonValue>> {
+        match self.stack.last_mut() {
+            None => {
+                self.state = if self.document { State::End } else { State::Value };
+
+                return Ok(Some(value));
+            },
+
+            Some(&mut StackBlock(JsonValue::Array(ref mut array), _)) => {
+                if array.len() == self.options.array_length_limit {
+                    return Err(Error::ExceededArrayLengthLimit);
+                }
+
+                array.push(value);
+            },
+
+            Some(&mut StackBlock(JsonValue::Object(ref mut object), index)) => {
+                self.duplicates.set(object, index, value)?;
+            },
+
+            _ => unreachable!(),
+        }
+
+        self.state = State::Next;
+
+        Ok(None)
+    }
+
+    // Count lines up to `at` in the chunk.
+    fn sync(&mut self, bytes: &[u8], at: usize) -> Result<()> {
+        self.lines.advance(&bytes[self.synced..at]);
+        self.synced = at;
+
+        if self.offset + at - self.start > self.options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        Ok(())
+    }
+
+    // Start a new token at `at` in the chunk.
+    fn begin(&mut self, bytes: &[u8], at: usize, state: State) -> Result<()> {
+        self.sync(bytes, at)?;
+        self.token.clear();
+        self.token_offset = self.offset + at;
+        self.token_lines = self.lines;
+        self.from = at;
+        self.state = state;
+
+        Ok(())
+    }
+
+    // Start a string at its opening quote at `at`, which is also the
+    // quote it has to end with.
+    fn begin_string(&mut self, bytes: &[u8], at: usize, key: bool) -> Result<()> {
+        let quote = bytes[at];
+
+        if key {
+            self.sync(bytes, at)?;
+            self.key_lines = self.lines;
+        }
+
+        self.begin(bytes, at + 1, State::String { key, quote, escaped: false })
+    }
+
+    // Start a comment at `at`, then carry on in the current state.
+    fn begin_comment(&mut self, bytes: &[u8], at: usize) -> Result<()> {
+        self.resume = self.state;
+        self.begin(bytes, at, State::Comment(Comment::Slash))
+    }
+
+    // The byte at `at` is not allowed, collect the character it starts.
+    fn unexpected_at(&mut self, bytes: &[u8], at: usize, expected: Expected) -> Result<()> {
+        let width = match bytes[at] {
+            0xC2 ..= 0xDF => 2,
+            0xE0 ..= 0xEF => 3,
+            0xF0 ..= 0xF4 => 4,
+            _             => 1,
+        };
+
+        self.begin(bytes, at, State::Unexpected { width, expected })
+    }
+
+    // Add the token bytes in the chunk up to `end`.
+    fn take_token(&mut self, bytes: &[u8], end: usize) {
+        self.token.extend_from_slice(&bytes[self.from..end]);
+        self.from = end;
+    }
+
+    fn token_parser(&self) -> Parser<'_> {
+        Parser::from_token(&self.token, &self.options, self.token_offset, self.token_lines)
+    }
+
+    // Decode the string in the token.
+    fn read_string(&self) -> Result<JsonValue> {
+        let mut parser = self.token_parser();
+
+        Ok(match self.state {
+            State::String { quote: b'"', .. } => parser.read_string()?,
+            _                                 => parser.read_single_quoted_string()?,
+        }.into())
+    }
+
+    // Check the comment in the token.
+    fn read_comment(&self) -> Result<()> {
+        let mut parser = self.token_parser();
+
+        parser.bump();
+        parser.skip_comment()
+    }
+
+    // Decode the number in the token. If `terminated`, the token was
+    // followed by something that can't be a part of a number, and `None`
+    // means the number is incomplete, which makes that the unexpected
+    // character.
+    fn read_number(&self, terminated: bool) -> Result<Option<JsonValue>> {
+        let mut parser = self.token_parser();
+
+        match parser.read_number_value() {
+            Ok(number) => {
+                if parser.index < parser.length {
+                    let expected = match self.stack.last() {
+                        Some(&StackBlock(JsonValue::Object(_), _)) => Expected::CommaOrEndOfObject,
+                        Some(_)                                    => Expected::CommaOrEndOfArray,
+                        None                                       => Expected::EndOfJson,
+                    };
+
+                    parser.bump();
+                    return parser.unexpected_character(expected);
+                }
+
+                Ok(Some(number))
+            },
+            Err(Error::UnexpectedEndOfJson) if terminated => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    // The key that was just read is already in the object, and that's not
+    // allowed.
+    #[cold]
+    fn duplicate_key<T>(&self, key: &str) -> Result<T> {
+        let (line, column) = self.key_lines.position();
+
+        Err(Error::DuplicateKey {
+            key: key.into(),
+            line,
+            column,
+        })
+    }
+
+    // Report the unexpected character in the token.
+    fn unexpected<T>(&self, expected: Expected) -> Result<T> {
+        let mut parser = self.token_parser();
+
+        parser.bump();
+        parser.unexpected_character(expected)
+    }
+}
+
+// Add a key to the object on top of the stack, its value is set later.
+// Returns `false` if it's a duplicate that isn't allowed.
+fn insert_key(
+    stack: &mut [StackBlock],
+    duplicates: &mut Duplicates,
+    options: &ParserOptions,
+    key: &str,
+) -> Result<bool> {
+    let depth = stack.len() - 1;
+
+    if let Some(&mut StackBlock(JsonValue::Object(ref mut object), ref mut index)) = stack.last_mut() {
+        *index = match duplicates.insert(object, depth, key) {
+            Some(index) => index,
+            None        => return Ok(false),
+        };
+
+        if object.len() > options.object_entries_limit {
+            return Err(Error::ExceededObjectEntriesLimit);
+        }
+    }
+
+    Ok(true)
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        PushParser::new()
+    }
+}
@@ -0,0 +1,100 @@
+// Parsing from `io::Read`. Instead of reading everything into a `String`
+// first, the source is pulled into a window that only holds the bytes the
+// parser hasn't gone past yet. Strings are the only thing that need to stay
+// in the window until they are done, so it never grows much larger than the
+// longest string in the document.
+
+use std::io::{ self, Read };
+
+use crate::{ JsonValue, Result };
+use super::{ Parser, ParserOptions, Refill, DEFAULT_OPTIONS };
+
+// How many bytes to ask the reader for at a time.
+pub(super) const CHUNK_SIZE: usize = 8 * 1024;
+
+pub(super) struct ReadWindow<R: Read> {
+    reader: R,
+    window: Vec<u8>,
+}
+
+impl<R: Read> ReadWindow<R> {
+    pub fn new(reader: R) -> Self {
+        ReadWindow {
+            reader,
+            window: Vec::with_capacity(CHUNK_SIZE),
+        }
+    }
+}
+
+impl<R: Read> Refill for ReadWindow<R> {
+    fn refill(&mut self, consumed: usize) -> io::Result<()> {
+        let len = self.window.len();
+
+        self.window.resize(len + CHUNK_SIZE, 0);
+
+        loop {
+            match self.reader.read(&mut self.window[len..]) {
+                Ok(read) => {
+                    self.window.truncate(len + read);
+                    break;
+                },
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {},
+                Err(error) => {
+                    self.window.truncate(len);
+                    return Err(error);
+                }
+            }
+        }
+
+        self.window.drain(..consumed);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn window(&self) -> &[u8] {
+        &self.window
+    }
+}
+
+/// Parse a JSON document from an implementor of `std::io::Read`, such as
+/// a `File` or a `TcpStream`. The source is read in chunks as the parser
+/// needs them, so it doesn't have to be kept in memory along with the
+/// parsed value. There is no need to wrap the reader in a `BufReader`.
+///
+/// The result, including error positions, is the same as parsing the
+/// whole source with `parse_bytes`. Errors from the reader itself are
+/// reported as `Error::Io`.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// let source: &[u8] = br#"{"foo":[1,2,3]}"#;
+///
+/// assert_eq!(json::parse_reader(source).unwrap(), object!{ foo: [1, 2, 3] });
+/// # }
+/// ```
+pub fn parse_reader<R: Read>(reader: R) -> Result<JsonValue> {
+    parse_reader_with(reader, &DEFAULT_OPTIONS)
+}
+
+/// Parse a JSON document from an implementor of `std::io::Read` while
+/// enforcing the limits set on `options`.
+///
+/// Unlike `parse_bytes_with`, which checks the size of the whole source
+/// before anything else, `size_limit` can only be checked as the source
+/// is read. An error in what was read before the limit is hit is returned
+/// instead of `Error::ExceededSizeLimit`.
+pub fn parse_reader_with<R: Read>(reader: R, options: &ParserOptions) -> Result<JsonValue> {
+    let mut window = ReadWindow::new(reader);
+
+    let mut parser = Parser::from_refill(&mut window, options);
+    let result = parser.parse();
+
+    // The parser can't tell a failed read from the end of the source,
+    // whatever it did after that is irrelevant.
+    match parser.refill_error.take() {
+        Some(error) => Err(error),
+        None        => result,
+    }
+}
@@ -0,0 +1,621 @@
+// Parsing that carries on past errors, for editors and other tools that have
+// to deal with documents in the middle of being written. It goes over the
+// source with the same predictions as `Parser::parse`, and reads the same
+// tokens with the same methods, but whenever the prediction doesn't hold,
+// the error is noted down and the source is patched up, or skipped over,
+// until things make sense again.
+//
+// Unlike `Parser::parse`, it walks through the source one step at a time,
+// the same as everything else that has to know where it is. Speed is not
+// the point here, being able to step back in after an error is: when a step
+// fails, what the walk expected at that point says how to carry on.
+
+use crate::object::Object;
+use crate::{ JsonValue, Error, Expected, Result };
+use super::{ Parser, ParserOptions, StackBlock, DEFAULT_OPTIONS };
+use super::walk::{ Walk, Step, State };
+
+/// Parse a JSON document that might be broken. Instead of failing at the
+/// first error, the parser notes it down and carries on with its best guess
+/// of what was meant. Returns the value it ended up with, along with all the
+/// errors found on the way, in order. Without any errors the value is the
+/// same as from `parse`.
+///
+/// To keep going, the parser:
+///
+/// - skips anything that can't be where it is,
+/// - assumes missing commas and colons are there,
+/// - uses `null` for missing values of object entries,
+/// - leaves out missing array elements,
+/// - keeps what's between the quotes of broken strings as it is, up to the
+///   end of the line,
+/// - closes containers that are left open at a closing bracket of another
+///   container, or at the end of the source.
+///
+/// An error is only reported if something was parsed fine since the last
+/// one, so that a single mistake doesn't end up reported over and over.
+/// Exceeding a limit still stops the parsing, with all the containers so far
+/// closed up.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// let (value, errors) = json::parse_recover(r#"{ "a": [1 2], "b": tru, "c": "#);
+///
+/// assert_eq!(value, object!{ a: [1, 2], b: true, c: null });
+/// assert_eq!(errors.len(), 3);
+/// assert_eq!(errors[2], json::Error::UnexpectedEndOfJson);
+/// # }
+/// ```
+pub fn parse_recover(source: &str) -> (JsonValue, Vec<Error>) {
+    Parser::new(source, &DEFAULT_OPTIONS).parse_recover()
+}
+
+/// Same as `parse_recover`, with the options set on `options`.
+pub fn parse_recover_with(source: &str, options: &ParserOptions) -> (JsonValue, Vec<Error>) {
+    Parser::new(source, options).parse_recover()
+}
+
+struct Recover<'p, 'a> {
+    parser: &'p mut Parser<'a>,
+    stack: Vec<StackBlock>,
+    walk: Walk,
+    root: Option<JsonValue>,
+    errors: Vec<Error>,
+
+    // Set when an error is reported, until something parses fine again.
+    quiet: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_recover(&mut self) -> (JsonValue, Vec<Error>) {
+        if self.length > self.options.size_limit {
+            return (JsonValue::Null, vec![Error::ExceededSizeLimit]);
+        }
+
+        Recover {
+            walk: Walk::value(self.options, 0),
+            parser: self,
+            stack: Vec::with_capacity(3),
+            root: None,
+            errors: Vec::new(),
+            quiet: false,
+        }.run()
+    }
+}
+
+impl<'p, 'a> Recover<'p, 'a> {
+    fn run(mut self) -> (JsonValue, Vec<Error>) {
+        loop {
+            let ch = match self.skip_whitespace() {
+                Some(ch) => ch,
+                None     => {
+                    // Unless a string or a literal just ran into it
+                    if self.root.is_none() && self.errors.last() != Some(&Error::UnexpectedEndOfJson) {
+                        self.report(Error::UnexpectedEndOfJson);
+                    }
+                    break;
+                }
+            };
+
+            // Nothing else to do with the rest of the source
+            if self.root.is_some() {
+                let error = self.unexpected(Expected::EndOfJson);
+
+                self.report(error);
+                break;
+            }
+
+            // Limits are there for a reason, no going past them
+            if let Err(error) = self.step(ch) {
+                self.errors.push(error);
+                break;
+            }
+        }
+
+        self.finish()
+    }
+
+    // Take the next step of the walk, which starts with `ch`.
+    fn step(&mut self, ch: u8) -> Result<()> {
+        let options = self.parser.options;
+        let start = self.parser.index;
+        let before = self.walk.state();
+        let step = self.parser.step(&mut self.walk);
+
+        // Got past a `:` or `,`
+        if matches!(before, State::Colon | State::Counted) && self.walk.state() != before {
+            self.quiet = false;
+        }
+
+        let step = match step {
+            Ok(step)                       => step,
+            Err(Error::ExceededDepthLimit) => {
+                // Never got into the one that was just opened, it's left out
+                self.stack.pop();
+                return Err(Error::ExceededDepthLimit);
+            },
+            Err(error) if is_limit(&error) => return Err(error),
+            Err(error)                     => return self.recover(ch, start, error),
+        };
+
+        match step {
+            Step::Open(object) => self.open(object),
+            Step::Close(_)     => self.close(),
+            Step::End          => Ok(()),
+
+            Step::Key(ch, expected) => {
+                self.parser.index -= 1;
+
+                match ch {
+                    b'}' => {
+                        // Only a trailing comma gets here
+                        let error = self.unexpected(expected);
+
+                        self.report(error);
+                        self.parser.bump();
+                        self.walk.close();
+                        self.close()
+                    },
+                    b'"' => self.key(ch),
+                    b'\'' if options.single_quotes => self.key(ch),
+                    b'a' ..= b'z' | b'A' ..= b'Z' | b'_' | b'$' => {
+                        // Most likely meant as a key either way
+                        if !options.unquoted_keys {
+                            let error = self.unexpected(expected);
+
+                            self.report(error);
+                        }
+                        self.key(ch)
+                    },
+                    b']' => {
+                        self.walk.again();
+                        self.close_other(ch, expected)
+                    },
+                    _    => {
+                        let error = self.unexpected(expected);
+
+                        self.report(error);
+                        self.skip_token();
+                        self.walk.again();
+                        Ok(())
+                    }
+                }
+            },
+
+            Step::Scalar(ch) => {
+                self.parser.index -= 1;
+
+                let element = self.walk.object() == Some(false);
+
+                match ch {
+                    // Only a trailing comma gets here
+                    b']' if element => {
+                        let error = self.unexpected(Expected::Value);
+
+                        self.report(error);
+                        self.parser.bump();
+                        self.walk.close();
+                        self.close()
+                    },
+                    b',' if element => {
+                        let error = self.unexpected(Expected::Value);
+
+                        self.report(error);
+                        self.parser.bump();
+                        self.walk.again();
+                        Ok(())
+                    },
+                    b'}' if element => {
+                        self.walk.again();
+                        self.close_other(ch, Expected::Value)
+                    },
+                    _ => self.value(ch),
+                }
+            },
+        }
+    }
+
+    // The step starting with `ch` at `start` failed with the `error`.
+    // Whatever it expected isn't there, patch it up or skip it.
+    fn recover(&mut self, ch: u8, start: usize, error: Error) -> Result<()> {
+        match self.walk.state() {
+            State::Colon => {
+                self.parser.index = start;
+                self.report(error);
+
+                match ch {
+                    // The value stays `null`
+                    b',' | b'}' | b']' => self.walk.expect(State::Counted),
+                    _ if starts_value(ch, self.parser.options) => self.walk.expect(State::Value),
+                    _ => self.skip_token(),
+                }
+
+                Ok(())
+            },
+
+            State::Counted => {
+                self.parser.index = start;
+
+                let (expected, next) = match self.walk.object() {
+                    Some(true) => (Expected::CommaOrEndOfObject, State::Key { first: false }),
+                    _          => (Expected::CommaOrEndOfArray, State::Element { first: false }),
+                };
+
+                match ch {
+                    b']' | b'}' => self.close_other(ch, expected),
+                    // Missing comma, whatever it is goes to the next state
+                    _ => {
+                        self.report(error);
+                        self.walk.expect(next);
+                        Ok(())
+                    }
+                }
+            },
+
+            // A comment that's broken, or the end of the source, after a
+            // `:` or `,`
+            _ => {
+                self.report(error);
+                Ok(())
+            },
+        }
+    }
+
+    // Read a value starting with `ch`.
+    fn value(&mut self, ch: u8) -> Result<()> {
+        let options = self.parser.options;
+
+        match ch {
+            b'"' => {
+                let value = self.string(ch)?;
+                self.complete(value.as_str().into())
+            },
+            b'\'' if options.single_quotes => {
+                let value = self.string(ch)?;
+                self.complete(value.as_str().into())
+            },
+            b'0' ..= b'9' | b'-' => self.number(),
+            b'+' if options.leading_plus => self.number(),
+            b'.' if options.leading_decimal_point => self.number(),
+            b't' => self.literal("true", JsonValue::Boolean(true)),
+            b'f' => self.literal("false", JsonValue::Boolean(false)),
+            b'n' => self.literal("null", JsonValue::Null),
+            // Missing value of an object entry, which stays `null`
+            b',' | b']' | b'}' if !self.stack.is_empty() => {
+                let error = self.unexpected(Expected::Value);
+
+                self.report(error);
+                self.complete(JsonValue::Null)
+            },
+            _ => {
+                let error = self.unexpected(Expected::Value);
+
+                self.report(error);
+                self.skip_token();
+                self.walk.again();
+                Ok(())
+            }
+        }
+    }
+
+    // Read a key starting with `ch` and add it to the object on top of the
+    // stack.
+    fn key(&mut self, ch: u8) -> Result<()> {
+        let start = self.parser.index;
+        let key = match ch {
+            b'"' | b'\'' => self.string(ch)?,
+            _            => {
+                self.parser.bump();
+                self.parser.read_identifier()?.to_owned()
+            }
+        };
+
+        let depth = self.stack.len() - 1;
+        let parser = &mut *self.parser;
+
+        if let Some(&mut StackBlock(JsonValue::Object(ref mut object), ref mut index)) = self.stack.last_mut() {
+            *index = match parser.duplicates.insert(object, depth, &key) {
+                Some(index) => index,
+                None        => {
+                    parser.pin = start;
+
+                    let error = parser.duplicate_key::<()>(&key).unwrap_err();
+
+                    parser.pin = usize::MAX;
+                    self.errors.push(error);
+
+                    // Carry on as if the last one wins
+                    object.insert_index(&key, JsonValue::Null)
+                }
+            };
+
+            if object.len() > parser.options.object_entries_limit {
+                return Err(Error::ExceededObjectEntriesLimit);
+            }
+        }
+
+        self.quiet = false;
+        Ok(())
+    }
+
+    // Read a string starting with the `quote`. If it's broken, its contents
+    // are taken as they are in the source, up to the closing quote or the
+    // end of the line.
+    fn string(&mut self, quote: u8) -> Result<String> {
+        self.parser.bump();
+
+        let start = self.parser.index;
+        let result = match quote {
+            b'"' => self.parser.read_string().map(String::from),
+            _    => self.parser.read_single_quoted_string().map(String::from),
+        };
+
+        match result {
+            Ok(string)                     => {
+                self.quiet = false;
+                return Ok(string);
+            },
+            Err(error) if is_limit(&error) => return Err(error),
+            Err(error)                     => self.report(error),
+        }
+
+        let source = self.parser.source();
+        let mut end = start;
+        let mut escaped = false;
+
+        while end < self.parser.length {
+            match source[end] {
+                b'\n'             => break,
+                _ if escaped      => escaped = false,
+                b'\\'             => escaped = true,
+                ch if ch == quote => break,
+                _                 => {},
+            }
+            end += 1;
+        }
+
+        // Whatever follows a string that didn't end is not worth reporting
+        if source.get(end) == Some(&quote) {
+            self.parser.index = end + 1;
+            self.quiet = false;
+        } else {
+            self.parser.index = end;
+        }
+
+        Ok(String::from_utf8_lossy(&source[start..end]).into_owned())
+    }
+
+    // Read a number, which is `null` if it's broken.
+    fn number(&mut self) -> Result<()> {
+        let start = self.parser.index;
+
+        match self.parser.read_number_value() {
+            Ok(number) => {
+                self.quiet = false;
+                self.complete(number)
+            },
+            Err(error) if is_limit(&error) => Err(error),
+            Err(error) => {
+                self.report(error);
+                self.parser.index = start;
+                self.skip_token();
+                self.complete(JsonValue::Null)
+            }
+        }
+    }
+
+    // Read `true`, `false` or `null`. Cut short, it's still most likely
+    // what was meant, any other word is skipped.
+    fn literal(&mut self, literal: &'static str, value: JsonValue) -> Result<()> {
+        let start = self.parser.index;
+
+        self.parser.bump();
+
+        for &byte in &literal.as_bytes()[1..] {
+            if self.parser.is_eof() {
+                self.report(Error::UnexpectedEndOfJson);
+                return self.complete(value);
+            }
+
+            let ch = self.parser.read_byte();
+
+            if ch != byte {
+                let error = self.unexpected(Expected::Literal(literal));
+
+                self.report(error);
+
+                if is_word(ch) {
+                    self.parser.index = start;
+                    self.skip_token();
+                    self.walk.again();
+                    return Ok(());
+                }
+
+                return self.complete(value);
+            }
+
+            self.parser.bump();
+        }
+
+        self.quiet = false;
+        self.complete(value)
+    }
+
+    // Put a new array or object on the stack, its opening bracket has just
+    // been read.
+    fn open(&mut self, object: bool) -> Result<()> {
+        let container = match object {
+            true  => {
+                self.parser.duplicates.open(self.stack.len());
+                JsonValue::Object(Object::with_capacity(3))
+            },
+            false => JsonValue::Array(Vec::with_capacity(2)),
+        };
+
+        self.stack.push(StackBlock(container, 0));
+        self.quiet = false;
+        Ok(())
+    }
+
+    // The container on top of the stack is done, and so is the walk
+    // through it.
+    fn close(&mut self) -> Result<()> {
+        self.quiet = false;
+
+        match self.stack.pop() {
+            Some(StackBlock(value, _)) => self.complete(value),
+            None                       => Ok(()),
+        }
+    }
+
+    // Found the closing bracket `ch` of a container that isn't on top of
+    // the stack, where `expected` should be. If it's further down, all
+    // containers above it are closed along with it, otherwise it's skipped.
+    fn close_other(&mut self, ch: u8, expected: Expected) -> Result<()> {
+        let error = self.unexpected(expected);
+
+        self.report(error);
+        self.parser.bump();
+
+        let open = self.stack.iter().rposition(|block| matches!(
+            (ch, &block.0),
+            (b']', &JsonValue::Array(_)) | (b'}', &JsonValue::Object(_))
+        ));
+
+        if let Some(open) = open {
+            while self.stack.len() > open {
+                self.walk.close();
+                self.close()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Put a finished value where it belongs.
+    fn complete(&mut self, value: JsonValue) -> Result<()> {
+        match self.stack.last_mut() {
+            None => self.root = Some(value),
+
+            Some(&mut StackBlock(JsonValue::Array(ref mut array), _)) => {
+                if array.len() == self.parser.options.array_length_limit {
+                    return Err(Error::ExceededArrayLengthLimit);
+                }
+
+                array.push(value);
+            },
+
+            Some(&mut StackBlock(JsonValue::Object(ref mut object), index)) => {
+                self.parser.duplicates.set(object, index, value)?;
+            },
+
+            _ => unreachable!(),
+        }
+
+        self.parser.end_value(&mut self.walk)
+    }
+
+    // Close up whatever is still open.
+    fn finish(mut self) -> (JsonValue, Vec<Error>) {
+        let mut value = self.root.take();
+
+        while let Some(StackBlock(mut container, index)) = self.stack.pop() {
+            if let Some(value) = value {
+                // A value collected past the limit is left out
+                match container {
+                    JsonValue::Array(ref mut array)   => array.push(value),
+                    JsonValue::Object(ref mut object) => self.parser.duplicates.set(object, index, value).unwrap_or(()),
+                    _                                 => unreachable!(),
+                }
+            }
+
+            value = Some(container);
+        }
+
+        (value.unwrap_or(JsonValue::Null), self.errors)
+    }
+
+    // Skip over whitespace, and comments if they are allowed, returning the
+    // byte after it without consuming it.
+    fn skip_whitespace(&mut self) -> Option<u8> {
+        while !self.parser.is_eof() {
+            match self.parser.read_byte() {
+                9 ..= 13 | 32 => self.parser.bump(),
+                b'/' if self.parser.options.comments => {
+                    self.parser.bump();
+
+                    if let Err(error) = self.parser.skip_comment() {
+                        self.report(error);
+                    }
+                },
+                ch => return Some(ch),
+            }
+        }
+
+        None
+    }
+
+    // Skip a token that's not supposed to be there. Whatever looks like a
+    // word or a number goes as a whole, anything else one character at
+    // a time.
+    fn skip_token(&mut self) {
+        let start = self.parser.index;
+
+        while !self.parser.is_eof() && is_word(self.parser.read_byte()) {
+            self.parser.bump();
+        }
+
+        if self.parser.index == start {
+            self.parser.bump();
+
+            while !self.parser.is_eof() && self.parser.read_byte() & 0xC0 == 0x80 {
+                self.parser.bump();
+            }
+        }
+    }
+
+    // The error for the character that's next, without consuming it.
+    fn unexpected(&mut self, expected: Expected) -> Error {
+        self.parser.bump();
+
+        let error = self.parser.unexpected_character::<()>(expected).unwrap_err();
+
+        self.parser.index -= 1;
+        error
+    }
+
+    fn report(&mut self, error: Error) {
+        if !self.quiet {
+            self.errors.push(error);
+        }
+        self.quiet = true;
+    }
+}
+
+// Whether a value can start with `ch`.
+fn starts_value(ch: u8, options: &ParserOptions) -> bool {
+    match ch {
+        b'[' | b'{' | b'"' | b'-' | b'0' ..= b'9' | b't' | b'f' | b'n' => true,
+        b'\''  => options.single_quotes,
+        b'+'   => options.leading_plus,
+        b'.'   => options.leading_decimal_point,
+        _      => false,
+    }
+}
+
+// Whether `ch` can be a part of a word or a number.
+fn is_word(ch: u8) -> bool {
+    matches!(ch, b'a' ..= b'z' | b'A' ..= b'Z' | b'0' ..= b'9' | b'_' | b'$' | b'.' | b'+' | b'-')
+}
+
+fn is_limit(error: &Error) -> bool {
+    matches!(
+        *error,
+        Error::ExceededDepthLimit         |
+        Error::ExceededSizeLimit          |
+        Error::ExceededStringLengthLimit  |
+        Error::ExceededObjectEntriesLimit |
+        Error::ExceededArrayLengthLimit
+    )
+}
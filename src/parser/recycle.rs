@@ -0,0 +1,216 @@
+// Parsing lots of similar documents one after another, like messages off a
+// queue, spends a good part of the time allocating arrays, objects and
+// strings, only to free them again once the document has been dealt with.
+// Instead, a value that's no longer needed can be taken apart into empty
+// containers that keep their capacity, and the parser takes those before
+// it allocates anything new. The stack of the parser and the buffer for
+// strings with escapes are kept between documents as well.
+
+use std::mem;
+
+use crate::short;
+use crate::object::Object;
+use crate::{ JsonValue, Result };
+use super::{ Parser, ParserOptions, StackBlock, DEFAULT_OPTIONS };
+
+// Empty containers left over from values that have been recycled.
+#[derive(Default)]
+pub(super) struct Recycled {
+    arrays: Vec<Vec<JsonValue>>,
+    objects: Vec<Object>,
+    strings: Vec<String>,
+    stack: Vec<StackBlock>,
+
+    // Values waiting to be taken apart, kept so that taking them apart
+    // doesn't allocate either.
+    pending: Vec<JsonValue>,
+}
+
+impl Recycled {
+    // Take `value` apart. Containers that haven't allocated anything aren't
+    // worth keeping.
+    fn recycle(&mut self, value: JsonValue) {
+        self.pending.push(value);
+
+        while let Some(value) = self.pending.pop() {
+            match value {
+                JsonValue::Array(mut array) => {
+                    self.pending.append(&mut array);
+
+                    if array.capacity() != 0 {
+                        self.arrays.push(array);
+                    }
+                },
+                JsonValue::Object(mut object) => {
+                    for (_, value) in object.iter_mut() {
+                        self.pending.push(value.take());
+                    }
+
+                    object.clear();
+
+                    if object.capacity() != 0 {
+                        self.objects.push(object);
+                    }
+                },
+                JsonValue::String(mut string) => {
+                    string.clear();
+                    self.strings.push(string);
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    #[inline]
+    pub(super) fn new_array(&mut self) -> Vec<JsonValue> {
+        self.recycled.arrays.pop().unwrap_or_else(|| Vec::with_capacity(2))
+    }
+
+    #[inline]
+    pub(super) fn new_object(&mut self) -> Object {
+        self.recycled.objects.pop().unwrap_or_else(|| Object::with_capacity(3))
+    }
+
+    // The stack isn't put back if parsing fails, it would have to be
+    // emptied out anyway.
+    #[inline]
+    pub(super) fn new_stack(&mut self) -> Vec<StackBlock> {
+        match self.recycled.stack.capacity() {
+            0 => Vec::with_capacity(3),
+            _ => mem::take(&mut self.recycled.stack),
+        }
+    }
+
+    #[inline]
+    pub(super) fn put_stack(&mut self, stack: Vec<StackBlock>) {
+        self.recycled.stack = stack;
+    }
+
+    // Strings short enough to fit in a `JsonValue::Short` don't allocate.
+    #[inline]
+    pub(super) fn new_string(&mut self, string: &str) -> JsonValue {
+        if string.len() > short::MAX_LEN {
+            if let Some(mut recycled) = self.recycled.strings.pop() {
+                recycled.push_str(string);
+                return JsonValue::String(recycled);
+            }
+        }
+
+        string.into()
+    }
+}
+
+/// Parser that can be used for many documents, one after another, reusing
+/// allocations from one to the next. Values given back to it with `recycle`,
+/// or replaced with `parse_into`, are taken apart, and their arrays, objects
+/// and strings are filled with the contents of the documents that follow.
+///
+/// Containers keep whatever capacity they had, and are kept around until
+/// they are used, so this works best for documents that are similar in
+/// shape and size.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// use json::ReusableParser;
+///
+/// let mut parser = ReusableParser::new();
+/// let mut value = json::JsonValue::Null;
+///
+/// for message in &[r#"{"id":1,"tags":["a","b"]}"#, r#"{"id":2,"tags":["c"]}"#] {
+///     // The object and the array of the first message are used again
+///     parser.parse_into(message, &mut value).unwrap();
+/// }
+///
+/// assert_eq!(value, object!{ id: 2, tags: ["c"] });
+/// # }
+/// ```
+pub struct ReusableParser {
+    options: ParserOptions,
+    buffer: Vec<u8>,
+    recycled: Recycled,
+}
+
+impl ReusableParser {
+    /// Create a new `ReusableParser` with the options of `parse`.
+    pub fn new() -> Self {
+        ReusableParser::with_options(&DEFAULT_OPTIONS)
+    }
+
+    /// Create a new `ReusableParser` that enforces the limits set on
+    /// `options`.
+    pub fn with_options(options: &ParserOptions) -> Self {
+        ReusableParser {
+            options: options.clone(),
+            buffer: Vec::new(),
+            recycled: Recycled::default(),
+        }
+    }
+
+    /// Parse a document, same as `parse_with`, using up what's been
+    /// recycled so far.
+    pub fn parse(&mut self, source: &str) -> Result<JsonValue> {
+        let mut parser = Parser::new(source, &self.options);
+
+        parser.buffer = mem::take(&mut self.buffer);
+        parser.buffer.clear();
+        parser.recycled = mem::take(&mut self.recycled);
+
+        let result = parser.parse();
+
+        self.buffer = mem::take(&mut parser.buffer);
+        self.recycled = mem::take(&mut parser.recycled);
+
+        result
+    }
+
+    /// Parse a document into `value`, recycling whatever was in it first.
+    /// On error `value` is left as `Null`.
+    pub fn parse_into(&mut self, source: &str, value: &mut JsonValue) -> Result<()> {
+        self.recycle(value.take());
+
+        *value = self.parse(source)?;
+
+        Ok(())
+    }
+
+    /// Give back a value that is no longer needed, so that its allocations
+    /// can be used for the documents that follow.
+    pub fn recycle(&mut self, value: JsonValue) {
+        self.recycled.recycle(value);
+    }
+}
+
+impl Default for ReusableParser {
+    fn default() -> Self {
+        ReusableParser::new()
+    }
+}
+
+/// Parse a document into `value`, reusing the arrays, objects and strings
+/// that were in it for the new one. On error `value` is left as `Null`.
+///
+/// To keep allocations around between documents that aren't parsed into the
+/// same value, use a `ReusableParser`.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// let mut value = json::parse(r#"["a", "b", "c"]"#).unwrap();
+///
+/// json::parse_into("[1, 2]", &mut value).unwrap();
+///
+/// assert_eq!(value, array![1, 2]);
+/// # }
+/// ```
+pub fn parse_into(source: &str, value: &mut JsonValue) -> Result<()> {
+    parse_into_with(source, value, &DEFAULT_OPTIONS)
+}
+
+/// Parse a document into `value`, reusing its allocations, while enforcing
+/// the limits set on `options`.
+pub fn parse_into_with(source: &str, value: &mut JsonValue, options: &ParserOptions) -> Result<()> {
+    ReusableParser::with_options(options).parse_into(source, value)
+}
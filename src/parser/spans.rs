@@ -0,0 +1,448 @@
+// Parsing with a map of where everything came from. This walks through the
+// source building the same value `Parser::parse_value` does, and on the side
+// it notes where every key and value starts and ends. Nodes
+// are numbered in the order they start in the source, which puts everything
+// inside an array or object right after it, so the map only has to remember
+// where each node ends to know what's in it.
+//
+// Keys are kept as they are in the source, duplicates included. Which of
+// them a JSON Pointer leads to depends on `DuplicateKeys`, and that's only
+// worked out when looking a pointer up.
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::{ str, slice };
+
+use crate::object::Object;
+use crate::{ Error, Expected, JsonValue, Result };
+use super::{ Parser, ParserOptions, DuplicateKeys, LineCounter, ALLOWED, DEFAULT_OPTIONS };
+use super::walk::{ Walk, Step, Scalar };
+
+// `parent` of the root
+const ROOT: usize = usize::MAX;
+
+/// A piece of the source, as byte offsets, along with the line and column
+/// it starts at. Both of those start at 1, columns count characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// Byte range of the span, to slice the source with.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// Where a value is in the source, and where its key is if it's in an
+/// object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeSpan {
+    pub key: Option<Span>,
+    pub value: Span,
+
+    /// Id of the array or object the value is in, `None` for the root.
+    pub parent: Option<usize>,
+}
+
+struct Entry {
+    span: NodeSpan,
+
+    // Key, or index in the array, the value is under
+    key: Option<String>,
+    index: usize,
+
+    // First node that's not inside this one
+    end: usize,
+}
+
+/// Spans of every key and value in a document, as returned from
+/// `parse_spanned`.
+///
+/// Nodes can be looked up by a JSON Pointer, or by their id, which is the
+/// position of the value among all of the values in the source, starting
+/// with the root at 0. Every occurrence of a duplicate key is in the map, but
+/// pointers lead to the ones the `DuplicateKeys` policy kept. Values that were
+/// dropped are only found by their id, for `DuplicateKeys::Collect` pointers
+/// lead through the arrays values are collected into, as in `/key/1`.
+///
+/// ```
+/// let source = "{\n  \"name\": \"Ann\",\n  \"tags\": [\"a\", \"b\"]\n}";
+/// let (value, spans) = json::parse_spanned(source).unwrap();
+///
+/// assert_eq!(value["tags"][1], "b");
+///
+/// let tag = spans.get("/tags/1").unwrap();
+///
+/// assert_eq!(&source[tag.value.range()], "\"b\"");
+/// assert_eq!((tag.value.line, tag.value.column), (3, 17));
+///
+/// let name = spans.get("/name").unwrap();
+///
+/// assert_eq!(&source[name.key.unwrap().range()], "\"name\"");
+/// assert_eq!(spans.pointer(2).as_deref(), Some("/tags"));
+/// ```
+pub struct SourceMap {
+    nodes: Vec<Entry>,
+    policy: DuplicateKeys,
+}
+
+impl SourceMap {
+    /// Number of nodes, which is the number of values in the source.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Spans of the node with the given id.
+    pub fn node(&self, id: usize) -> Option<&NodeSpan> {
+        self.nodes.get(id).map(|entry| &entry.span)
+    }
+
+    /// Spans of the node a JSON Pointer leads to.
+    pub fn get(&self, pointer: &str) -> Option<&NodeSpan> {
+        self.find(pointer).and_then(|id| self.node(id))
+    }
+
+    /// Id of the node a JSON Pointer leads to. An empty pointer is the root.
+    pub fn find(&self, pointer: &str) -> Option<usize> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        if pointer.is_empty() {
+            return Some(0);
+        }
+
+        let mut tokens = pointer.strip_prefix('/')?.split('/').map(unescape).peekable();
+        let mut id = 0;
+
+        while let Some(token) = tokens.next() {
+            let mut children = self.children(id);
+
+            id = match children.clone().next().map(|child| self.nodes[child].key.is_some()) {
+                Some(false) => children.nth(array_index(&token)?)?,
+                Some(true)  => {
+                    let mut found = children.filter(|&child| self.nodes[child].key.as_deref() == Some(&*token));
+                    let first = found.next()?;
+
+                    match self.policy {
+                        DuplicateKeys::KeepLast => found.last().unwrap_or(first),
+                        DuplicateKeys::Collect  => {
+                            let rest: Vec<usize> = found.collect();
+
+                            // Values are collected into an array, which isn't
+                            // anywhere in the source, the first of them stands
+                            // in for it
+                            match tokens.peek() {
+                                Some(token) if !rest.is_empty() => {
+                                    let index = array_index(token)?;
+
+                                    tokens.next();
+
+                                    match index {
+                                        0     => first,
+                                        index => *rest.get(index - 1)?,
+                                    }
+                                },
+                                _ => first,
+                            }
+                        },
+                        _ => first,
+                    }
+                },
+                None => return None,
+            };
+        }
+
+        Some(id)
+    }
+
+    /// JSON Pointer of the node with the given id.
+    pub fn pointer(&self, id: usize) -> Option<String> {
+        let mut path = Vec::new();
+        let mut at = id;
+
+        while at != ROOT {
+            let entry = self.nodes.get(at)?;
+
+            path.push(at);
+            at = entry.span.parent.unwrap_or(ROOT);
+        }
+
+        let mut pointer = String::new();
+
+        for &at in path.iter().rev() {
+            let entry = &self.nodes[at];
+
+            match entry.key {
+                Some(ref key) => {
+                    pointer.push('/');
+                    pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+
+                    if let (DuplicateKeys::Collect, Some(parent)) = (self.policy, entry.span.parent) {
+                        let same = |&child: &usize| self.nodes[child].key == entry.key;
+
+                        if self.children(parent).filter(same).nth(1).is_some() {
+                            let index = self.children(parent).filter(same).take_while(|&child| child != at).count();
+
+                            pointer.push('/');
+                            pointer.push_str(&index.to_string());
+                        }
+                    }
+                },
+                None if entry.span.parent.is_some() => {
+                    pointer.push('/');
+                    pointer.push_str(&entry.index.to_string());
+                },
+                None => {},
+            }
+        }
+
+        Some(pointer)
+    }
+
+    // Add a node for the value that starts at `span`, in the innermost of
+    // the `open` arrays and objects, under `key` if it's an object. Returns
+    // its id.
+    fn push(&mut self, open: &[Open], key: Option<(String, Span)>, span: Span) -> usize {
+        let id = self.nodes.len();
+        let (parent, index) = match open.last() {
+            Some(&Open { value: JsonValue::Array(ref array), id, .. }) => (Some(id), array.len()),
+            Some(open) => (Some(open.id), 0),
+            None       => (None, 0),
+        };
+        let (key, key_span) = match key {
+            Some((key, span)) => (Some(key), Some(span)),
+            None              => (None, None),
+        };
+
+        self.nodes.push(Entry {
+            span: NodeSpan {
+                key: key_span,
+                value: span,
+                parent,
+            },
+            key,
+            index,
+            end: id + 1,
+        });
+
+        id
+    }
+
+    // Ids of the nodes directly inside the one with `id`.
+    fn children(&self, id: usize) -> Children<'_> {
+        Children {
+            nodes: &self.nodes,
+            next: id + 1,
+            end: self.nodes[id].end,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Children<'a> {
+    nodes: &'a [Entry],
+    next: usize,
+    end: usize,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.next >= self.end {
+            return None;
+        }
+
+        let id = self.next;
+
+        self.next = self.nodes[id].end;
+
+        Some(id)
+    }
+}
+
+// Reference token of a JSON Pointer, with `~1` and `~0` turned back into
+// `/` and `~`.
+pub(super) fn unescape(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+// Reference token as an index into an array, which can't have a sign or
+// leading zeros.
+pub(super) fn array_index(token: &str) -> Option<usize> {
+    if token.is_empty() || !token.bytes().all(|byte| byte.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+
+    token.parse().ok()
+}
+
+/// Parse a JSON document, along with a map of where each key and value
+/// in it is in the source.
+pub fn parse_spanned(source: &str) -> Result<(JsonValue, SourceMap)> {
+    parse_spanned_with(source, &DEFAULT_OPTIONS)
+}
+
+/// Parse a JSON document, along with a map of where each key and value
+/// in it is, while enforcing the limits set on `options`.
+pub fn parse_spanned_with(source: &str, options: &ParserOptions) -> Result<(JsonValue, SourceMap)> {
+    Parser::new(source, options).parse_spanned()
+}
+
+// Lines and columns of positions in the source, which only ever move
+// forward.
+#[derive(Default)]
+struct Positions {
+    lines: LineCounter,
+    counted: usize,
+}
+
+impl Positions {
+    fn span(&mut self, source: &[u8], start: usize) -> Span {
+        self.lines.advance(&source[self.counted..start]);
+        self.counted = start;
+
+        Span {
+            start,
+            end: start,
+            line: self.lines.newlines + 1,
+            column: self.lines.column + 1,
+        }
+    }
+}
+
+// An array or object that's still being parsed, with the entry the current
+// value goes to, and the node it is.
+struct Open {
+    value: JsonValue,
+    index: usize,
+    id: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_spanned(&mut self) -> Result<(JsonValue, SourceMap)> {
+        if self.length > self.options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        self.find_structurals();
+
+        let mut map = SourceMap {
+            nodes: Vec::new(),
+            policy: self.options.duplicate_keys,
+        };
+        let mut positions = Positions::default();
+        let mut walk = Walk::new(self.options);
+        let mut stack: Vec<Open> = Vec::with_capacity(3);
+        let mut root = JsonValue::Null;
+        // Key of the value that comes next, and where it is
+        let mut pending = None;
+
+        loop {
+            let value = match self.step(&mut walk)? {
+                Step::Key(ch, expected) => {
+                    let depth = stack.len() - 1;
+
+                    if let Some(&mut Open { value: JsonValue::Object(ref mut object), ref mut index, .. }) = stack.last_mut() {
+                        let len = object.len();
+                        let (at, read) = self.spanned_key(&mut positions, object, depth, ch, expected)?;
+
+                        self.count_entry(&mut walk, object.len() > len)?;
+
+                        *index = at;
+                        pending = Some(read);
+                    }
+                    continue;
+                },
+                Step::Close(_) => {
+                    let open = stack.pop().expect("Must have an open array or object");
+                    let end = map.nodes.len();
+                    let node = &mut map.nodes[open.id];
+
+                    node.span.value.end = self.index;
+                    node.end = end;
+                    open.value
+                },
+                Step::End => return Ok((root, map)),
+                Step::Open(object) => {
+                    let id = map.push(&stack, pending.take(), positions.span(self.source(), walk.start()));
+                    let value = match object {
+                        true  => {
+                            self.duplicates.open(stack.len());
+                            JsonValue::Object(Object::with_capacity(3))
+                        },
+                        false => JsonValue::Array(Vec::with_capacity(2)),
+                    };
+
+                    stack.push(Open { value, index: 0, id });
+                    continue;
+                },
+                Step::Scalar(ch) => {
+                    let id = map.push(&stack, pending.take(), positions.span(self.source(), walk.start()));
+                    let value = match self.read_scalar(ch)? {
+                        Scalar::String(string)   => string.into(),
+                        Scalar::Number(number)   => JsonValue::Number(number),
+                        Scalar::Decimal(decimal) => JsonValue::Decimal(decimal),
+                        Scalar::Boolean(value)   => JsonValue::Boolean(value),
+                        Scalar::Null             => JsonValue::Null,
+                    };
+
+                    map.nodes[id].span.value.end = self.index;
+                    value
+                },
+            };
+
+            match stack.last_mut() {
+                None => root = value,
+
+                Some(&mut Open { value: JsonValue::Array(ref mut array), .. }) => array.push(value),
+
+                Some(&mut Open { value: JsonValue::Object(ref mut object), index, .. }) => {
+                    self.duplicates.set(object, index, value)?;
+                },
+
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    // Read a key starting with `ch` and add it to the `object`, which is at
+    // `depth` on the stack. Returns the index of its entry, along with the
+    // key and where it is.
+    fn spanned_key(
+        &mut self,
+        positions: &mut Positions,
+        object: &mut Object,
+        depth: usize,
+        ch: u8,
+        expected: Expected,
+    ) -> Result<(usize, (String, Span))> {
+        self.pin = self.index - 1;
+
+        let mut span = positions.span(self.source(), self.pin);
+        let key = expect_key!(self, ch, expected);
+        let index = match self.duplicates.insert(object, depth, key) {
+            Some(index) => index,
+            None        => return self.duplicate_key(key),
+        };
+
+        span.end = self.index;
+
+        self.pin = usize::MAX;
+        Ok((index, (key.to_owned(), span)))
+    }
+}
@@ -0,0 +1,565 @@
+// Sequences of documents, as found in JSON Lines (NDJSON) logs or in RFC 7464
+// `application/json-seq` streams. Values are parsed one after another with
+// the same parser, so line numbers and offsets in errors are counted from
+// the start of the whole source rather than from the start of each value.
+//
+// Anything broken is reported and then skipped up to the next line, or the
+// next record separator if the value started with one, so that a single bad
+// record doesn't take the rest of the log down with it.
+//
+// Also the elements of a single array, for documents that are one huge
+// array of records. Everything on the way to the array is only validated,
+// and so is everything after it, the elements are parsed one at a time.
+
+use std::io::{ self, Read, Write };
+use std::{ str, slice };
+
+use crate::{ Error, Expected, JsonValue, Result };
+use super::{ Parser, ParserOptions, Refill, LineCounter, ALLOWED, DEFAULT_OPTIONS };
+use super::read::ReadWindow;
+use super::spans::{ unescape, array_index };
+use super::walk::{ Walk, Step };
+
+// Record separator from RFC 7464, put in front of every value.
+const RS: u8 = 0x1E;
+
+/// Iterator over values in a `&str`, separated by whitespace, newlines or
+/// record separators (`0x1E`).
+///
+/// Every item is the result of parsing a single value, with errors
+/// positioned relative to the start of the whole source. After an error,
+/// parsing picks up again from the next line, or from the next record
+/// separator if the broken value started with one.
+///
+/// The size limit set on `ParserOptions` applies to every value on its own.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// use json::stream::Values;
+///
+/// let log = "{\"status\":200,\"path\":\"/\"}\n{\"status\":404,\n  \"path\":\"/nope\"}\n";
+///
+/// let statuses: Vec<_> = Values::new(log)
+///     .map(|record| record.unwrap()["status"].as_u16())
+///     .collect();
+///
+/// assert_eq!(statuses, [Some(200), Some(404)]);
+/// # }
+/// ```
+pub struct Values<'a> {
+    source: &'a str,
+
+    // Options with the size limit lifted, it's checked for each value
+    options: ParserOptions,
+    size_limit: usize,
+
+    // Where the next value is looked for
+    index: usize,
+}
+
+impl<'a> Values<'a> {
+    /// Iterate over values in the `source`, with the default options.
+    pub fn new(source: &'a str) -> Self {
+        Values::with_options(source, &DEFAULT_OPTIONS)
+    }
+
+    /// Iterate over values in the `source`, enforcing the limits set on
+    /// `options` for each of them.
+    pub fn with_options(source: &'a str, options: &ParserOptions) -> Self {
+        Values {
+            source,
+            options: options.clone().size_limit(usize::MAX),
+            size_limit: options.size_limit,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Values<'a> {
+    type Item = Result<JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut parser = Parser::new(self.source, &self.options);
+        parser.index = self.index;
+
+        let item = parser.next_item(self.size_limit);
+
+        self.index = parser.index;
+        item
+    }
+}
+
+/// Iterator over values read from an implementor of `std::io::Read`,
+/// separated by whitespace, newlines or record separators (`0x1E`). The
+/// source is read in chunks, as with `parse_reader`, so it can be as large
+/// as needed, or never end at all.
+///
+/// Items are the same as from `Values` over the whole source. Errors from
+/// the reader itself are reported as `Error::Io`, after which the iterator
+/// is done.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// use json::stream::ReadValues;
+///
+/// let source: &[u8] = b"\x1e[1, 2]\n\x1e{\"foo\": true}\n";
+/// let values: Vec<_> = ReadValues::new(source).collect();
+///
+/// assert_eq!(values, [Ok(array![1, 2]), Ok(object!{ foo: true })]);
+/// # }
+/// ```
+pub struct ReadValues<R: Read> {
+    window: ReadWindow<R>,
+
+    // Options with the size limit lifted, it's checked for each value
+    options: ParserOptions,
+    size_limit: usize,
+
+    // Where the parser left off in the window, and how much of the source
+    // has been dropped from it so far
+    index: usize,
+    offset: usize,
+    dropped: LineCounter,
+
+    // The reader failed, nothing more can be read
+    done: bool,
+}
+
+impl<R: Read> ReadValues<R> {
+    /// Iterate over values read from the `reader`, with the default options.
+    pub fn new(reader: R) -> Self {
+        ReadValues::with_options(reader, &DEFAULT_OPTIONS)
+    }
+
+    /// Iterate over values read from the `reader`, enforcing the limits set
+    /// on `options` for each of them.
+    pub fn with_options(reader: R, options: &ParserOptions) -> Self {
+        ReadValues {
+            window: ReadWindow::new(reader),
+            options: options.clone().size_limit(usize::MAX),
+            size_limit: options.size_limit,
+            index: 0,
+            offset: 0,
+            dropped: LineCounter::default(),
+            done: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for ReadValues<R> {
+    type Item = Result<JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        // Pick up where the last parser left off, bytes still in the window
+        // are used before anything new is read.
+        let (byte_ptr, length) = {
+            let window = self.window.window();
+            (window.as_ptr(), window.len())
+        };
+
+        let mut parser = Parser::from_refill(&mut self.window, &self.options);
+        parser.byte_ptr = byte_ptr;
+        parser.length = length;
+        parser.index = self.index;
+        parser.offset = self.offset;
+        parser.dropped = self.dropped;
+
+        let item = parser.next_item(self.size_limit);
+
+        self.index = parser.index;
+        self.offset = parser.offset;
+        self.dropped = parser.dropped;
+
+        // Same as with `parse_reader`, a failed read trumps everything
+        match parser.refill_error.take() {
+            Some(error) => {
+                self.done = true;
+                Some(Err(error))
+            },
+            None => item,
+        }
+    }
+}
+
+/// Iterator over the elements of an array in a document read from an
+/// implementor of `std::io::Read`, the top level one or one at a JSON
+/// Pointer. Elements are parsed one at a time as they are read, so no more
+/// than the largest of them has to be kept in memory.
+///
+/// The whole document is checked the same way `parse_reader` would, the
+/// elements that are already out are good, but any error still ends up as
+/// the last item, after which the iterator is done. A pointer that doesn't
+/// lead anywhere makes for no elements, one that leads to something other
+/// than an array is an `Error::WrongType`. If a key on the way to the array
+/// is in an object more than once, the first one is followed.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// use json::stream::Elements;
+///
+/// let export: &[u8] = br#"{"version": 2, "rows": [{"id": 1}, {"id": 2}, {"id": 3}]}"#;
+/// let ids: Vec<_> = Elements::new(export)
+///     .at("/rows")
+///     .map(|row| row.unwrap()["id"].as_u32())
+///     .collect();
+///
+/// assert_eq!(ids, [Some(1), Some(2), Some(3)]);
+///
+/// let elements: Vec<_> = Elements::new(&b"[1, [2], 3] x"[..]).collect();
+///
+/// assert_eq!(elements[..3], [Ok(1.into()), Ok(array![2]), Ok(3.into())]);
+/// assert!(elements[3].is_err());
+/// # }
+/// ```
+pub struct Elements<R: Read> {
+    window: ReadWindow<R>,
+    options: ParserOptions,
+
+    // Reference tokens of the pointer, `None` if it's not a pointer at all
+    tokens: Option<Vec<String>>,
+
+    // Where the parser left off in the window, and how much of the source
+    // has been dropped from it so far
+    index: usize,
+    offset: usize,
+    dropped: LineCounter,
+
+    // Where the parser is in the document
+    walk: Walk,
+    state: State,
+}
+
+// How far along `Elements` is.
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    // Nothing's been read yet
+    Start,
+
+    // The array is open, with elements left
+    Elements,
+
+    // Done with everything, or something went wrong
+    Done,
+}
+
+impl<R: Read> Elements<R> {
+    /// Iterate over the elements of the array read from the `reader`, with
+    /// the default options.
+    pub fn new(reader: R) -> Self {
+        Elements::with_options(reader, &DEFAULT_OPTIONS)
+    }
+
+    /// Iterate over the elements of the array read from the `reader`,
+    /// enforcing the limits set on `options`. The size limit applies to the
+    /// whole document.
+    pub fn with_options(reader: R, options: &ParserOptions) -> Self {
+        Elements {
+            window: ReadWindow::new(reader),
+            options: options.clone(),
+            tokens: Some(Vec::new()),
+            index: 0,
+            offset: 0,
+            dropped: LineCounter::default(),
+            walk: Walk::new(options),
+            state: State::Start,
+        }
+    }
+
+    /// Go through the array at the JSON Pointer `pointer`, rather than the
+    /// top level one.
+    pub fn at(mut self, pointer: &str) -> Self {
+        self.tokens = match pointer.strip_prefix('/') {
+            Some(pointer)             => Some(pointer.split('/').map(|token| unescape(token).into_owned()).collect()),
+            None if pointer.is_empty() => Some(Vec::new()),
+            None                      => None,
+        };
+        self
+    }
+}
+
+impl<R: Read> Iterator for Elements<R> {
+    type Item = Result<JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state == State::Done {
+            return None;
+        }
+
+        let (byte_ptr, length) = {
+            let window = self.window.window();
+            (window.as_ptr(), window.len())
+        };
+
+        let mut parser = Parser::from_refill(&mut self.window, &self.options);
+        parser.byte_ptr = byte_ptr;
+        parser.length = length;
+        parser.index = self.index;
+        parser.offset = self.offset;
+        parser.dropped = self.dropped;
+
+        let result = parser.next_element(&self.tokens, &mut self.walk, &mut self.state);
+
+        self.index = parser.index;
+        self.offset = parser.offset;
+        self.dropped = parser.dropped;
+
+        let result = match parser.refill_error.take() {
+            Some(error) => Err(error),
+            None        => result,
+        };
+
+        if result.is_err() {
+            self.state = State::Done;
+        }
+
+        result.transpose()
+    }
+}
+
+impl<'a> Parser<'a> {
+    // Next element of the array, finding it first if that hasn't been done
+    // yet. Once it's closed, whatever is left of the document is checked.
+    fn next_element(&mut self, tokens: &Option<Vec<String>>, walk: &mut Walk, state: &mut State) -> Result<Option<JsonValue>> {
+        if *state == State::Start {
+            *state = match *tokens {
+                Some(ref tokens) if self.find_array(tokens, walk)? => State::Elements,
+                _                                                 => State::Done,
+            };
+        }
+
+        if *state == State::Elements {
+            if let Some(value) = self.read_element(walk)? {
+                return Ok(Some(value));
+            }
+
+            *state = State::Done;
+        }
+
+        self.validate_rest(walk)?;
+
+        Ok(None)
+    }
+
+    // Follow the `tokens` to the array, and open it. `false` if they don't
+    // lead to an array, after skipping over the value they lead to, or
+    // stopping in the array or object that's missing what they point to.
+    fn find_array(&mut self, tokens: &[String], walk: &mut Walk) -> Result<bool> {
+        let mut step = self.step(walk)?;
+
+        for token in tokens {
+            let wanted = array_index(token);
+            let mut len = 0;
+
+            if let Step::Scalar(ch) = step {
+                // Nothing to go into
+                self.validate_scalar(ch)?;
+
+                return Ok(false);
+            }
+
+            step = loop {
+                match self.step(walk)? {
+                    Step::Key(ch, expected) => {
+                        self.pin = self.index - 1;
+
+                        let found = {
+                            let key = expect_key!(self, ch, expected);
+
+                            self.count_key(walk, key)?;
+
+                            key == token
+                        };
+
+                        self.pin = usize::MAX;
+
+                        let step = self.step(walk)?;
+
+                        if found {
+                            break step;
+                        }
+
+                        self.validate_member(walk, step)?;
+                    },
+                    Step::Close(_) => return Ok(false),
+                    step           => {
+                        if wanted == Some(len) {
+                            break step;
+                        }
+
+                        len += 1;
+
+                        self.validate_member(walk, step)?;
+                    },
+                }
+            };
+        }
+
+        match step {
+            Step::Open(false) => Ok(true),
+            _                 => Err(Error::wrong_type("array")),
+        }
+    }
+
+    // Read the next element of the array, `None` if it's been closed.
+    fn read_element(&mut self, walk: &mut Walk) -> Result<Option<JsonValue>> {
+        if let Step::Close(_) = self.step(walk)? {
+            return Ok(None);
+        }
+
+        self.hand_over(walk);
+
+        let value = self.parse_value(walk.depth())?;
+
+        // Same as for `parse`, the element counts once it's been read
+        self.end_value(walk)?;
+
+        Ok(Some(value))
+    }
+
+    // Parse the next value in the sequence, or skip past it if it's broken.
+    fn next_item(&mut self, size_limit: usize) -> Option<Result<JsonValue>> {
+        let mut separator = b'\n';
+
+        match self.next_value(size_limit, &mut separator) {
+            Ok(value)  => value.map(Ok),
+            Err(error) => {
+                self.resync(separator);
+                Some(Err(error))
+            }
+        }
+    }
+
+    // Read the next value, if there is one. `separator` is set to what
+    // should be skipped to should anything go wrong.
+    fn next_value(&mut self, size_limit: usize, separator: &mut u8) -> Result<Option<JsonValue>> {
+        loop {
+            if self.is_eof() {
+                return Ok(None);
+            }
+
+            match self.read_byte() {
+                9 ..= 13 | 32 => self.bump(),
+                RS => {
+                    self.bump();
+                    *separator = RS;
+                },
+                b'/' if self.options.comments => {
+                    self.bump();
+                    self.skip_comment()?;
+                },
+                _ => break,
+            }
+        }
+
+        let start = self.offset + self.index;
+        let value = self.parse_value(0)?;
+
+        if self.offset + self.index - start > size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        // Values have to be separated, `12` is one number and not two
+        if !self.is_eof() {
+            match self.read_byte() {
+                9 ..= 13 | 32 | RS => {},
+                b'/' if self.options.comments => {},
+                _ => {
+                    self.bump();
+                    return self.unexpected_character(Expected::EndOfJson);
+                }
+            }
+        }
+
+        Ok(Some(value))
+    }
+
+    // Skip past the next `separator` after an error. The last byte read
+    // could already be it, if it's what the error is about.
+    fn resync(&mut self, separator: u8) {
+        self.index = self.index.saturating_sub(1);
+
+        while !self.is_eof() {
+            let ch = self.read_byte();
+            self.bump();
+
+            if ch == separator {
+                break;
+            }
+        }
+    }
+}
+
+/// Writes values one after another, each compact and on its own line, so
+/// that they can be read back with `Values` or `ReadValues`, or by anything
+/// else that reads JSON Lines. Strings never have raw newlines in them, so
+/// a line is always exactly one value.
+///
+/// Every write goes straight to the writer, wrap it in a `BufWriter` when
+/// writing lots of small values to a file or socket.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// use json::stream::Writer;
+///
+/// let mut writer = Writer::new(Vec::new());
+///
+/// writer.write(&object!{ status: 200, path: "/" }).unwrap();
+/// writer.write(&array![1, 2, 3]).unwrap();
+///
+/// assert_eq!(writer.into_inner(), b"{\"status\":200,\"path\":\"/\"}\n[1,2,3]\n");
+/// # }
+/// ```
+pub struct Writer<W: Write> {
+    writer: W,
+
+    // Put `RS` in front of every value
+    record_separator: bool,
+}
+
+impl<W: Write> Writer<W> {
+    /// Write JSON Lines, with a `\n` after every value.
+    pub fn new(writer: W) -> Self {
+        Writer {
+            writer,
+            record_separator: false,
+        }
+    }
+
+    /// Write an RFC 7464 `application/json-seq` stream, with a record
+    /// separator (`0x1E`) before every value and a `\n` after it.
+    pub fn json_seq(writer: W) -> Self {
+        Writer {
+            writer,
+            record_separator: true,
+        }
+    }
+
+    /// Write the `value` as a single line.
+    pub fn write(&mut self, value: &JsonValue) -> io::Result<()> {
+        if self.record_separator {
+            self.writer.write_all(&[RS])?;
+        }
+
+        value.write(&mut self.writer)?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Get a mutable reference to the underlying writer, to flush it for
+    /// example.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
@@ -0,0 +1,296 @@
+// A first pass over the source, the way simdjson does it, before the parser
+// proper goes through it. Blocks of 64 bytes are classified at once, with
+// SSE2 or AVX2 on x86_64 and a plain loop anywhere else, into a bitmap with
+// a bit set for every byte the parser has to stop at:
+//
+// - quotes, backslashes and control characters, which are the only bytes
+//   that can end a run of plain characters in a string,
+// - structural characters, `{`, `}`, `[`, `]`, `:` and `,`,
+// - and the first byte after any whitespace, which is where the next value
+//   or structural character starts.
+//
+// Whatever the parser is doing, finding the end of a string or the end of
+// some whitespace is then a matter of finding the next bit set, which skips
+// over up to 64 bytes at a time. The bitmap doesn't know or care what is in
+// a string and what isn't, so it's right even for sources that aren't valid
+// JSON, it only ever points at bytes the parser would've stopped at anyway.
+
+// Sources shorter than that aren't worth the extra pass.
+pub(super) const MIN_LENGTH: usize = 256;
+
+pub(super) struct Structurals {
+    bits: Vec<u64>,
+    length: usize,
+}
+
+// Masks of bytes in a block of 64, one bit per byte.
+struct Block {
+    // Quotes, backslashes, control and structural characters
+    special: u64,
+
+    // Whitespace, as the parser sees it: 9 to 13 and space
+    whitespace: u64,
+}
+
+impl Structurals {
+    pub fn new(source: &[u8]) -> Self {
+        let mut bits = Vec::with_capacity(source.len() / 64 + 1);
+
+        // Start of the source counts as whitespace, it's not in the middle
+        // of anything.
+        let mut after_whitespace = 1;
+        let classify = classifier();
+
+        let mut chunks = source.chunks_exact(64);
+
+        for chunk in &mut chunks {
+            bits.push(stops(classify(chunk), &mut after_whitespace));
+        }
+
+        let rest = chunks.remainder();
+
+        if !rest.is_empty() {
+            // Padding with whitespace doesn't set any bits
+            let mut padded = [b' '; 64];
+
+            padded[..rest.len()].copy_from_slice(rest);
+            bits.push(stops(classify(&padded), &mut after_whitespace));
+        }
+
+        Structurals {
+            bits,
+            length: source.len(),
+        }
+    }
+
+    // Position of the first byte at or after `from` the parser has to stop
+    // at, or the end of the source if there isn't one.
+    #[inline]
+    pub fn next(&self, from: usize) -> usize {
+        let mut word = from / 64;
+
+        if word >= self.bits.len() {
+            return from;
+        }
+
+        let mut bits = self.bits[word] & (!0 << (from % 64));
+
+        loop {
+            if bits != 0 {
+                return word * 64 + bits.trailing_zeros() as usize;
+            }
+
+            word += 1;
+
+            if word == self.bits.len() {
+                return self.length;
+            }
+
+            bits = self.bits[word];
+        }
+    }
+}
+
+// Bytes to stop at in a block, given what it's been classified as. Bit 0 of
+// `after_whitespace` says whether the block comes after whitespace, it's
+// updated for the next one.
+#[inline]
+fn stops(block: Block, after_whitespace: &mut u64) -> u64 {
+    let starts = !block.whitespace & ((block.whitespace << 1) | *after_whitespace);
+
+    *after_whitespace = block.whitespace >> 63;
+
+    block.special | starts
+}
+
+// Pick the fastest way to classify blocks the CPU is capable of.
+fn classifier() -> fn(&[u8]) -> Block {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return x86::classify_avx2_block;
+        }
+
+        x86::classify_sse2_block
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        classify
+    }
+}
+
+// Portable version, one byte at a time.
+#[cfg_attr(target_arch = "x86_64", allow(dead_code))]
+fn classify(chunk: &[u8]) -> Block {
+    let mut block = Block {
+        special: 0,
+        whitespace: 0,
+    };
+
+    for (bit, &byte) in chunk.iter().enumerate() {
+        let special = matches!(byte, 0 ..= 0x1F | b'"' | b'\\' | b'{' | b'}' | b'[' | b']' | b':' | b',');
+        let whitespace = matches!(byte, 9 ..= 13 | b' ');
+
+        block.special |= (special as u64) << bit;
+        block.whitespace |= (whitespace as u64) << bit;
+    }
+
+    block
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    use super::Block;
+
+    // SSE2 is always there on x86_64, no need to check for it.
+    pub fn classify_sse2_block(chunk: &[u8]) -> Block {
+        debug_assert!(chunk.len() == 64);
+
+        unsafe { classify_sse2(chunk.as_ptr()) }
+    }
+
+    pub fn classify_avx2_block(chunk: &[u8]) -> Block {
+        debug_assert!(chunk.len() == 64);
+
+        // Only ever picked after checking for AVX2
+        unsafe { classify_avx2(chunk.as_ptr()) }
+    }
+
+    // Both versions are the same, only the width of the registers differs.
+    macro_rules! classify {
+        (
+            $ptr:ident, $vector:ty, $width:expr, $mask:ty,
+            $load:ident, $set1:ident, $cmpeq:ident, $max:ident, $or:ident, $sub:ident, $movemask:ident
+        ) => ({
+            let mut block = Block {
+                special: 0,
+                whitespace: 0,
+            };
+
+            for lane in 0..64 / $width {
+                let bytes = $load($ptr.add(lane * $width) as *const $vector);
+
+                // No unsigned comparisons, `max(x, 0x1F) == 0x1F` is `x <= 0x1F`
+                let control = $cmpeq($max(bytes, $set1(0x1F)), $set1(0x1F));
+
+                // Setting bit 5 turns `[` and `]` into `{` and `}`
+                let folded = $or(bytes, $set1(0x20));
+
+                let special = $or(
+                    $or(
+                        $or($cmpeq(bytes, $set1(b'"' as i8)), $cmpeq(bytes, $set1(b'\\' as i8))),
+                        $or($cmpeq(bytes, $set1(b':' as i8)), $cmpeq(bytes, $set1(b',' as i8))),
+                    ),
+                    $or(
+                        $or($cmpeq(folded, $set1(b'{' as i8)), $cmpeq(folded, $set1(b'}' as i8))),
+                        control,
+                    ),
+                );
+
+                // 9 to 13 is `x - 9 <= 4`
+                let shifted = $sub(bytes, $set1(9));
+                let whitespace = $or(
+                    $cmpeq(bytes, $set1(b' ' as i8)),
+                    $cmpeq($max(shifted, $set1(4)), $set1(4)),
+                );
+
+                block.special |= ($movemask(special) as $mask as u64) << (lane * $width);
+                block.whitespace |= ($movemask(whitespace) as $mask as u64) << (lane * $width);
+            }
+
+            block
+        })
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn classify_sse2(ptr: *const u8) -> Block {
+        classify!(
+            ptr, __m128i, 16, u16,
+            _mm_loadu_si128, _mm_set1_epi8, _mm_cmpeq_epi8, _mm_max_epu8, _mm_or_si128, _mm_sub_epi8, _mm_movemask_epi8
+        )
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn classify_avx2(ptr: *const u8) -> Block {
+        classify!(
+            ptr, __m256i, 32, u32,
+            _mm256_loadu_si256, _mm256_set1_epi8, _mm256_cmpeq_epi8, _mm256_max_epu8, _mm256_or_si256, _mm256_sub_epi8, _mm256_movemask_epi8
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bytes to stop at, worked out one by one
+    fn expected(source: &[u8]) -> Vec<usize> {
+        (0..source.len()).filter(|&at| {
+            let whitespace = |byte: u8| matches!(byte, 9 ..= 13 | b' ');
+            let byte = source[at];
+
+            matches!(byte, 0 ..= 0x1F | b'"' | b'\\' | b'{' | b'}' | b'[' | b']' | b':' | b',')
+                || (!whitespace(byte) && (at == 0 || whitespace(source[at - 1])))
+        }).collect()
+    }
+
+    fn found(structurals: &Structurals) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut at = structurals.next(0);
+
+        while at < structurals.length {
+            found.push(at);
+            at = structurals.next(at + 1);
+        }
+
+        found
+    }
+
+    #[test]
+    fn structurals_match_bytes() {
+        let mut source = Vec::new();
+
+        // Every byte, next to every kind of neighbour, across block edges
+        for byte in 0..=255u8 {
+            source.extend_from_slice(&[byte, b' ', byte, byte, b'\n', b'a', byte, b'"']);
+        }
+        source.extend_from_slice(b"{\"key\": [1, 2.5, \"a\\\"b\"],\t\"x\":null}    tail");
+
+        for length in (0..source.len()).step_by(7).chain(Some(source.len())) {
+            let source = &source[..length];
+            let structurals = Structurals::new(source);
+
+            assert_eq!(found(&structurals), expected(source), "length {}", length);
+        }
+    }
+
+    #[test]
+    fn classifiers_agree() {
+        let source: Vec<u8> = (0..64u32).map(|n| (n * 37 % 256) as u8).collect();
+        let portable = classify(&source);
+        let fastest = classifier()(&source);
+
+        assert_eq!(portable.special, fastest.special);
+        assert_eq!(portable.whitespace, fastest.whitespace);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            for offset in 0..=192 {
+                let source: Vec<u8> = (offset..offset + 64).map(|n: u32| (n * 13 % 256) as u8).collect();
+                let portable = classify(&source);
+                let sse2 = x86::classify_sse2_block(&source);
+
+                assert_eq!((portable.special, portable.whitespace), (sse2.special, sse2.whitespace));
+
+                if is_x86_feature_detected!("avx2") {
+                    let avx2 = x86::classify_avx2_block(&source);
+
+                    assert_eq!((portable.special, portable.whitespace), (avx2.special, avx2.whitespace));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+// Reading `\u` escapes. Anything outside of the Basic Multilingual Plane is
+// escaped as a pair of UTF-16 surrogates, a high one followed by a low one.
+// Surrogates on their own aren't characters, and can't be put in a `str`,
+// but JavaScript has no trouble producing them, so they do show up.
+//
+// Unless the options say otherwise, a high surrogate that isn't followed by
+// a `\u` escape is an unexpected character, and any surrogate that doesn't
+// make a pair is reported as `Error::LoneSurrogate`, pointing at its escape.
+// Otherwise they are either replaced, or moved out of the way into the last
+// private use plane, where a generator can find them and write them back
+// out as they were. Characters in that plane are real characters too, and
+// show up in strings that were never near a lone surrogate, so each one
+// that stands for a surrogate comes right after U+FFFF. That's one of the
+// noncharacters Unicode keeps for uses like this, and private use characters
+// without it in front are left alone.
+
+use std::str;
+use std::char::decode_utf16;
+use std::convert::TryFrom;
+
+use crate::{ Error, Expected, Result };
+use super::Parser;
+
+// Where `LoneSurrogates::Preserve` puts surrogates, U+D800 becomes U+10F800,
+// after `SURROGATE_MARK`.
+pub(crate) const PRESERVED: u32 = 0x10F800;
+pub(crate) const SURROGATE_MARK: char = '\u{FFFF}';
+
+/// What to do about `\u` escapes of UTF-16 surrogates that don't make up a
+/// pair, like `"\uDE00"`, which JavaScript happily produces by cutting an
+/// emoji in half.
+///
+/// ```
+/// use json::{ Error, LoneSurrogates, ParserOptions };
+/// use json::codegen::{ DumpGenerator, Generator };
+///
+/// let source = r#"["😀", "\uDE00 cut"]"#;
+/// let parse = |policy| json::parse_with(source, &ParserOptions::new().lone_surrogates(policy));
+///
+/// assert_eq!(parse(LoneSurrogates::Error), Err(Error::LoneSurrogate {
+///     codepoint: 0xDE00,
+///     line: 1,
+///     column: 8,
+///     offset: 10,
+/// }));
+/// assert_eq!(parse(LoneSurrogates::Replace).unwrap()[1], "\u{FFFD} cut");
+///
+/// let preserved = parse(LoneSurrogates::Preserve).unwrap();
+/// let mut generator = DumpGenerator::new().escape_lone_surrogates(true);
+///
+/// generator.write_json(&preserved).unwrap();
+///
+/// assert_eq!(generator.consume(), r#"["😀","\ude00 cut"]"#);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoneSurrogates {
+    /// Fail with `Error::LoneSurrogate`, pointing at the escape. A high
+    /// surrogate followed by anything but another escape fails as an
+    /// unexpected character instead. This is the default.
+    Error,
+
+    /// Replace each of them with U+FFFD REPLACEMENT CHARACTER.
+    Replace,
+
+    /// Keep them as characters in the last private use plane, U+D800 to
+    /// U+DFFF become U+10F800 to U+10FFFF, each one after a U+FFFF
+    /// noncharacter. Generators that are set to `escape_lone_surrogates`
+    /// write those pairs back out as `\u` escapes of the surrogates, so that
+    /// strings make it through unchanged.
+    ///
+    /// Private use characters that don't follow U+FFFF are written as they
+    /// are. A string that has U+FFFF followed by one of those to begin with
+    /// can't be told apart from a lone surrogate, and gets the same
+    /// treatment.
+    Preserve,
+}
+
+impl<'a> Parser<'a> {
+    // Read a `\u` escape into `buf`, which might be followed by the low
+    // surrogate of a pair. That's one character, or two for a surrogate
+    // that's preserved. `\u` has already been read.
+    pub(super) fn read_escaped<'b>(&mut self, buf: &'b mut [u8; 8]) -> Result<&'b str> {
+        // With a reader the escape might not be in the window anymore by
+        // the time it turns out to be a lone surrogate
+        let start = self.offset + self.index - 2;
+        let codepoint = self.read_hexdec_codepoint()?;
+
+        if let Ok(code) = char::try_from(codepoint as u32) {
+            return Ok(code.encode_utf8(buf));
+        }
+
+        if codepoint < 0xDC00 {
+            if self.options.lone_surrogates == LoneSurrogates::Error {
+                expect_sequence!(self, Expected::LowSurrogate, b'\\', b'u');
+
+                let low = self.read_hexdec_codepoint()?;
+
+                if let Some(Ok(code)) = decode_utf16([codepoint, low].iter().copied()).next() {
+                    return Ok(code.encode_utf8(buf));
+                }
+            } else if let Some(low) = self.low_surrogate_ahead() {
+                self.index += 6;
+
+                if let Some(Ok(code)) = decode_utf16([codepoint, low].iter().copied()).next() {
+                    return Ok(code.encode_utf8(buf));
+                }
+            }
+        }
+
+        match self.options.lone_surrogates {
+            LoneSurrogates::Error    => self.lone_surrogate(start, codepoint),
+            LoneSurrogates::Replace  => Ok(char::REPLACEMENT_CHARACTER.encode_utf8(buf)),
+            LoneSurrogates::Preserve => {
+                let preserved = char::try_from(PRESERVED + (codepoint as u32 - 0xD800)).expect("Must be a valid character");
+                let len = SURROGATE_MARK.encode_utf8(&mut buf[..]).len();
+                let len = len + preserved.encode_utf8(&mut buf[len..]).len();
+
+                Ok(str::from_utf8(&buf[..len]).expect("Must be UTF-8"))
+            },
+        }
+    }
+
+    // Low surrogate escaped right where the parser is, if there is one.
+    fn low_surrogate_ahead(&mut self) -> Option<u16> {
+        while self.length - self.index < 6 && self.refill(self.index) {}
+
+        let escape = self.source().get(self.index .. self.index + 6)?;
+
+        if &escape[..2] != b"\\u" {
+            return None;
+        }
+
+        let hex = std::str::from_utf8(&escape[2..]).ok()?;
+
+        match u16::from_str_radix(hex, 16) {
+            Ok(low @ 0xDC00 ..= 0xDFFF) if hex.bytes().all(|byte| byte.is_ascii_hexdigit()) => Some(low),
+            _ => None,
+        }
+    }
+
+    // The surrogate escaped at `start` doesn't make a pair, and that's
+    // not allowed. Everything since is part of the escapes, all on one line.
+    #[cold]
+    fn lone_surrogate<T: Sized>(&self, start: usize, codepoint: u16) -> Result<T> {
+        let (line, column) = self.position(self.index);
+
+        Err(Error::LoneSurrogate {
+            codepoint,
+            line,
+            column: column - (self.offset + self.index - start),
+            offset: start,
+        })
+    }
+}
@@ -0,0 +1,337 @@
+// Documents cut off at an arbitrary byte, as they come out of something
+// that streams JSON as it's generating it. Whatever is there is still worth
+// having, so the source is cut back to the longest prefix that makes a valid
+// document once the containers still open are closed, and whatever was being
+// written when it ended is finished off where it can be: strings are closed,
+// literals spelled out and numbers trimmed to their last valid digit.
+//
+// Finding the prefix takes walking through the source up to where it ends,
+// checking the syntax along the way, to know which containers are open and
+// where the last whole value ends. Anything that doesn't fit is left for the
+// parser to report, which reads the patched up source the same as any other,
+// so that values and errors are exactly what `parse_with` would produce.
+
+use crate::{ JsonValue, Result, Error };
+use super::{ Parser, ParserOptions, DuplicateKeys, DEFAULT_OPTIONS };
+use super::walk::{ Walk, Step };
+
+/// A value parsed with `parse_truncated`, along with what had to be
+/// completed to get it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Truncated {
+    /// The most complete value the source makes up.
+    pub value: JsonValue,
+
+    /// Everything that was cut off, innermost first. Empty if the source
+    /// was a whole document.
+    pub completed: Vec<Completion>,
+}
+
+/// Something that was cut off at the end of the source, and where it
+/// started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Completion {
+    pub kind: Incomplete,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// What kind of thing was cut off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Incomplete {
+    /// An array that was closed.
+    Array,
+
+    /// An object that was closed. Entries that were cut off before their
+    /// value began are left out.
+    Object,
+
+    /// A string that was closed after its last whole character. Escapes
+    /// that were cut off are left out.
+    String,
+
+    /// A number that ended with a sign, a decimal point or an exponent
+    /// without any digits, which were left out. If that was all there was
+    /// to it, the whole number is left out.
+    Number,
+
+    /// `true`, `false` or `null`, cut off part way through.
+    Literal,
+}
+
+/// Parse a JSON document that might have been cut off at any byte. Strings,
+/// arrays and objects that were left open are closed, literals are finished
+/// and numbers are trimmed, to get the most complete value the source makes
+/// up. What was completed is reported, innermost first, in `completed`.
+///
+/// Anything that doesn't make sense before the source ends is still an
+/// error, the same as from `parse`, as is a source that ends before any
+/// value begins.
+///
+/// ```
+/// # #[macro_use] extern crate json;
+/// # fn main() {
+/// use json::Incomplete;
+///
+/// let truncated = json::parse_truncated(r#"{"items":[{"name":"ab"#).unwrap();
+///
+/// assert_eq!(truncated.value, object!{ items: [{ name: "ab" }] });
+///
+/// let kinds: Vec<Incomplete> = truncated.completed.iter().map(|completion| completion.kind).collect();
+///
+/// assert_eq!(kinds, [Incomplete::String, Incomplete::Object, Incomplete::Array, Incomplete::Object]);
+/// assert_eq!(truncated.completed[1].column, 11);
+///
+/// assert_eq!(json::parse_truncated(r#"[1.5e-, tr"#).unwrap_err(), json::parse("[1.5e-, tr").unwrap_err());
+/// assert_eq!(json::parse_truncated(r#"[1.5, tr"#).unwrap().value, array![1.5, true]);
+/// assert_eq!(json::parse_truncated(r#"{"a": 1, "b"#).unwrap().value, object!{ a: 1 });
+/// # }
+/// ```
+pub fn parse_truncated(source: &str) -> Result<Truncated> {
+    parse_truncated_with(source, &DEFAULT_OPTIONS)
+}
+
+/// Parse a JSON document that might have been cut off, while enforcing the
+/// limits set on `options`.
+pub fn parse_truncated_with(source: &str, options: &ParserOptions) -> Result<Truncated> {
+    if source.len() > options.size_limit {
+        return Err(Error::ExceededSizeLimit);
+    }
+
+    // Doesn't make sense even as a prefix, or is a whole document, the
+    // parser can tell what it is
+    let (end, stack) = match scan(source, options) {
+        Some(scanned) => scanned,
+        None          => return Parser::new(source, options).parse().map(|value| Truncated {
+            value,
+            completed: Vec::new(),
+        }),
+    };
+
+    let mut patched = String::with_capacity(end.cut + end.tail.len() + stack.len());
+
+    patched.push_str(&source[..end.cut]);
+    patched.push_str(end.tail);
+
+    for &(_, closing) in stack.iter().rev() {
+        patched.push(closing as char);
+    }
+
+    // The closing brackets don't count towards the size
+    let unlimited = options.clone().size_limit(usize::MAX);
+    let value = Parser::new(&patched, &unlimited).parse()?;
+
+    let position = Parser::new(source, options);
+    let mut completed = Vec::with_capacity(stack.len() + 1);
+    let mut complete = |kind, offset| {
+        let (line, column) = position.position(offset);
+
+        completed.push(Completion { kind, offset, line, column });
+    };
+
+    if let Some((kind, offset)) = end.value {
+        complete(kind, offset);
+    }
+
+    for &(offset, closing) in stack.iter().rev() {
+        complete(if closing == b']' { Incomplete::Array } else { Incomplete::Object }, offset);
+    }
+
+    Ok(Truncated { value, completed })
+}
+
+// Where the source ends up cut off.
+struct End {
+    cut: usize,
+
+    // Appended to finish off the value that was cut off, if there's one
+    tail: &'static str,
+
+    // What that value was, and where it starts
+    value: Option<(Incomplete, usize)>,
+}
+
+impl End {
+    // The source ends part way through something that can't be finished,
+    // like a key, so everything after `whole`, where the last whole value
+    // ends, is left out.
+    fn drop_rest(whole: usize) -> Self {
+        End { cut: whole, tail: "", value: None }
+    }
+}
+
+// Walk through the source to find where it needs to be cut, along with the
+// arrays and objects that are still open there, as where they start and the
+// byte that closes them. `None` if it stops making sense before it ends, or
+// if it doesn't end early at all.
+fn scan(source: &str, options: &ParserOptions) -> Option<(End, Vec<(usize, u8)>)> {
+    // Only the syntax matters here, the limits are up to the parser
+    let syntax = ParserOptions {
+        depth_limit: usize::MAX,
+        size_limit: usize::MAX,
+        string_length_limit: usize::MAX,
+        object_entries_limit: usize::MAX,
+        array_length_limit: usize::MAX,
+        duplicate_keys: DuplicateKeys::KeepLast,
+        ..options.clone()
+    };
+    let mut parser = Parser::new(source, &syntax);
+    let mut walk = Walk::new(&syntax);
+    let mut stack = Vec::new();
+
+    // End of the longest prefix that is a valid document once the arrays
+    // and objects on the stack are closed
+    let mut whole = 0;
+
+    loop {
+        // A comment that isn't closed is as good as the end
+        let step = match parser.step(&mut walk) {
+            Ok(step)                        => step,
+            Err(Error::UnexpectedEndOfJson) => return Some((End::drop_rest(whole), stack)),
+            Err(_)                          => return None,
+        };
+
+        match step {
+            Step::Open(object) => stack.push((walk.start(), if object { b'}' } else { b']' })),
+            Step::Close(_)     => { stack.pop(); },
+
+            // Entries cut off before their value are left out
+            Step::Key(ch, expected) => match parser.validate_key(&mut walk, ch, expected) {
+                Ok(())                          => continue,
+                Err(Error::UnexpectedEndOfJson) => return Some((End::drop_rest(whole), stack)),
+                Err(_)                          => return None,
+            },
+
+            Step::Scalar(ch) => match parser.validate_scalar(ch) {
+                Ok(())                          => {},
+                Err(Error::UnexpectedEndOfJson) => return Some((finish(source.as_bytes(), options, ch, walk.start(), whole)?, stack)),
+                Err(_)                          => return None,
+            },
+
+            Step::End => return None,
+        }
+
+        whole = parser.index;
+    }
+}
+
+// Finish off the value starting with `ch` at `start`, which the source ends
+// inside of.
+fn finish(source: &[u8], options: &ParserOptions, ch: u8, start: usize, whole: usize) -> Option<End> {
+    match ch {
+        b'"' | b'\'' => Some(string(source, start, ch)),
+        b't' | b'f' | b'n' => literal(source, start),
+        _ => number(source, options, start, whole),
+    }
+}
+
+// Close a string starting with `quote` after its last whole character.
+fn string(source: &[u8], start: usize, quote: u8) -> End {
+    let mut index = start + 1;
+    let mut whole = index;
+
+    while index < source.len() {
+        if source[index] == b'\\' {
+            if escape_cut(source, index) {
+                break;
+            }
+
+            // Whatever the escape is made of can't end the string
+            index += 2;
+        } else {
+            index += 1;
+        }
+
+        whole = index;
+    }
+
+    End {
+        cut: whole,
+        tail: if quote == b'"' { "\"" } else { "'" },
+        value: Some((Incomplete::String, start)),
+    }
+}
+
+// Whether the source ends inside the escape at `at`. That includes a high
+// surrogate that might have been followed by a low one.
+fn escape_cut(source: &[u8], at: usize) -> bool {
+    let escape = &source[at..];
+
+    if escape.len() < 2 {
+        return true;
+    }
+
+    if escape[1] != b'u' {
+        return false;
+    }
+
+    let hex = &escape[2..escape.len().min(6)];
+
+    // Not an escape the parser will take either way
+    if !hex.iter().all(u8::is_ascii_hexdigit) {
+        return false;
+    }
+
+    if hex.len() < 4 {
+        return true;
+    }
+
+    let codepoint = u16::from_str_radix(std::str::from_utf8(hex).expect("Must be ASCII"), 16).expect("Must be hex");
+
+    match escape.get(6) {
+        None if (0xD800 .. 0xDC00).contains(&codepoint) => true,
+        Some(b'\\') if (0xD800 .. 0xDC00).contains(&codepoint) => escape_cut(source, at + 6),
+        _ => false,
+    }
+}
+
+// Spell out `true`, `false` or `null`, cut off part way through.
+fn literal(source: &[u8], start: usize) -> Option<End> {
+    let word = &source[start..];
+    let literal = ["true", "false", "null"].iter().find(|literal| literal.as_bytes().starts_with(word))?;
+
+    Some(End {
+        cut: source.len(),
+        tail: &literal[word.len()..],
+        value: Some((Incomplete::Literal, start)),
+    })
+}
+
+// Cut a number back to its last digit. If there's none, it's left out, along
+// with everything after `whole`.
+fn number(source: &[u8], options: &ParserOptions, start: usize, whole: usize) -> Option<End> {
+    let number = &source[start..];
+    let digits = number.iter().position(|&byte| byte != b'-' && byte != b'+').unwrap_or(number.len());
+    let hex = options.hex_numbers && matches!(number[digits..], [b'0', b'x', ..] | [b'0', b'X', ..]);
+    let mut end = number.len();
+
+    // Without hex numbers `0x` can't go anywhere, the parser says why
+    loop {
+        match number[..end].last() {
+            Some(b'x') | Some(b'X') if options.hex_numbers => end -= 1,
+            Some(b'.') | Some(b'+') | Some(b'-') => end -= 1,
+            Some(b'e') | Some(b'E') if !hex => end -= 1,
+            _ => break,
+        }
+    }
+
+    if end == number.len() {
+        return None;
+    }
+
+    // Nothing but a sign or a decimal point, leave it out, but still say
+    // that it was there
+    if end == 0 {
+        return Some(End {
+            value: Some((Incomplete::Number, start)),
+            ..End::drop_rest(whole)
+        });
+    }
+
+    Some(End {
+        cut: start + end,
+        tail: "",
+        value: Some((Incomplete::Number, start)),
+    })
+}
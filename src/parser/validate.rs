@@ -0,0 +1,271 @@
+// Going through a document without building anything. The walk takes care
+// of everything but the values and keys themselves, which are read with the
+// same macros `Parser::parse_value` uses, so that whatever is wrong with the
+// source is found at the same position, except strings are only checked and
+// never read into the buffer.
+//
+// With the default options nothing is allocated at all. Keys are only read
+// into the buffer, and kept, when the options need them to find duplicates.
+// That's for `DuplicateKeys::Error`, but also for a limit on entries, since
+// every other policy leaves a single entry for each key.
+
+use std::{ str, slice };
+
+use crate::{ Error, Expected, Result };
+use super::{ Parser, ParserOptions, ALLOWED, DEFAULT_OPTIONS };
+use super::walk::{ Walk, Step };
+
+/// Check that a document is valid JSON, without parsing it into anything.
+///
+/// Errors are the same, at the same positions, as those `parse` would
+/// return. Nothing is allocated while checking.
+///
+/// ```
+/// # use json::{ Error, Expected };
+/// assert_eq!(json::validate(r#"{"a": [1, 2, {"b": "c\n"}]}"#), Ok(()));
+///
+/// assert_eq!(json::validate("[1, 2,]"), Err(Error::UnexpectedCharacter {
+///     ch: ']',
+///     line: 1,
+///     column: 7,
+///     offset: 6,
+///     expected: Expected::Value,
+/// }));
+/// ```
+pub fn validate(source: &str) -> Result<()> {
+    Parser::new(source, &DEFAULT_OPTIONS).validate(false)
+}
+
+/// Check that a document is valid while enforcing the limits set on
+/// `options`. Nothing is allocated, unless the options set a limit on the
+/// length of arrays or number of entries in objects, or ask for duplicate
+/// keys to be rejected, which needs a bit of memory for every array or
+/// object that's open.
+pub fn validate_with(source: &str, options: &ParserOptions) -> Result<()> {
+    Parser::new(source, options).validate(false)
+}
+
+/// Check that raw bytes are a valid JSON document, including that they are
+/// valid UTF-8.
+pub fn validate_bytes(source: &[u8]) -> Result<()> {
+    Parser::from_bytes(source, &DEFAULT_OPTIONS, true).validate(false)
+}
+
+/// Check that raw bytes are a valid JSON document while enforcing the limits
+/// set on `options`.
+pub fn validate_bytes_with(source: &[u8], options: &ParserOptions) -> Result<()> {
+    Parser::from_bytes(source, options, true).validate(false)
+}
+
+impl<'a> Parser<'a> {
+    // Check the whole source, the way `parse` would. The first pass pays
+    // off for anything that goes on to use the source, but when only
+    // checking it, it would be the one thing that allocates.
+    pub(super) fn validate(&mut self, first_pass: bool) -> Result<()> {
+        if self.length > self.options.size_limit {
+            return Err(Error::ExceededSizeLimit);
+        }
+
+        if first_pass {
+            self.find_structurals();
+        }
+
+        self.validate_rest(&mut Walk::new(self.options))
+    }
+
+    // Check whatever is left of the `walk`.
+    pub(super) fn validate_rest(&mut self, walk: &mut Walk) -> Result<()> {
+        loop {
+            match self.step(walk)? {
+                Step::Scalar(ch)        => self.validate_scalar(ch)?,
+                Step::Key(ch, expected) => self.validate_key(walk, ch, expected)?,
+                Step::End               => return Ok(()),
+                _                       => {},
+            }
+        }
+    }
+
+    // Check the value that `step` of the `walk` starts, walking through the
+    // rest of it.
+    pub(super) fn validate_member(&mut self, walk: &mut Walk, step: Step) -> Result<()> {
+        let depth = match step {
+            Step::Open(_)    => walk.depth() - 1,
+            Step::Scalar(ch) => return self.validate_scalar(ch),
+            _                => return Ok(()),
+        };
+
+        loop {
+            match self.step(walk)? {
+                Step::Scalar(ch)                        => self.validate_scalar(ch)?,
+                Step::Key(ch, expected)                 => self.validate_key(walk, ch, expected)?,
+                Step::Close(_) if walk.depth() == depth => return Ok(()),
+                _                                       => {},
+            }
+        }
+    }
+
+    // Same as `Parser::read_scalar`, only nothing is read into the buffer.
+    pub(super) fn validate_scalar(&mut self, ch: u8) -> Result<()> {
+        match ch {
+            b'"' => self.validate_string()?,
+            b'\'' if self.options.single_quotes => {
+                self.validate_complex_string(self.index, b'\'')?;
+            },
+            _ if self.options.starts_number(ch) => {
+                self.index -= 1;
+                self.read_number()?;
+            },
+            b't' => {
+                expect_sequence!(self, Expected::Literal("true"), b'r', b'u', b'e');
+            },
+            b'f' => {
+                expect_sequence!(self, Expected::Literal("false"), b'a', b'l', b's', b'e');
+            },
+            b'n' => {
+                expect_sequence!(self, Expected::Literal("null"), b'u', b'l', b'l');
+            },
+            _ => return self.unexpected_character(Expected::Value)
+        }
+
+        Ok(())
+    }
+
+    // Check a key starting with `ch` and count it towards the object it's
+    // in. It's only read into the buffer when keys have to be kept.
+    pub(super) fn validate_key(&mut self, walk: &mut Walk, ch: u8, expected: Expected) -> Result<()> {
+        if !self.options.keeps_keys() {
+            match ch {
+                b'"' => self.validate_string()?,
+                b'\'' if self.options.single_quotes => self.validate_complex_string(self.index, b'\'')?,
+                b'a' ..= b'z' | b'A' ..= b'Z' | b'_' | b'$' if self.options.unquoted_keys => {
+                    self.read_identifier()?;
+                },
+                _ => return self.unexpected_character(expected)
+            }
+
+            return self.count_entry(walk, true);
+        }
+
+        self.pin = self.index - 1;
+
+        let key = expect_key!(self, ch, expected);
+
+        self.count_key(walk, key)?;
+
+        // The key's been copied, no need to keep it in the buffer
+        self.buffer.clear();
+
+        self.pin = usize::MAX;
+        Ok(())
+    }
+
+    // Same as `expect_string!`, the opening quote has already been read.
+    fn validate_string(&mut self) -> Result<()> {
+        let mut start = self.index;
+
+        loop {
+            self.skip_plain();
+
+            // Keep the string in the window, it's checked as a whole
+            if self.index == self.length {
+                let index = self.index;
+
+                if !self.refill(start) {
+                    return Err(Error::UnexpectedEndOfJson);
+                }
+                start -= index - self.index;
+            }
+
+            let ch = self.read_byte();
+            self.bump();
+
+            if ALLOWED[ch as usize] {
+                continue;
+            }
+            if ch == b'"' {
+                if self.index - 1 - start > self.options.string_length_limit {
+                    return Err(Error::ExceededStringLengthLimit);
+                }
+
+                if self.validate_utf8 {
+                    self.check_utf8(start, self.index - 1)?;
+                }
+
+                return Ok(());
+            }
+            if ch == b'\\' {
+                return self.validate_complex_string(start, b'"');
+            }
+
+            if self.validate_utf8 {
+                self.check_utf8(start, self.index - 1)?;
+            }
+
+            return self.unexpected_character(Expected::StringCharacter);
+        }
+    }
+
+    // Same as `Parser::read_complex_string`, except escapes are only
+    // checked, and the length of the string they make up is only counted.
+    // The string starts at `start`. For strings in single quotes that's
+    // right after the opening quote, and nothing after it has been read yet.
+    fn validate_complex_string(&mut self, mut start: usize, quote: u8) -> Result<()> {
+        if quote == b'\'' {
+            expect_byte!(self);
+
+            // Reading it might have moved the window
+            start = self.index - 1;
+        }
+
+        let mut ch = self.source()[self.index - 1];
+
+        if self.validate_utf8 {
+            self.check_utf8(start, self.index - 1)?;
+        }
+
+        let mut len = self.index - 1 - start;
+
+        loop {
+            if len > self.options.string_length_limit {
+                return Err(Error::ExceededStringLengthLimit);
+            }
+            if ALLOWED[ch as usize] && ch != quote {
+                if ch < 0x80 || !self.validate_utf8 {
+                    len += 1;
+                } else {
+                    len += self.skip_utf8_char(ch)?.len();
+                }
+                ch = expect_byte!(self);
+                continue;
+            }
+            match ch {
+                _ if ch == quote => break,
+                // Only when in single quotes
+                b'"'  => len += 1,
+                b'\\' => {
+                    match expect_byte!(self) {
+                        b'u'  => {
+                            len += self.read_escaped(&mut [0; 8])?.len();
+                            ch = expect_byte!(self);
+                            continue;
+                        },
+                        b'"'  |
+                        b'\\' |
+                        b'/'  |
+                        b'b'  |
+                        b'f'  |
+                        b't'  |
+                        b'r'  |
+                        b'n'  => len += 1,
+                        b'\'' if self.options.single_quotes => len += 1,
+                        _     => return self.unexpected_character(Expected::Escape)
+                    }
+                },
+                _ => return self.unexpected_character(Expected::StringCharacter)
+            }
+            ch = expect_byte!(self);
+        }
+
+        Ok(())
+    }
+}
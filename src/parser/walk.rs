@@ -0,0 +1,561 @@
+// Going through a document one step at a time, which is what everything but
+// `Parser::parse_value` and the push parser is built on: validating, pull
+// parsing, parsing into borrowed values, documents and source maps, lazy
+// values, extracting pointers, going through the elements of a streamed
+// array, and parsing documents that are broken or cut off. A step takes care
+// of whitespace and comments, commas and colons, trailing commas, the depth
+// limit, the length of arrays and the end of the source, all in the same
+// order `Parser::parse_value` does, so that whatever goes through a document
+// this way finds the same errors at the same positions. What a step hands
+// out is where each value or key starts, reading those, and building
+// anything out of them, is up to whoever is walking.
+//
+// Entries are counted as keys are handed back with `count_entry` or
+// `count_key`, only the one walking knows whether a key is new to an object.
+//
+// All that's kept about arrays and objects that are open is a bit for which
+// of the two each is, and those fit inline up to the default depth limit, so
+// with the default options walking doesn't allocate anything. Limits on the
+// length of arrays or the number of entries, or keys that have to be kept,
+// need a bit more for each, on the heap.
+
+use std::collections::HashMap;
+use std::{ str, slice };
+
+use crate::number::{ Number, Decimal };
+use crate::{ Error, Expected, JsonValue, Result };
+use super::{ Parser, ParserOptions, DuplicateKeys, ALLOWED, DEPTH_LIMIT };
+
+// Words of bits that fit the default depth limit.
+const INLINE: usize = DEPTH_LIMIT / 64;
+
+// What a step has come to.
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum Step {
+    // First byte of a value that's not an array or object, which is up to
+    // the caller to read, or to find out it's not a value at all
+    Scalar(u8),
+
+    // `[`, or `{` if it's `true`
+    Open(bool),
+
+    // First byte of a key, along with what's expected there. The caller
+    // reads and counts it before taking the next step, which is its value.
+    Key(u8, Expected),
+
+    // `]`, or `}` if it's `true`
+    Close(bool),
+
+    // Done with the value, and with the source if it's a whole document
+    End,
+}
+
+// What the walk expects next, same as in `recover.rs`.
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum State {
+    // Value of an object entry, or the value the walk is through
+    Value,
+
+    // Array element, or the end of the array if it's `first`
+    Element { first: bool },
+
+    // Object key, or the end of the object if it's `first`
+    Key { first: bool },
+
+    // `:` after a key
+    Colon,
+
+    // Right after a value, which isn't counted towards the array it's in yet
+    Next,
+
+    // Same, once it has been. `,` or the end of the current array or
+    // object, or the end of the walk if there is none
+    Counted,
+
+    // Nothing left
+    Done,
+}
+
+// Where a walk through a document, or through a single value in it, is.
+pub(super) struct Walk {
+    state: State,
+
+    // What was expected before the last value or key was handed out
+    last: State,
+
+    // Arrays and objects the value is in, for the depth limit
+    outside: usize,
+
+    // Nothing but whitespace and comments can follow the value
+    whole: bool,
+
+    // Where the last value or key handed out starts, in the window
+    start: usize,
+
+    nesting: Nesting,
+}
+
+// Arrays and objects that are still open, as a bit for each that's set for
+// objects.
+struct Nesting {
+    depth: usize,
+    bits: [u64; INLINE],
+
+    // Bits for anything nested deeper than the default depth limit allows
+    deeper: Vec<u64>,
+
+    // Members or entries of each, only when the options need them
+    counted: Option<Vec<Open>>,
+}
+
+// What's counted for an array or object that's still open.
+pub(super) struct Open {
+    pub(super) len: usize,
+
+    // Keys so far, and how many times each was found, only when the options
+    // need them kept
+    keys: HashMap<String, usize>,
+
+    // A key was found more times than its values can be collected for.
+    // That fails once its value has been read, same as for `parse`.
+    overflow: bool,
+}
+
+impl Open {
+    fn new() -> Self {
+        Open { len: 0, keys: HashMap::new(), overflow: false }
+    }
+
+    // Note down the `key`, returns how many times it's been found so far.
+    pub(super) fn insert(&mut self, key: &str) -> usize {
+        match self.keys.get_mut(key) {
+            Some(found) => {
+                *found += 1;
+                *found
+            },
+            None => {
+                self.keys.insert(key.to_owned(), 1);
+                1
+            },
+        }
+    }
+}
+
+impl Nesting {
+    fn push(&mut self, object: bool) {
+        let (word, bit) = (self.depth / 64, self.depth % 64);
+        let word = match self.bits.get_mut(word) {
+            Some(word) => word,
+            None       => {
+                if self.deeper.len() <= word - INLINE {
+                    self.deeper.push(0);
+                }
+
+                &mut self.deeper[word - INLINE]
+            },
+        };
+
+        *word = (*word & !(1 << bit)) | ((object as u64) << bit);
+        self.depth += 1;
+
+        if let Some(ref mut counted) = self.counted {
+            counted.push(Open::new());
+        }
+    }
+
+    fn pop(&mut self) {
+        self.depth -= 1;
+
+        if let Some(ref mut counted) = self.counted {
+            counted.pop();
+        }
+    }
+
+    // Whether the innermost array or object is an object, `None` if there
+    // is nothing open.
+    fn object(&self) -> Option<bool> {
+        let depth = self.depth.checked_sub(1)?;
+        let (word, bit) = (depth / 64, depth % 64);
+        let word = match self.bits.get(word) {
+            Some(&word) => word,
+            None        => self.deeper[word - INLINE],
+        };
+
+        Some(word & (1 << bit) != 0)
+    }
+
+    fn open(&mut self) -> Option<&mut Open> {
+        self.counted.as_mut().and_then(|counted| counted.last_mut())
+    }
+}
+
+impl Walk {
+    // Walk through a whole document.
+    pub(super) fn new(options: &ParserOptions) -> Self {
+        Walk {
+            whole: true,
+            ..Walk::value(options, 0)
+        }
+    }
+
+    // Walk through a single value that's in `depth` arrays and objects,
+    // leaving whatever comes after it alone.
+    pub(super) fn value(options: &ParserOptions, depth: usize) -> Self {
+        let counted = options.array_length_limit != usize::MAX || options.keeps_keys();
+
+        Walk {
+            state: State::Value,
+            last: State::Value,
+            outside: depth,
+            whole: false,
+            start: 0,
+            nesting: Nesting {
+                depth: 0,
+                bits: [0; INLINE],
+                deeper: Vec::new(),
+                counted: if counted { Some(Vec::new()) } else { None },
+            },
+        }
+    }
+
+    pub(super) fn state(&self) -> State {
+        self.state
+    }
+
+    // Carry on as if the source had `state` coming next.
+    pub(super) fn expect(&mut self, state: State) {
+        self.state = state;
+    }
+
+    // Carry on as if the value or key that was just handed out wasn't there.
+    pub(super) fn again(&mut self) {
+        self.state = self.last;
+    }
+
+    // Number of arrays and objects open, the ones the walk started in too.
+    pub(super) fn depth(&self) -> usize {
+        self.outside + self.nesting.depth
+    }
+
+    // Where the last value or key handed out starts.
+    pub(super) fn start(&self) -> usize {
+        self.start
+    }
+
+    // Whether the innermost array or object is an object, `None` if the
+    // walk isn't in any.
+    pub(super) fn object(&self) -> Option<bool> {
+        self.nesting.object()
+    }
+
+    // What's counted for the innermost array or object, if anything is.
+    pub(super) fn open(&mut self) -> Option<&mut Open> {
+        self.nesting.open()
+    }
+
+    // The innermost array or object is done.
+    pub(super) fn close(&mut self) -> Step {
+        let object = self.nesting.object() == Some(true);
+
+        self.nesting.pop();
+        self.state = State::Next;
+
+        Step::Close(object)
+    }
+}
+
+// A value that's not an array or object, as read by `Parser::read_scalar`.
+pub(super) enum Scalar<'s> {
+    String(&'s str),
+    Number(Number),
+    Decimal(Decimal),
+    Boolean(bool),
+    Null,
+}
+
+impl<'a> Parser<'a> {
+    // Take the next step of the `walk`.
+    pub(super) fn step(&mut self, walk: &mut Walk) -> Result<Step> {
+        let ch = loop {
+            match walk.state {
+                State::Value => break expect_byte_ignore_whitespace!(self),
+
+                State::Element { first } => {
+                    let ch = expect_byte_ignore_whitespace!(self);
+
+                    if ch == b']' && (first || self.options.trailing_commas) {
+                        return Ok(walk.close());
+                    }
+
+                    // Same as for `parse`, only arrays and objects that
+                    // have anything in them count towards the depth limit
+                    if first && walk.depth() > self.options.depth_limit {
+                        return Err(Error::ExceededDepthLimit);
+                    }
+
+                    break ch;
+                },
+
+                State::Key { first } => {
+                    let ch = expect_byte_ignore_whitespace!(self);
+
+                    if ch == b'}' && (first || self.options.trailing_commas) {
+                        return Ok(walk.close());
+                    }
+
+                    if first && walk.depth() > self.options.depth_limit {
+                        return Err(Error::ExceededDepthLimit);
+                    }
+
+                    walk.last = walk.state;
+                    walk.start = self.index - 1;
+                    walk.state = State::Colon;
+
+                    return Ok(Step::Key(ch, match first {
+                        true  => Expected::KeyOrEndOfObject,
+                        false => Expected::Key,
+                    }));
+                },
+
+                State::Colon => {
+                    expect!(self, b':', Expected::Colon);
+
+                    walk.state = State::Value;
+                },
+
+                State::Next => self.end_value(walk)?,
+
+                State::Counted => {
+                    let object = match walk.nesting.object() {
+                        Some(object) => object,
+                        None         => {
+                            if walk.whole {
+                                expect_eof!(self);
+                            }
+
+                            walk.state = State::Done;
+                            return Ok(Step::End);
+                        },
+                    };
+
+                    match expect_byte_ignore_whitespace!(self) {
+                        b','            => walk.state = match object {
+                            true  => State::Key { first: false },
+                            false => State::Element { first: false },
+                        },
+                        b']' if !object => return Ok(walk.close()),
+                        b'}' if object  => return Ok(walk.close()),
+                        _ if object     => return self.unexpected_character(Expected::CommaOrEndOfObject),
+                        _               => return self.unexpected_character(Expected::CommaOrEndOfArray),
+                    }
+                },
+
+                State::Done => return Ok(Step::End),
+            }
+        };
+
+        walk.last = walk.state;
+        walk.start = self.index - 1;
+
+        match ch {
+            b'[' | b'{' => {
+                let object = ch == b'{';
+
+                walk.nesting.push(object);
+                walk.state = match object {
+                    true  => State::Key { first: true },
+                    false => State::Element { first: true },
+                };
+
+                Ok(Step::Open(object))
+            },
+            _ => {
+                walk.state = State::Next;
+
+                Ok(Step::Scalar(ch))
+            },
+        }
+    }
+
+    // The value that was just read is done. Same as for `parse`, that's
+    // when it counts towards the array it's in, and when a key collected
+    // too many times over in an object fails.
+    pub(super) fn end_value(&self, walk: &mut Walk) -> Result<()> {
+        if walk.state != State::Next {
+            return Ok(());
+        }
+
+        let object = walk.nesting.object();
+
+        if let (Some(object), Some(open)) = (object, walk.nesting.open()) {
+            if object {
+                if open.overflow {
+                    return Err(Error::ExceededArrayLengthLimit);
+                }
+            } else {
+                if open.len == self.options.array_length_limit {
+                    return Err(Error::ExceededArrayLengthLimit);
+                }
+
+                open.len += 1;
+            }
+        }
+
+        walk.state = State::Counted;
+        Ok(())
+    }
+
+    // Leave the value the last step started to be read as a whole, by
+    // `Parser::parse_value` or anything else that starts at its first byte.
+    // The walk carries on after it.
+    pub(super) fn hand_over(&mut self, walk: &mut Walk) {
+        self.index -= 1;
+
+        if let State::Element { first: true } | State::Key { first: true } = walk.state {
+            walk.nesting.pop();
+        }
+
+        walk.state = State::Next;
+    }
+
+    // Count an entry of the innermost object towards the limit. Keys that
+    // are already there aren't another entry, only `new` ones count.
+    pub(super) fn count_entry(&self, walk: &mut Walk, new: bool) -> Result<()> {
+        if let (true, Some(open)) = (new, walk.nesting.open()) {
+            open.len += 1;
+
+            if open.len > self.options.object_entries_limit {
+                return Err(Error::ExceededObjectEntriesLimit);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Count the `key` towards the innermost object, for when there's no
+    // object being built to tell whether it's new to it. Keys are only kept
+    // when the options need them, otherwise every key is new. One that's
+    // already there is an error for `DuplicateKeys::Error`, or for
+    // `DuplicateKeys::Collect` once it's been found more times than an array
+    // can be long. The key starts at `pin`.
+    pub(super) fn count_key(&self, walk: &mut Walk, key: &str) -> Result<bool> {
+        let open = match walk.nesting.open() {
+            Some(open) if self.options.keeps_keys() => open,
+            _                                       => return self.count_entry(walk, true).map(|_| true),
+        };
+
+        let found = open.insert(key);
+
+        match self.options.duplicate_keys {
+            _ if found == 1        => {},
+            DuplicateKeys::Error   => return self.duplicate_key(key),
+            DuplicateKeys::Collect => open.overflow |= found > self.options.array_length_limit,
+            _                      => {},
+        }
+
+        self.count_entry(walk, found == 1)?;
+
+        Ok(found == 1)
+    }
+
+    // Read the value starting with `ch` that's not an array or object.
+    pub(super) fn read_scalar<'s>(&mut self, ch: u8) -> Result<Scalar<'s>> {
+        Ok(match ch {
+            b'"' => Scalar::String(expect_string!(self)),
+            b'\'' if self.options.single_quotes => {
+                Scalar::String(self.read_single_quoted_string()?)
+            },
+            _ if self.options.starts_number(ch) => {
+                self.index -= 1;
+
+                match self.read_number_value()? {
+                    JsonValue::Decimal(decimal) => Scalar::Decimal(decimal),
+                    number                      => Scalar::Number(number.as_number().expect("Must be a number")),
+                }
+            },
+            b't' => {
+                expect_sequence!(self, Expected::Literal("true"), b'r', b'u', b'e');
+                Scalar::Boolean(true)
+            },
+            b'f' => {
+                expect_sequence!(self, Expected::Literal("false"), b'a', b'l', b's', b'e');
+                Scalar::Boolean(false)
+            },
+            b'n' => {
+                expect_sequence!(self, Expected::Literal("null"), b'u', b'l', b'l');
+                Scalar::Null
+            },
+            _ => return self.unexpected_character(Expected::Value)
+        })
+    }
+
+    // Skip to the end of the innermost array or object, without checking
+    // anything but the brackets.
+    pub(super) fn skip_open(&mut self, walk: &mut Walk) -> Result<()> {
+        let object = match walk.nesting.object() {
+            Some(object) => object,
+            None         => return Ok(()),
+        };
+        let depth = walk.nesting.depth - 1;
+
+        // Right after it's opened it's not been held to the depth limit yet,
+        // which only lets it through if it's empty
+        if let State::Element { first: true } | State::Key { first: true } = walk.state {
+            if walk.depth() > self.options.depth_limit {
+                if expect_byte_ignore_whitespace!(self) != if object { b'}' } else { b']' } {
+                    return Err(Error::ExceededDepthLimit);
+                }
+
+                self.index -= 1;
+            }
+        }
+
+        while walk.nesting.depth > depth {
+            match expect_byte!(self) {
+                b'"' => self.skip_string(b'"')?,
+                b'\'' if self.options.single_quotes => self.skip_string(b'\'')?,
+                b'/' if self.options.comments => self.skip_comment()?,
+                ch @ b'[' | ch @ b'{' => {
+                    let object = ch == b'{';
+
+                    // An empty one at the limit is skipped whole
+                    if walk.depth() == self.options.depth_limit {
+                        if expect_byte_ignore_whitespace!(self) != if object { b'}' } else { b']' } {
+                            return Err(Error::ExceededDepthLimit);
+                        }
+                        continue;
+                    }
+
+                    walk.nesting.push(object);
+                },
+                ch @ b']' | ch @ b'}' => {
+                    let object = walk.nesting.object() == Some(true);
+
+                    walk.nesting.pop();
+
+                    match (ch, object) {
+                        (b']', true)  => return self.unexpected_character(Expected::CommaOrEndOfObject),
+                        (b'}', false) => return self.unexpected_character(Expected::CommaOrEndOfArray),
+                        _             => {},
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        walk.state = State::Next;
+        Ok(())
+    }
+
+    // Skip a string up to the closing `quote`, the opening one has already
+    // been read.
+    pub(super) fn skip_string(&mut self, quote: u8) -> Result<()> {
+        loop {
+            match expect_byte!(self) {
+                b'\\'             => { expect_byte!(self); },
+                ch if ch == quote => return Ok(()),
+                _                 => {},
+            }
+        }
+    }
+}
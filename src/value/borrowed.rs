@@ -0,0 +1,159 @@
+// A version of `JsonValue` that borrows its strings from the source it was
+// parsed from, see `json::parse_borrowed`. Objects are plain vectors of
+// entries here, there is nothing to own the keys that a hash map would need.
+
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::ops::Index;
+
+use crate::number::{ Number, Decimal };
+use crate::object::Object;
+use crate::JsonValue;
+
+static NULL: JsonValueRef<'static> = JsonValueRef::Null;
+
+/// A parsed document that borrows strings from its source wherever it can.
+/// Only strings with escapes in them have to be decoded, and with that
+/// allocated, everything else points straight into the source.
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use json::JsonValueRef;
+///
+/// let source = r#"{"name":"zażółć","quote":"\"hi\""}"#;
+/// let data = json::parse_borrowed(source).unwrap();
+///
+/// assert_eq!(data["name"].as_str(), Some("zażółć"));
+/// assert_eq!(data["quote"].as_str(), Some("\"hi\""));
+///
+/// assert!(matches!(data["name"], JsonValueRef::String(Cow::Borrowed(_))));
+/// assert!(matches!(data["quote"], JsonValueRef::String(Cow::Owned(_))));
+///
+/// assert_eq!(data.into_owned(), json::parse(source).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValueRef<'a> {
+    Null,
+    String(Cow<'a, str>),
+    Number(Number),
+    Decimal(Decimal),
+    Boolean(bool),
+
+    /// Entries in the order their keys first appeared in. Keys are unique,
+    /// duplicates are dealt with the same way they are for `JsonValue`.
+    Object(Vec<(Cow<'a, str>, JsonValueRef<'a>)>),
+
+    Array(Vec<JsonValueRef<'a>>),
+}
+
+impl<'a> JsonValueRef<'a> {
+    pub fn is_null(&self) -> bool {
+        matches!(*self, JsonValueRef::Null)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            JsonValueRef::String(ref value) => Some(value),
+            _                               => None,
+        }
+    }
+
+    /// Get the number, `Decimal` values are rounded to the closest `Number`.
+    pub fn as_number(&self) -> Option<Number> {
+        match *self {
+            JsonValueRef::Number(value)      => Some(value),
+            JsonValueRef::Decimal(ref value) => Some(value.to_number()),
+            _                                => None,
+        }
+    }
+
+    pub fn as_decimal(&self) -> Option<&Decimal> {
+        match *self {
+            JsonValueRef::Decimal(ref value) => Some(value),
+            _                                => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(Into::into)
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(|value| value.try_into().ok())
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(|value| value.try_into().ok())
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            JsonValueRef::Boolean(value) => Some(value),
+            _                            => None,
+        }
+    }
+
+    /// Get the value of the entry with the `key`, if this is an object that
+    /// has it.
+    pub fn get(&self, key: &str) -> Option<&JsonValueRef<'a>> {
+        match *self {
+            JsonValueRef::Object(ref entries) => {
+                entries.iter().find(|entry| entry.0 == key).map(|entry| &entry.1)
+            },
+            _ => None,
+        }
+    }
+
+    /// Copy everything that is borrowed, for a value that can outlive the
+    /// source.
+    pub fn into_owned(self) -> JsonValue {
+        match self {
+            JsonValueRef::Null            => JsonValue::Null,
+            JsonValueRef::String(value)   => value.as_ref().into(),
+            JsonValueRef::Number(value)   => JsonValue::Number(value),
+            JsonValueRef::Decimal(value)  => JsonValue::Decimal(value),
+            JsonValueRef::Boolean(value)  => JsonValue::Boolean(value),
+            JsonValueRef::Array(array)    => {
+                JsonValue::Array(array.into_iter().map(JsonValueRef::into_owned).collect())
+            },
+            JsonValueRef::Object(entries) => {
+                let mut object = Object::with_capacity(entries.len());
+
+                for (key, value) in entries {
+                    object.insert(&key, value.into_owned());
+                }
+
+                JsonValue::Object(object)
+            },
+        }
+    }
+}
+
+impl<'a> From<JsonValueRef<'a>> for JsonValue {
+    fn from(value: JsonValueRef<'a>) -> JsonValue {
+        value.into_owned()
+    }
+}
+
+/// Elements of arrays can be read by their index, anything out of bounds
+/// is `Null`.
+impl<'a> Index<usize> for JsonValueRef<'a> {
+    type Output = JsonValueRef<'a>;
+
+    fn index(&self, index: usize) -> &JsonValueRef<'a> {
+        match *self {
+            JsonValueRef::Array(ref array) => array.get(index).unwrap_or(&NULL),
+            _                              => &NULL,
+        }
+    }
+}
+
+/// Values of object entries can be read by their key, anything missing is
+/// `Null`.
+impl<'a> Index<&str> for JsonValueRef<'a> {
+    type Output = JsonValueRef<'a>;
+
+    fn index(&self, key: &str) -> &JsonValueRef<'a> {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
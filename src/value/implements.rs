@@ -4,7 +4,7 @@
 use std::collections::{BTreeMap, HashMap};
 
 use crate::short::{self, Short};
-use crate::number::Number;
+use crate::number::{ Number, Decimal };
 use crate::object::Object;
 use crate::value::JsonValue;
 
@@ -39,6 +39,33 @@ macro_rules! implement_eq {
     }
 }
 
+// Same as `implement_eq`, except `Decimal` values are compared too.
+macro_rules! implement_eq_num {
+    ($from:ty) => {
+        impl PartialEq<$from> for JsonValue {
+            fn eq(&self, other: &$from) -> bool {
+                match *self {
+                    JsonValue::Number(ref value)  => value == other,
+                    JsonValue::Decimal(ref value) => *value == Number::from(*other),
+                    _                             => false
+                }
+            }
+        }
+
+        impl<'a> PartialEq<$from> for &'a JsonValue {
+            fn eq(&self, other: &$from) -> bool {
+                (**self).eq(other)
+            }
+        }
+
+        impl PartialEq<JsonValue> for $from {
+            fn eq(&self, other: &JsonValue) -> bool {
+                other.eq(self)
+            }
+        }
+    }
+}
+
 macro_rules! implement {
     ($to:ident, $from:ty as num) => {
         impl From<$from> for JsonValue {
@@ -47,7 +74,7 @@ macro_rules! implement {
             }
         }
 
-        implement_eq!($to, $from);
+        implement_eq_num!($from);
     };
     ($to:ident, $from:ty) => {
         impl From<$from> for JsonValue {
@@ -156,6 +183,7 @@ implement!(Number, u32 as num);
 implement!(Number, u64 as num);
 implement!(Number, f32 as num);
 implement!(Number, f64 as num);
-implement!(Number, Number);
+implement!(Number, Number as num);
+implement!(Decimal, Decimal);
 implement!(Object, Object);
 implement!(Boolean, bool);
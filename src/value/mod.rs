@@ -5,12 +5,15 @@ use std::io::{self, Write};
 
 use crate::{Result, Error};
 use crate::short::Short;
-use crate::number::Number;
+use crate::number::{ Number, Decimal };
 use crate::object::Object;
 use crate::iterators::{ Members, MembersMut, Entries, EntriesMut };
 use crate::codegen::{ Generator, PrettyGenerator, DumpGenerator, WriterGenerator, PrettyWriterGenerator };
 
 mod implements;
+mod borrowed;
+
+pub use self::borrowed::JsonValueRef;
 
 // These are convenience macros for converting `f64` to the `$unsigned` type.
 // The macros check that the numbers are representable the target type.
@@ -40,6 +43,7 @@ pub enum JsonValue {
     Short(Short),
     String(String),
     Number(Number),
+    Decimal(Decimal),
     Boolean(bool),
     Object(Object),
     Array(Vec<JsonValue>),
@@ -55,6 +59,9 @@ impl PartialEq for JsonValue {
             (&Short(ref a), &String(ref b))
             | (&String(ref b), &Short(ref a)) => a.as_str() == b.as_str(),
             (&Number(ref a), &Number(ref b)) => a == b,
+            (&Decimal(ref a), &Decimal(ref b)) => a == b,
+            (&Decimal(ref a), &Number(ref b))
+            | (&Number(ref b), &Decimal(ref a)) => a == b,
             (&Boolean(ref a), &Boolean(ref b)) => a == b,
             (&Object(ref a), &Object(ref b)) => a == b,
             (&Array(ref a), &Array(ref b)) => a == b,
@@ -82,6 +89,7 @@ impl fmt::Display for JsonValue {
                 JsonValue::Short(ref value)   => value.fmt(f),
                 JsonValue::String(ref value)  => value.fmt(f),
                 JsonValue::Number(ref value)  => value.fmt(f),
+                JsonValue::Decimal(ref value) => value.fmt(f),
                 JsonValue::Boolean(ref value) => value.fmt(f),
                 JsonValue::Null               => f.write_str("null"),
                 _                             => f.write_str(&self.dump())
@@ -152,8 +160,9 @@ impl JsonValue {
 
     pub fn is_number(&self) -> bool {
         match *self {
-            JsonValue::Number(_) => true,
-            _                    => false,
+            JsonValue::Number(_)  => true,
+            JsonValue::Decimal(_) => true,
+            _                     => false,
         }
     }
 
@@ -199,6 +208,7 @@ impl JsonValue {
             JsonValue::Short(ref value)   => value.is_empty(),
             JsonValue::String(ref value)  => value.is_empty(),
             JsonValue::Number(ref value)  => value.is_empty(),
+            JsonValue::Decimal(ref value) => value.to_number().is_empty(),
             JsonValue::Boolean(ref value) => !value,
             JsonValue::Array(ref value)   => value.is_empty(),
             JsonValue::Object(ref value)  => value.is_empty(),
@@ -213,10 +223,21 @@ impl JsonValue {
         }
     }
 
+    /// Get the number, `Decimal` values are rounded to the closest `Number`.
     pub fn as_number(&self) -> Option<Number> {
         match *self {
-            JsonValue::Number(value) => Some(value),
-            _                        => None
+            JsonValue::Number(value)      => Some(value),
+            JsonValue::Decimal(ref value) => Some(value.to_number()),
+            _                             => None
+        }
+    }
+
+    /// Get the number with all of its digits, only available when parsed
+    /// with `lossless_numbers` set on `ParserOptions`.
+    pub fn as_decimal(&self) -> Option<&Decimal> {
+        match *self {
+            JsonValue::Decimal(ref value) => Some(value),
+            _                             => None
         }
     }
 
@@ -296,8 +317,9 @@ impl JsonValue {
     /// ```
     pub fn as_fixed_point_u64(&self, point: u16) -> Option<u64> {
         match *self {
-            JsonValue::Number(ref value) => value.as_fixed_point_u64(point),
-            _                            => None
+            JsonValue::Number(ref value)  => value.as_fixed_point_u64(point),
+            JsonValue::Decimal(ref value) => value.to_number().as_fixed_point_u64(point),
+            _                             => None
         }
     }
 
@@ -314,8 +336,9 @@ impl JsonValue {
     /// ```
     pub fn as_fixed_point_i64(&self, point: u16) -> Option<i64> {
         match *self {
-            JsonValue::Number(ref value) => value.as_fixed_point_i64(point),
-            _                            => None
+            JsonValue::Number(ref value)  => value.as_fixed_point_i64(point),
+            JsonValue::Decimal(ref value) => value.to_number().as_fixed_point_i64(point),
+            _                             => None
         }
     }
 
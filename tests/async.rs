@@ -0,0 +1,130 @@
+#![cfg(feature = "async")]
+
+extern crate json;
+
+use std::io;
+use std::pin::Pin;
+use std::task::{ Context, Poll };
+
+use futures_executor::block_on;
+use futures_io::AsyncRead;
+use json::{ parse_async, parse_async_with, parse_reader, parse_reader_with, Error, ParserOptions };
+
+// Reader that hands out a few bytes at a time, and makes the task wait
+// before every one of them, like a socket would.
+struct Trickle<'a> {
+    source: &'a [u8],
+    step: usize,
+    ready: bool,
+}
+
+impl<'a> AsyncRead for Trickle<'a> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        let len = self.step.min(buf.len()).min(self.source.len());
+
+        buf[..len].copy_from_slice(&self.source[..len]);
+        self.source = &self.source[len..];
+        self.ready = false;
+
+        Poll::Ready(Ok(len))
+    }
+}
+
+fn trickle(source: &[u8], step: usize) -> Trickle<'_> {
+    Trickle { source, step, ready: false }
+}
+
+// Reader that fails once it's been read from a few times.
+struct Broken(usize);
+
+impl AsyncRead for Broken {
+    fn poll_read(mut self: Pin<&mut Self>, _: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        if self.0 == 0 {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::ConnectionReset, "gone")));
+        }
+        self.0 -= 1;
+        buf[0] = b'[';
+        Poll::Ready(Ok(1))
+    }
+}
+
+const SOURCES: &[&str] = &[
+    "null",
+    "  true  ",
+    "-12.5e-3",
+    "18446744073709551616",
+    r#""escaped \"string\" with \\ \/ \b \f \n \r \t \u0041 \uD834\uDD1E""#,
+    "\"zażółć\\ngęślą jaźń 🦄\"",
+    r#"{"a":1,"b":[true,false,null],"c":{"d":"e","f":[{}, []]}}"#,
+    "[\n  1,\n  \"two\",\n  { \"three\": 3.0 }\n]\n",
+    "",
+    "   ",
+    "[1] x",
+    "[1] [2]",
+    "1 2",
+    "[1, 🦄]",
+    "{\"foo\" 1}",
+    "[\"abc",
+    "[\"\\uD834\"]",
+    "[\n\"zaz\u{f3}\", tru]",
+];
+
+#[test]
+fn parse_async_matches_parse_reader() {
+    for source in SOURCES {
+        let expected = parse_reader(source.as_bytes());
+
+        assert_eq!(block_on(parse_async(source.as_bytes())), expected, "{}", source);
+
+        for step in 1..4 {
+            assert_eq!(block_on(parse_async(trickle(source.as_bytes(), step))), expected, "{}", source);
+        }
+    }
+
+    let long = format!("[\"{}\", {}]", "a".repeat(20000), "1, ".repeat(5000));
+
+    assert_eq!(block_on(parse_async(trickle(long.as_bytes(), 7000))), parse_reader(long.as_bytes()));
+
+    let invalid: &[u8] = b"[\"fo\xFF\"]";
+
+    assert_eq!(block_on(parse_async(invalid)), parse_reader(invalid));
+}
+
+#[test]
+fn parse_async_with_options() {
+    let options = ParserOptions::json5();
+    let sources = &[
+        "// comment\n{ a: 'single', 'b': [+1, 0x10, .5,], c: [ 'x' ], } /* done */",
+        "[1] // done",
+        "[1] /* done",
+        "[1] / x",
+    ];
+
+    for source in sources {
+        let expected = parse_reader_with(source.as_bytes(), &options);
+
+        for step in 1..4 {
+            assert_eq!(block_on(parse_async_with(trickle(source.as_bytes(), step), &options)), expected, "{}", source);
+        }
+    }
+
+    let options = ParserOptions::new().size_limit(10);
+
+    assert_eq!(block_on(parse_async_with(trickle(b"[1, 2, 3, 4, 5]", 4), &options)), Err(Error::ExceededSizeLimit));
+    assert_eq!(block_on(parse_async_with(&b"[1, 2, 3]   "[..], &options)), Err(Error::ExceededSizeLimit));
+    assert_eq!(block_on(parse_async_with(&b"[1, 2, 3]"[..], &options)), parse_reader_with(&b"[1, 2, 3]"[..], &options));
+}
+
+#[test]
+fn parse_async_io_error() {
+    match block_on(parse_async(Broken(3))) {
+        Err(Error::Io { kind, .. }) => assert_eq!(kind, io::ErrorKind::ConnectionReset),
+        other => panic!("Expected an I/O error, got {:?}", other),
+    }
+}
@@ -0,0 +1,80 @@
+extern crate json;
+
+mod common;
+
+use std::borrow::Cow;
+use json::{ parse_borrowed, parse_borrowed_with, parse_with, Error, JsonValueRef, ParserOptions };
+
+fn borrowed_with(source: &str, options: &ParserOptions) -> Result<json::JsonValue, Error> {
+    parse_borrowed_with(source, options).map(JsonValueRef::into_owned)
+}
+
+fn is_borrowed(value: &JsonValueRef) -> bool {
+    matches!(*value, JsonValueRef::String(Cow::Borrowed(_)))
+}
+
+#[test]
+fn borrowed_matches_parse() {
+    common::matches_parse(&common::all_options(), borrowed_with, |value| value);
+}
+
+#[test]
+fn borrowed_strings() {
+    let source = r#"{"plain": "text", "esc\naped": ["a\tb", "", "zażółć"]}"#;
+    let data = parse_borrowed(source).unwrap();
+
+    assert!(is_borrowed(&data["plain"]));
+    assert!(!is_borrowed(&data["esc\naped"][0]));
+    assert!(is_borrowed(&data["esc\naped"][1]));
+    assert!(is_borrowed(&data["esc\naped"][2]));
+
+    match data {
+        JsonValueRef::Object(ref entries) => {
+            assert!(matches!(entries[0].0, Cow::Borrowed("plain")));
+            assert!(matches!(entries[1].0, Cow::Owned(_)));
+        },
+        _ => panic!("Expected an object"),
+    }
+
+    assert_eq!(data["plain"].as_str(), Some("text"));
+    assert_eq!(data["esc\naped"][0].as_str(), Some("a\tb"));
+    assert_eq!(data["esc\naped"][3], JsonValueRef::Null);
+    assert_eq!(data["missing"], JsonValueRef::Null);
+    assert_eq!(data.get("missing"), None);
+}
+
+#[test]
+fn borrowed_accessors() {
+    let data = parse_borrowed(r#"{"n": 42, "f": -0.5, "b": true, "z": null}"#).unwrap();
+
+    assert_eq!(data["n"].as_u64(), Some(42));
+    assert_eq!(data["n"].as_i64(), Some(42));
+    assert_eq!(data["f"].as_f64(), Some(-0.5));
+    assert_eq!(data["f"].as_u64(), None);
+    assert_eq!(data["b"].as_bool(), Some(true));
+    assert!(data["z"].is_null());
+    assert_eq!(data["b"].as_str(), None);
+}
+
+#[test]
+fn borrowed_duplicate_keys() {
+    let data = parse_borrowed(r#"{"a": 1, "b": 2, "a": 3}"#).unwrap();
+
+    assert_eq!(data, JsonValueRef::Object(vec![
+        ("a".into(), JsonValueRef::Number(3.into())),
+        ("b".into(), JsonValueRef::Number(2.into())),
+    ]));
+}
+
+#[test]
+fn borrowed_with_options() {
+    let options = ParserOptions::json5().lossless_numbers(true);
+    let source = "// comment\n{ a: 'single', 'b': [+1, 0x10, 0.10,], c: 18446744073709551616 }";
+
+    assert_eq!(borrowed_with(source, &options), parse_with(source, &options));
+
+    match parse_borrowed_with(source, &options) {
+        Ok(JsonValueRef::Object(entries)) => assert!(matches!(entries[0].0, Cow::Borrowed("a"))),
+        other => panic!("Expected an object, got {:?}", other),
+    }
+}
@@ -0,0 +1,157 @@
+// Sources and options that every way of parsing a document is held up
+// against `parse_with`. Whatever else an entry point does, with the same
+// options it has to come up with the same value, or fail with the same
+// error, as parsing the whole thing into a `JsonValue` would.
+//
+// Not every test uses all of it.
+#![allow(dead_code)]
+
+use std::fmt::Debug;
+use json::{ parse_with, DuplicateKeys, Error, JsonValue, ParserOptions };
+
+const SOURCES: &[&str] = &[
+    "null",
+    "  true  ",
+    "-12.5e-3",
+    "18446744073709551616",
+    "0.1000000000000000000001",
+    r#""escaped \"string\" with \\ \/ \b \f \n \r \t \u0041 \uD834\uDD1E 𝄞""#,
+    "\"zażółć gęślą jaźń 🦄\"",
+    r#"{"a":1,"b":[true,false,null],"c":{"d":"e","f":[{}, []]}}"#,
+    r#"{"A\\":-0.5,"a\"b":[1e10, 20E-2, 3.0e+1],"":{}, "x]": "[{"}"#,
+    "[[[1, [2, 3]], 4], [], [[5], {\"x\": [6, {\"y\": [7]}]}], 8]",
+    "[\n  1,\n  \"two\",\n  { \"three\": 3.0 }\n]\n",
+
+    // Right at the limits, and just past them
+    "[[1, 2]]",
+    "[[[1]]]",
+    "[1, 2, 3]",
+    "{\"a\":1, \"b\":2}",
+    "[\"abc\"]",
+    "[\"abcd\"]",
+    "[\"a\\u0041\\nb\"]",
+    "[\"a\\u0041\\nb",
+    "[1, 2]                 ",
+    "[1E+2,123456789012345678901234567890,\"🦄zzzz\"]",
+    "[1, 2, \"\\uD834\"]",
+    "[1, 2, [[[3]]]]",
+
+    // Empty containers are fine at the depth limit
+    "[]",
+    "[[]]",
+    "[[ ], {}]",
+    "[[[ ]]]",
+    r#"{"a":[]}"#,
+    r#"{"a": { }, "b": 1}"#,
+    r#"[{"a": {}}]"#,
+
+    // Keys that are already there, which aren't another entry
+    r#"{"a":1,"a":2}"#,
+    r#"{"a":1,"a":2,"b":3}"#,
+    r#"[{"a":{"b":1,"b":2},"a":3}]"#,
+    r#"{"a": 1, "b": {"c": 2, "c": [3]}, "a": {"x": 4}, "a": 5}"#,
+    r#"{"a":1,"a":2,"a":3}"#,
+    r#"[{"a":[1],"b":2,"a":{},"a":[2, 3, 4]}]"#,
+
+    // Broken ones
+    "",
+    "[1, 🦄]",
+    "{\"foo\" 1}",
+    "{\"foo\": 1,}",
+    "{\"foo\": 1]",
+    "{1: 2}",
+    "[1}",
+    "[\"abc",
+    "[1, 2",
+    "[[\n{",
+    "{\"a\": 1} x",
+    "[01]",
+    "[1.]",
+    "[-]",
+    "[tru]",
+    "[\"a\\x\"]",
+    "[\"\\uD834\"]",
+    "[\"\\uD834\\u0041\"]",
+    "[\"\\u12G4\"]",
+    "[\"tab\tinside\"]",
+
+    // Only with the relaxed syntax
+    "// comment\n{ a: 'single ]', 'b': [+1, 0x10, .5,], /* } */ c: [ 'x' ], } // after",
+    "[ // zażółć\n 1, /* 🦄 */ 2 ] // end",
+    "{ 'a\\q': 1 }",
+    "[1, /* unterminated ]",
+];
+
+/// Everything in `SOURCES`, along with an object that has enough keys to
+/// be indexed, with keys repeated on both sides of that.
+pub fn sources() -> Vec<String> {
+    let keys: Vec<String> = (0..40).map(|n| format!("\"k{}\": {}", n % 25, n)).collect();
+    let indexed = format!("{{{}, \"nested\": {{\"a\": 1, \"a\": [2]}}}}", keys.join(", "));
+
+    SOURCES.iter().map(|&source| source.to_owned()).chain(Some(indexed)).collect()
+}
+
+/// Options that make a difference to every way of parsing: the syntax and
+/// the limits.
+pub fn options() -> Vec<ParserOptions> {
+    vec![
+        ParserOptions::new(),
+        ParserOptions::json5(),
+        ParserOptions::new()
+            .depth_limit(2)
+            .array_length_limit(2)
+            .object_entries_limit(1)
+            .string_length_limit(3)
+            .size_limit(20),
+        ParserOptions::new().depth_limit(1),
+        ParserOptions::new().depth_limit(0),
+        ParserOptions::json5().depth_limit(1).object_entries_limit(1),
+    ]
+}
+
+/// Options that only make a difference to what's built: what to do about
+/// duplicate keys, with and without a limit on entries or on the arrays
+/// values are collected into, and numbers kept whole.
+pub fn policies() -> Vec<ParserOptions> {
+    let mut options = vec![
+        ParserOptions::new().lossless_numbers(true),
+        ParserOptions::json5().lossless_numbers(true),
+    ];
+
+    for &policy in &[DuplicateKeys::KeepLast, DuplicateKeys::KeepFirst, DuplicateKeys::Collect, DuplicateKeys::Error] {
+        options.push(ParserOptions::new().duplicate_keys(policy));
+        options.push(ParserOptions::new().duplicate_keys(policy).object_entries_limit(1));
+        options.push(ParserOptions::new().duplicate_keys(policy).object_entries_limit(2).depth_limit(1));
+        options.push(ParserOptions::new().duplicate_keys(policy).array_length_limit(2));
+    }
+
+    options
+}
+
+/// Both of the above.
+pub fn all_options() -> Vec<ParserOptions> {
+    let mut options = options();
+
+    options.extend(policies());
+    options
+}
+
+/// Run `parse` over every source with every one of `options`, and compare
+/// what it comes up with to `parse_with`, its value turned into whatever
+/// `parse` returns with `expected`.
+pub fn matches_parse<T, F, G>(options: &[ParserOptions], mut parse: F, expected: G)
+where
+    T: PartialEq + Debug,
+    F: FnMut(&str, &ParserOptions) -> Result<T, Error>,
+    G: Fn(JsonValue) -> T,
+{
+    for options in options {
+        for source in sources() {
+            assert_eq!(
+                parse(&source, options),
+                parse_with(&source, options).map(&expected),
+                "{:?} with {:?}", source, options,
+            );
+        }
+    }
+}
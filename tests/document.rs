@@ -0,0 +1,97 @@
+extern crate json;
+
+mod common;
+
+use json::{ parse_document, parse_document_with, parse_with, Error, ParserOptions };
+
+fn document_with(source: &str, options: &ParserOptions) -> Result<json::JsonValue, Error> {
+    parse_document_with(source, options).map(|document| document.to_json_value())
+}
+
+#[test]
+fn document_matches_parse() {
+    common::matches_parse(&common::all_options(), document_with, |value| value);
+}
+
+#[test]
+fn document_navigation() {
+    let document = parse_document(r#"
+        {
+            "name": "zażółć",
+            "tags": ["a", "b\tc", ""],
+            "nested": { "n": 42, "f": -0.5, "b": true, "z": null },
+            "empty": []
+        }
+    "#).unwrap();
+
+    assert!(document.is_object());
+    assert_eq!(document.len(), 4);
+    assert!(document.has_key("tags"));
+    assert!(!document.has_key("missing"));
+
+    assert_eq!(document["name"].as_str(), Some("zażółć"));
+    assert_eq!(document["tags"][1].as_str(), Some("b\tc"));
+    assert!(document["tags"][2].is_empty());
+    assert!(document["tags"][3].is_null());
+    assert!(document["missing"]["deeper"][0].is_null());
+
+    let nested = &document["nested"];
+
+    assert_eq!(nested["n"].as_u64(), Some(42));
+    assert_eq!(nested["n"].as_u8(), Some(42));
+    assert_eq!(nested["f"].as_f64(), Some(-0.5));
+    assert_eq!(nested["f"].as_i64(), None);
+    assert_eq!(nested["b"].as_bool(), Some(true));
+    assert!(nested["z"].is_null());
+
+    let tags: Vec<_> = document["tags"].members().map(|tag| tag.as_str().unwrap()).collect();
+
+    assert_eq!(tags, ["a", "b\tc", ""]);
+
+    let keys: Vec<_> = document.entries().map(|(key, _)| key).collect();
+
+    assert_eq!(keys, ["name", "tags", "nested", "empty"]);
+    assert_eq!(nested.entries().next_back().map(|(key, _)| key), Some("z"));
+
+    assert!(document["empty"].is_array());
+    assert!(document["empty"].is_empty());
+    assert_eq!(document["empty"].members().count(), 0);
+    assert_eq!(document["name"].entries().count(), 0);
+
+    assert_eq!(format!("{:?}", nested), r#"{"n": 42, "f": -0.5, "b": true, "z": null}"#);
+}
+
+#[test]
+fn document_duplicate_keys() {
+    let document = parse_document(r#"{"a": 1, "b": 2, "a": [3, 4]}"#).unwrap();
+
+    assert_eq!(document.len(), 2);
+    assert_eq!(document["a"][1].as_u64(), Some(4));
+}
+
+#[test]
+fn document_with_options() {
+    let options = ParserOptions::json5().lossless_numbers(true);
+    let source = "// comment\n{ a: 'single', 'b': [+1, 0x10, 0.10,], c: 18446744073709551616 }";
+
+    assert_eq!(document_with(source, &options), parse_with(source, &options));
+
+    let document = parse_document_with(source, &options).unwrap();
+
+    assert_eq!(document["c"].as_decimal().unwrap().as_str(), "18446744073709551616");
+    assert_eq!(document["c"].as_f64(), Some(18446744073709551616.0));
+    assert_eq!(document["a"].as_decimal(), None);
+}
+
+#[test]
+fn document_is_send_and_sync() {
+    fn check<T: Send + Sync>(_: &T) {}
+
+    let document = parse_document("[1, \"two\"]").unwrap();
+
+    check(&document);
+
+    let handle = std::thread::spawn(move || document[1].as_str().map(str::to_owned));
+
+    assert_eq!(handle.join().unwrap(), Some("two".into()));
+}
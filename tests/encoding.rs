@@ -0,0 +1,107 @@
+extern crate json;
+
+use json::{ parse, parse_bytes_detect, parse_bytes_detect_with, Encoding, Error, Expected, ParserOptions };
+
+fn encode(text: &str, encoding: Encoding) -> Vec<u8> {
+    match encoding {
+        Encoding::Utf8    => text.as_bytes().to_vec(),
+        Encoding::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        Encoding::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        Encoding::Utf32Le => text.chars().flat_map(|ch| (ch as u32).to_le_bytes()).collect(),
+        Encoding::Utf32Be => text.chars().flat_map(|ch| (ch as u32).to_be_bytes()).collect(),
+    }
+}
+
+const ENCODINGS: &[Encoding] = &[
+    Encoding::Utf8,
+    Encoding::Utf16Le,
+    Encoding::Utf16Be,
+    Encoding::Utf32Le,
+    Encoding::Utf32Be,
+];
+
+#[test]
+fn detect_encodings() {
+    let sources = &[
+        "{\"name\": \"zażółć\", \"emoji\": \"😀\", \"list\": [1, 2.5, null]}",
+        "[\"\\uD834\\uDD1E\"]",
+        "\"x\"",
+        "1",
+        "12",
+        "[]",
+    ];
+
+    for &encoding in ENCODINGS {
+        for source in sources {
+            let expected = parse(source);
+
+            assert_eq!(Encoding::detect(&encode(source, encoding)).0, encoding, "{} {}", encoding, source);
+            assert_eq!(parse_bytes_detect(&encode(source, encoding)), expected, "{} {}", encoding, source);
+
+            let marked = encode(&format!("\u{FEFF}{}", source), encoding);
+
+            assert_eq!(Encoding::detect(&marked).0, encoding);
+            assert_eq!(parse_bytes_detect(&marked), expected, "{} with BOM {}", encoding, source);
+        }
+    }
+
+    assert_eq!(parse_bytes_detect(b""), Err(Error::UnexpectedEndOfJson));
+    assert_eq!(parse_bytes_detect(b"\xEF\xBB\xBF"), Err(Error::UnexpectedEndOfJson));
+    assert_eq!(parse_bytes_detect(b"\xFF\xFE"), Err(Error::UnexpectedEndOfJson));
+}
+
+#[test]
+fn detect_errors_point_into_source() {
+    let source = "\u{FEFF}[\"😀\", x]";
+
+    for &encoding in ENCODINGS {
+        let unit = encode("x", encoding).len();
+        let offset = match encoding {
+            Encoding::Utf8 => 3 + "[\"😀\", ".len(),
+            _              => encode("\u{FEFF}[\"😀\", ", encoding).len(),
+        };
+
+        assert_eq!(parse_bytes_detect(&encode(source, encoding)), Err(Error::UnexpectedCharacter {
+            ch: 'x',
+            line: 1,
+            column: 7,
+            offset,
+            expected: Expected::Value,
+        }), "{}", encoding);
+
+        // Half a character at the end
+        if unit > 1 {
+            let mut truncated = encode("[1]", encoding);
+
+            truncated.pop();
+
+            assert_eq!(parse_bytes_detect(&truncated), Err(Error::InvalidEncoding {
+                encoding,
+                offset: unit * 2,
+            }));
+        }
+    }
+
+    // Unpaired surrogates, and what isn't a character at all
+    assert_eq!(parse_bytes_detect(b"\x00[\x00\"\xDC\x00\x00\"\x00]"), Err(Error::InvalidEncoding {
+        encoding: Encoding::Utf16Be,
+        offset: 4,
+    }));
+    assert_eq!(parse_bytes_detect(b"[\x00\x00\x00\x00\x00\x11\x00]\x00\x00\x00"), Err(Error::InvalidEncoding {
+        encoding: Encoding::Utf32Le,
+        offset: 4,
+    }));
+    assert_eq!(parse_bytes_detect(b"\xEF\xBB\xBF[\"\xFF\"]"), Err(Error::InvalidUtf8 {
+        offset: 5,
+        line: 1,
+        column: 3,
+    }));
+
+    let error = parse_bytes_detect(b"\x00[\xD8\x00").unwrap_err();
+
+    assert_eq!(error.to_string(), "Invalid UTF-16BE at offset 2");
+
+    let options = ParserOptions::new().depth_limit(1);
+
+    assert_eq!(parse_bytes_detect_with(&encode("[[1]]", Encoding::Utf16Le), &options), Err(Error::ExceededDepthLimit));
+}
@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate json;
+
+mod common;
+
+use json::{ extract, extract_with, parse_with, DuplicateKeys, Error, JsonValue, ParserOptions };
+
+// Follow a pointer through a value that's been parsed in full
+fn follow(value: &JsonValue, pointer: &str) -> Option<JsonValue> {
+    if pointer.is_empty() {
+        return Some(value.clone());
+    }
+
+    let mut value = value;
+
+    for token in pointer.strip_prefix('/')?.split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+
+        value = match *value {
+            JsonValue::Object(ref object) => object.get(&token)?,
+            JsonValue::Array(ref array) if token == "0" || !token.starts_with('0') => {
+                array.get(token.parse::<usize>().ok()?)?
+            },
+            _ => return None,
+        };
+    }
+
+    Some(value.clone())
+}
+
+fn parse_and_follow(source: &str, pointers: &[&str], options: &ParserOptions) -> Result<Vec<Option<JsonValue>>, Error> {
+    parse_with(source, options).map(|value| {
+        pointers.iter().map(|pointer| follow(&value, pointer)).collect()
+    })
+}
+
+#[test]
+fn extract_matches_parse() {
+    let source = r#"{
+        "user": { "id": 42, "name": "Ann", "a/b": { "~": true } },
+        "items": [{ "price": 9.99 }, { "price": -1, "tags": [] }, [[1, 2], 3], "]}"],
+        "empty": {},
+        "none": null,
+        "": { "": 0 }
+    }"#;
+    let pointers = &[
+        "", "/user", "/user/id", "/user/name", "/user/a~1b/~0", "/items/0/price",
+        "/items/1", "/items/2/0/1", "/items/3", "/items/4", "/items/01", "/items/-",
+        "/user/id/0", "/none/x", "/empty", "/empty/x", "/", "//", "/missing", "user",
+    ];
+
+    for options in &common::all_options() {
+        assert_eq!(extract_with(source, pointers, options), parse_and_follow(source, pointers, options));
+    }
+
+    // All of them at once, and each on its own, since what's skipped
+    // depends on where the pointers go
+    let pointers = &[
+        "", "/a", "/a/0", "/a/x", "/b", "/b/c", "/b/c/1", "/0", "/0/0", "/1",
+        "/0/a/b", "/c/f/0", "/k3", "/nested/a", "/x]", "/missing",
+    ];
+
+    common::matches_parse(&common::all_options(), |source, options| extract_with(source, pointers, options), |value| {
+        pointers.iter().map(|pointer| follow(&value, pointer)).collect()
+    });
+
+    for pointer in pointers {
+        common::matches_parse(&common::all_options(), |source, options| extract_with(source, &[pointer], options), |value| {
+            vec![follow(&value, pointer)]
+        });
+    }
+
+    // Keys that are already there don't count towards the limit
+    for &policy in &[DuplicateKeys::KeepLast, DuplicateKeys::KeepFirst, DuplicateKeys::Collect, DuplicateKeys::Error] {
+        let options = ParserOptions::new().duplicate_keys(policy).object_entries_limit(1);
+
+        for source in &[r#"{"a":1,"a":2}"#, r#"{"a":1,"a":2,"b":3}"#, r#"{"b":{"a":1,"a":2},"b":3}"#] {
+            for pointers in &[&["/a"][..], &["/b"], &["/b/a"], &["/c"]] {
+                assert_eq!(extract_with(source, pointers, &options), parse_and_follow(source, pointers, &options), "{} {:?}", source, policy);
+            }
+        }
+    }
+
+    assert_eq!(extract(source, &["/user/id", "/items/2/1"]), Ok(vec![Some(42.into()), Some(3.into())]));
+    assert_eq!(extract(source, &[]), Ok(vec![]));
+    assert_eq!(extract("[1, [2, 3]]", &["/1/1", "/1/1", "/0"]), Ok(vec![Some(3.into()), Some(3.into()), Some(1.into())]));
+}
+
+#[test]
+fn extract_checks_everything() {
+    let sources = &[
+        "",
+        "[1, 🦄]",
+        "{\"foo\" 1}",
+        "{\"foo\": 1,}",
+        "[1}",
+        "[\"abc",
+        "{\"a\": 1} x",
+        "{\"a\": {\"skipped\": [1, 2,]}, \"b\": 1}",
+        "{\"b\": 1, \"a\": {\"skipped\": tru}}",
+    ];
+
+    for source in sources {
+        let options = ParserOptions::new();
+
+        assert_eq!(extract(source, &["/b"]), parse_and_follow(source, &["/b"], &options));
+    }
+
+    let options = ParserOptions::json5();
+    let source = "// comment\n{ a: 'single', 'b': [+1, 0x10, .5,], /* } */ c: [ 'x' ], }";
+    let pointers = &["/a", "/b/1", "/b/2", "/c/0", "/d"];
+
+    assert_eq!(extract_with(source, pointers, &options), parse_and_follow(source, pointers, &options));
+}
+
+#[test]
+fn extract_duplicate_keys() {
+    let source = r#"{"a": {"x": 1, "y": 2}, "b": [3], "a": {"x": 4}, "b": 5, "c": {"d": 6, "d": 7}}"#;
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Collect);
+
+    assert_eq!(extract_with(source, &["/a"], &options), Ok(vec![Some(array![{ x: 1, y: 2 }, { x: 4 }])]));
+    assert_eq!(extract_with(source, &["/a/y"], &ParserOptions::new()), Ok(vec![None]));
+}
@@ -0,0 +1,177 @@
+#[macro_use]
+extern crate json;
+
+use std::thread;
+use json::object::{ Interner, Object };
+use json::push::{ PushParser, Status };
+use json::{ parse, parse_with, parse_reader_with, DuplicateKeys, JsonValue, ParserOptions };
+
+const LONG: &str = "request_headers_x_forwarded_for_original";
+const LONGER: &str = "response_headers_content_security_policy";
+
+fn object(value: &JsonValue) -> &Object {
+    match *value {
+        JsonValue::Object(ref object) => object,
+        _                             => panic!("Expected an object, got {:?}", value),
+    }
+}
+
+// Where the bytes of `key` in `object` live.
+fn key_ptr(object: &Object, key: &str) -> *const u8 {
+    object.iter().find(|&(k, _)| k == key).expect("Key must be there").0.as_ptr()
+}
+
+#[test]
+fn interner_shares_long_keys() {
+    let interner = Interner::new();
+    let options = ParserOptions::new().interner(&interner);
+    let records: Vec<String> = (0..10).map(|n| {
+        format!(r#"{{"id":{},"{}":"10.0.0.{}","nested":{{"{}":[{}]}}}}"#, n, LONG, n, LONGER, n)
+    }).collect();
+
+    let values: Vec<JsonValue> = records.iter().map(|record| parse_with(record, &options).unwrap()).collect();
+
+    for (record, value) in records.iter().zip(&values) {
+        assert_eq!(*value, parse(record).unwrap());
+    }
+
+    // Short keys are stored in the objects either way
+    assert_eq!(interner.len(), 2);
+
+    let long = interner.intern(LONG);
+    let longer = interner.intern(LONGER);
+
+    for (n, value) in values.iter().enumerate() {
+        let record = object(value);
+        let nested = object(&value["nested"]);
+
+        assert_eq!(key_ptr(record, LONG), long.as_ptr());
+        assert_eq!(key_ptr(nested, LONGER), longer.as_ptr());
+        assert_eq!(record.get_interned(&long), Some(&JsonValue::from(format!("10.0.0.{}", n))));
+        assert_eq!(nested.get_interned(&longer), Some(&array![n]));
+        assert_eq!(record.get_interned(&longer), None);
+        assert_eq!(record.get_interned(&interner.intern("id")), Some(&JsonValue::from(n)));
+    }
+
+    // Handles from another interner still find the keys, by their bytes
+    let other = Interner::new().intern(LONG);
+
+    assert_ne!(other.as_ptr(), long.as_ptr());
+    assert_eq!(object(&values[3]).get_interned(&other), Some(&JsonValue::from("10.0.0.3")));
+
+    // Other ways of parsing go through the interner as well
+    let value = parse_reader_with(records[0].as_bytes(), &options).unwrap();
+
+    assert_eq!(key_ptr(object(&value), LONG), long.as_ptr());
+
+    let mut parser = PushParser::with_options(&options);
+
+    match parser.feed(records[1].as_bytes()).unwrap() {
+        Status::Complete(value) => assert_eq!(key_ptr(object(&value), LONG), long.as_ptr()),
+        other                   => panic!("Expected a value, got {:?}", other),
+    }
+
+    // Along with "id", interned by hand above
+    assert_eq!(interner.len(), 3);
+}
+
+#[test]
+fn interned_keys_in_objects() {
+    let interner = Interner::new();
+    let long = interner.intern(LONG);
+    let longer = interner.intern(LONGER);
+    let mut object = Object::new();
+
+    object.insert_interned(&long, 1.into());
+    object.insert(LONGER, 2.into());
+    object.insert_interned(&interner.intern("short"), 3.into());
+
+    // The same key, whichever way it's inserted
+    object.insert(LONG, 4.into());
+    object.insert_interned(&longer, 5.into());
+
+    assert_eq!(object.len(), 3);
+    assert_eq!(object.get(LONG), Some(&JsonValue::from(4)));
+    assert_eq!(object.get_interned(&longer), Some(&JsonValue::from(5)));
+    assert_eq!(object.get("short"), Some(&JsonValue::from(3)));
+    assert_eq!(key_ptr(&object, LONG), long.as_ptr());
+    assert_ne!(key_ptr(&object, LONGER), longer.as_ptr());
+
+    *object.get_interned_mut(&long).unwrap() = 6.into();
+
+    // Clones and objects rebuilt after a removal keep sharing the key
+    let cloned = object.clone();
+
+    assert_eq!(cloned, object);
+    assert_eq!(key_ptr(&cloned, LONG), long.as_ptr());
+
+    object.remove("short");
+
+    assert_eq!(object.get_interned(&long), Some(&JsonValue::from(6)));
+    assert_eq!(key_ptr(&object, LONG), long.as_ptr());
+
+    // The key outlives the interner and every handle to it
+    drop(interner);
+    drop(long);
+    drop(cloned);
+
+    assert_eq!(object.dump(), format!(r#"{{"{}":6,"{}":5}}"#, LONG, LONGER));
+}
+
+#[test]
+fn interner_limit() {
+    let interner = Interner::with_limit(1);
+    let options = ParserOptions::new().interner(&interner);
+    let source = format!(r#"{{"{}":1,"{}":2}}"#, LONG, LONGER);
+    let value = parse_with(&source, &options).unwrap();
+
+    assert_eq!(value, parse(&source).unwrap());
+    assert_eq!(interner.len(), 1);
+    assert_eq!(key_ptr(object(&value), LONG), interner.intern(LONG).as_ptr());
+
+    // Handles to keys past the limit work, they just aren't shared
+    let longer = interner.intern(LONGER);
+
+    assert_ne!(key_ptr(object(&value), LONGER), longer.as_ptr());
+    assert_eq!(object(&value).get_interned(&longer), Some(&JsonValue::from(2)));
+    assert_eq!(interner.len(), 1);
+}
+
+#[test]
+fn interner_with_duplicate_keys() {
+    let interner = Interner::new();
+    let source = format!(r#"{{"{}":1,"a":2,"{}":3}}"#, LONG, LONG);
+
+    for &policy in &[DuplicateKeys::KeepLast, DuplicateKeys::KeepFirst, DuplicateKeys::Collect, DuplicateKeys::Error] {
+        let options = ParserOptions::new().duplicate_keys(policy);
+
+        assert_eq!(
+            parse_with(&source, &options.clone().interner(&interner)),
+            parse_with(&source, &options),
+        );
+    }
+}
+
+#[test]
+fn interner_across_threads() {
+    let interner = Interner::new();
+
+    let handles: Vec<_> = (0..4).map(|n| {
+        let options = ParserOptions::new().interner(&interner);
+
+        thread::spawn(move || {
+            parse_with(&format!(r#"{{"{}":{}}}"#, LONG, n), &options).unwrap()
+        })
+    }).collect();
+
+    let long = interner.intern(LONG);
+
+    for (n, handle) in handles.into_iter().enumerate() {
+        let value = handle.join().unwrap();
+
+        assert_eq!(object(&value).get_interned(&long), Some(&JsonValue::from(n)));
+        assert_eq!(key_ptr(object(&value), LONG), long.as_ptr());
+    }
+
+    assert_eq!(interner.len(), 1);
+}
@@ -0,0 +1,103 @@
+#[macro_use]
+extern crate json;
+
+mod common;
+
+use json::lazy::LazyValue;
+use json::{ parse_lazy, parse_lazy_with, parse_with, DuplicateKeys, Error, JsonValue, ParserOptions };
+
+// Put the value back together by going through it, rather than parsing it
+// all at once.
+fn rebuild(value: &LazyValue) -> JsonValue {
+    if value.is_object() {
+        let mut object = JsonValue::new_object();
+
+        for (key, value) in value.entries() {
+            object[key] = rebuild(value);
+        }
+
+        return object;
+    }
+
+    if value.is_array() {
+        return JsonValue::Array(value.members().map(rebuild).collect());
+    }
+
+    value.value().clone()
+}
+
+fn lazy_with(source: &str, options: &ParserOptions) -> Result<JsonValue, Error> {
+    parse_lazy_with(source, options).map(|value| rebuild(&value))
+}
+
+#[test]
+fn lazy_matches_parse() {
+    common::matches_parse(&common::all_options(), lazy_with, |value| value);
+}
+
+#[test]
+fn lazy_navigation() {
+    let data = parse_lazy(r#"
+        {
+            "user": { "id": 42, "name": "Ann\tB", "active": true },
+            "items": [{ "price": 9.99 }, { "price": -1 }, "]}"],
+            "empty": {},
+            "skipped": { "deep": [[[{ "a": "}" }]]] }
+        }
+    "#).unwrap();
+
+    assert!(data.is_object());
+    assert_eq!(data.len(), 4);
+    assert!(data.has_key("items"));
+
+    assert_eq!(data["user"]["id"].as_u64(), Some(42));
+    assert_eq!(data["user"]["name"].as_str(), Some("Ann\tB"));
+    assert_eq!(data["user"]["active"].as_bool(), Some(true));
+    assert_eq!(data["items"][1]["price"].as_i64(), Some(-1));
+    assert_eq!(data["items"][2].as_str(), Some("]}"));
+    assert_eq!(data["items"].members().count(), 3);
+    assert_eq!(data.get("user").map(|user| user.len()), Some(3));
+
+    assert!(data["empty"].is_empty());
+    assert!(data["missing"].is_null());
+    assert!(data["items"][7]["price"].is_null());
+    assert_eq!(data["user"]["id"].as_str(), None);
+    assert_eq!(data.get("missing").map(|value| value.raw()), None);
+
+    let keys: Vec<_> = data["user"].entries().map(|(key, _)| key).collect();
+
+    assert_eq!(keys, ["id", "name", "active"]);
+
+    // Nothing that hasn't been read has been parsed
+    assert_eq!(data["skipped"].raw(), r#"{ "deep": [[[{ "a": "}" }]]] }"#);
+    assert!(!data["skipped"].is_empty());
+    assert_eq!(format!("{:?}", data["skipped"]), format!("LazyValue({})", data["skipped"].raw()));
+    assert_eq!(format!("{:?}", data["user"]["id"]), format!("{:?}", JsonValue::from(42)));
+}
+
+#[test]
+fn lazy_duplicate_keys() {
+    let source = r#"{"a": 1, "b": {"c": 2, "c": [3]}, "a": {"x": 4}, "a": 5}"#;
+
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Collect);
+    let data = parse_lazy_with(source, &options).unwrap();
+
+    assert_eq!(data["a"][1]["x"].as_u64(), Some(4));
+    assert_eq!(data["a"].value(), &array![1, { x: 4 }, 5]);
+    assert_eq!(data["a"].raw(), "");
+}
+
+#[test]
+fn lazy_with_options() {
+    let options = ParserOptions::json5();
+    let source = "// comment\n{ a: 'single ]', 'b': [+1, 0x10, .5,], /* } */ c: [ 'x' ] } // after";
+
+    assert_eq!(lazy_with(source, &options), parse_with(source, &options));
+    assert_eq!(parse_lazy_with(source, &options).unwrap()["b"][1].as_u64(), Some(16));
+
+    // Keys that are already there don't count towards the limit
+    let options = ParserOptions::new().object_entries_limit(1);
+
+    assert_eq!(lazy_with("{\"a\":1,\"a\":2}", &options), Ok(object!{ a: 2 }));
+    assert_eq!(lazy_with("{\"a\":1,\"a\":2}", &options), parse_with("{\"a\":1,\"a\":2}", &options));
+}
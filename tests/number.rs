@@ -1,6 +1,7 @@
 extern crate json;
 
-use json::number::Number;
+use json::number::{ Number, Decimal };
+use std::convert::TryFrom;
 use std::f64;
 
 #[test]
@@ -125,3 +126,66 @@ fn as_fixed_point_i64() {
 fn convert_f64_precision() {
     assert_eq!(unsafe { Number::from_parts_unchecked(true, 4750000000000001, -18) }, 0.004750000000000001);
 }
+
+fn decimal(text: &str) -> Decimal {
+    text.parse().unwrap()
+}
+
+#[test]
+fn decimal_from_str() {
+    for &text in &["0", "-0", "1.50", "-12.5e-3", "1E+400", "340282366920938463463374607431768211456"] {
+        assert_eq!(decimal(text).as_str(), text);
+    }
+
+    for &text in &["", "-", "01", "1.", ".5", "+1", "0x10", "1e", " 1", "1 ", "NaN"] {
+        assert!(text.parse::<Decimal>().is_err(), "{:?}", text);
+    }
+
+    assert_eq!("1.".parse::<Decimal>().unwrap_err(), json::parse("1.").unwrap_err());
+}
+
+#[test]
+fn decimal_eq() {
+    assert_eq!(decimal("1.5"), decimal("1.50"));
+    assert_eq!(decimal("150"), decimal("1.5e2"));
+    assert_eq!(decimal("0"), decimal("-0.0e10"));
+    assert_ne!(decimal("1.5"), decimal("-1.5"));
+    assert_ne!(decimal("18446744073709551616"), decimal("18446744073709551617"));
+
+    assert_eq!(decimal("0.10"), Number::from(0.1));
+    assert_ne!(decimal("18446744073709551617"), Number::from(18446744073709551615u64));
+}
+
+#[test]
+fn decimal_to_number() {
+    assert_eq!(decimal("10.50").to_number(), 10.5);
+    assert_eq!(decimal("18446744073709551617").to_number(), json::parse("18446744073709551617").unwrap());
+    assert_eq!(decimal("1e400").to_number(), json::parse("1e400").unwrap());
+
+    assert_eq!(Number::try_from(&decimal("10.50")).ok(), Some(Number::from_parts(true, 105, -1)));
+    assert_eq!(Number::try_from(&decimal("-18446744073709551615")).ok(), Some(-Number::from(u64::MAX)));
+    assert!(Number::try_from(&decimal("18446744073709551616")).is_err());
+    assert!(Number::try_from(&decimal("1e40000")).is_err());
+}
+
+#[test]
+fn decimal_to_integer() {
+    assert_eq!(u128::try_from(&decimal("340282366920938463463374607431768211455")).ok(), Some(u128::MAX));
+    assert_eq!(i128::try_from(&decimal("-170141183460469231731687303715884105728")).ok(), Some(i128::MIN));
+    assert_eq!(i128::try_from(&decimal("1.2e3")).ok(), Some(1200));
+    assert_eq!(u128::try_from(&decimal("-0")).ok(), Some(0));
+
+    assert!(u128::try_from(&decimal("340282366920938463463374607431768211456")).is_err());
+    assert!(i128::try_from(&decimal("170141183460469231731687303715884105728")).is_err());
+    assert!(u128::try_from(&decimal("-1")).is_err());
+    assert!(u128::try_from(&decimal("1.5")).is_err());
+    assert!(u128::try_from(&decimal("1e39")).is_err());
+}
+
+#[test]
+fn decimal_from_number() {
+    assert_eq!(Decimal::from(u128::MAX).as_str(), "340282366920938463463374607431768211455");
+    assert_eq!(Decimal::from(-5i8).as_str(), "-5");
+    assert_eq!(Decimal::try_from(Number::from(0.25)).ok().map(|decimal| decimal.to_string()), Some("0.25".into()));
+    assert!(Decimal::try_from(json::number::NAN).is_err());
+}
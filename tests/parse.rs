@@ -378,3 +378,429 @@ fn does_not_panic_on_huge_numbers() {
 
     let _ = json::parse(&string);
 }
+
+#[test]
+fn parse_with_default_options() {
+    let options = json::ParserOptions::new();
+
+    assert_eq!(json::parse_with(r#"{"foo":[1,2,3]}"#, &options).unwrap(), object!{
+        foo: [1, 2, 3]
+    });
+}
+
+#[test]
+fn parse_with_depth_limit() {
+    let options = json::ParserOptions::new().depth_limit(2);
+
+    assert!(json::parse_with(r#"[{"a":1}]"#, &options).is_ok());
+    assert_eq!(json::parse_with(r#"[{"a":[]}]"#, &options), Ok(array![{ a: [] }]));
+    assert_eq!(json::parse_with(r#"[{"a":[1]}]"#, &options), Err(json::Error::ExceededDepthLimit));
+}
+
+#[test]
+fn parse_with_size_limit() {
+    let options = json::ParserOptions::new().size_limit(8);
+
+    assert!(json::parse_with("[1,2,3] ", &options).is_ok());
+    assert_eq!(json::parse_with("[1,2,3]  ", &options), Err(json::Error::ExceededSizeLimit));
+}
+
+#[test]
+fn parse_with_string_length_limit() {
+    let options = json::ParserOptions::new().string_length_limit(3);
+
+    assert!(json::parse_with(r#"{"foo":"bar"}"#, &options).is_ok());
+    assert!(json::parse_with(r#""\n\t\"""#, &options).is_ok());
+    assert_eq!(json::parse_with(r#""fooo""#, &options), Err(json::Error::ExceededStringLengthLimit));
+    assert_eq!(json::parse_with(r#"{"fooo":1}"#, &options), Err(json::Error::ExceededStringLengthLimit));
+    assert_eq!(json::parse_with(r#""\nfoo""#, &options), Err(json::Error::ExceededStringLengthLimit));
+
+    // Escaped strings are cut short as soon as they're too long
+    let long = format!("\"\\n{}", "x".repeat(100_000));
+
+    assert_eq!(json::parse_with(&long, &options), Err(json::Error::ExceededStringLengthLimit));
+}
+
+#[test]
+fn parse_with_object_entries_limit() {
+    let options = json::ParserOptions::new().object_entries_limit(2);
+
+    assert!(json::parse_with(r#"{"a":1,"b":2}"#, &options).is_ok());
+    assert!(json::parse_with(r#"{"a":1,"a":2,"b":3}"#, &options).is_ok());
+    assert_eq!(json::parse_with(r#"{"a":1,"b":2,"c":3}"#, &options), Err(json::Error::ExceededObjectEntriesLimit));
+
+    let options = json::ParserOptions::new().object_entries_limit(0);
+
+    assert!(json::parse_with("{}", &options).is_ok());
+    assert_eq!(json::parse_with(r#"{"a":1}"#, &options), Err(json::Error::ExceededObjectEntriesLimit));
+}
+
+#[test]
+fn parse_with_array_length_limit() {
+    let options = json::ParserOptions::new().array_length_limit(2);
+
+    assert!(json::parse_with("[[1,2],[3,4]]", &options).is_ok());
+    assert_eq!(json::parse_with("[1,2,3]", &options), Err(json::Error::ExceededArrayLengthLimit));
+
+    let options = json::ParserOptions::new().array_length_limit(0);
+
+    assert!(json::parse_with("[]", &options).is_ok());
+    assert_eq!(json::parse_with("[1]", &options), Err(json::Error::ExceededArrayLengthLimit));
+}
+
+#[test]
+fn parse_bytes() {
+    assert_eq!(json::parse_bytes(br#"{"foo":["bar",10,true,null]}"#).unwrap(), object!{
+        foo: ["bar", 10, true, null]
+    });
+    assert_eq!(json::parse_bytes("\"zaż\\nółć\"".as_bytes()).unwrap(), "zaż\nółć");
+}
+
+#[test]
+fn parse_bytes_invalid_utf8_in_string() {
+    assert_eq!(json::parse_bytes(b"{\n  \"foo\": \"b\xC3\x28r\"\n}"), Err(json::Error::InvalidUtf8 {
+        offset: 13,
+        line: 2,
+        column: 12,
+    }));
+}
+
+#[test]
+fn parse_bytes_invalid_utf8_in_escaped_string() {
+    assert_eq!(json::parse_bytes(b"[\"\\n\xF0\x9F\"]"), Err(json::Error::InvalidUtf8 {
+        offset: 4,
+        line: 1,
+        column: 5,
+    }));
+
+    // Invalid byte comes before the invalid escape
+    assert_eq!(json::parse_bytes(b"[\"\\n\xFF\\x\"]"), Err(json::Error::InvalidUtf8 {
+        offset: 4,
+        line: 1,
+        column: 5,
+    }));
+}
+
+#[test]
+fn parse_bytes_invalid_utf8_outside_string() {
+    assert_eq!(json::parse_bytes(b"[1, \xE9]"), Err(json::Error::InvalidUtf8 {
+        offset: 4,
+        line: 1,
+        column: 5,
+    }));
+}
+
+#[test]
+fn parse_bytes_unexpected_character() {
+    assert_eq!(json::parse_bytes("[\"é\", é]".as_bytes()), Err(json::Error::UnexpectedCharacter {
+        ch: 'é',
+        line: 1,
+        column: 7,
+        offset: 7,
+        expected: json::Expected::Value,
+    }));
+}
+
+#[test]
+fn parse_unexpected_character_expected() {
+    use json::Expected;
+
+    let sources: &[(&str, Expected, usize)] = &[
+        ("x", Expected::Value, 0),
+        ("[1, x]", Expected::Value, 4),
+        ("[1 2]", Expected::CommaOrEndOfArray, 3),
+        ("{\"a\": 1 \"b\": 2}", Expected::CommaOrEndOfObject, 8),
+        ("{1: 2}", Expected::KeyOrEndOfObject, 1),
+        ("{\"a\": 1, 2: 3}", Expected::Key, 9),
+        ("{\"a\" 1}", Expected::Colon, 5),
+        ("tRue", Expected::Literal("true"), 1),
+        ("falsy", Expected::Literal("false"), 4),
+        ("[nul]", Expected::Literal("null"), 4),
+        ("-x", Expected::Digit, 1),
+        ("1.x", Expected::Digit, 2),
+        ("1e+x", Expected::Digit, 3),
+        ("\"\\u12x4\"", Expected::HexDigit, 5),
+        ("\"\\uD834x\"", Expected::LowSurrogate, 7),
+        ("\"\\x\"", Expected::Escape, 2),
+        ("\"a\tb\"", Expected::StringCharacter, 2),
+        ("\"\\na\tb\"", Expected::StringCharacter, 4),
+        ("[1] ]", Expected::EndOfJson, 4),
+    ];
+
+    for &(source, expected, offset) in sources {
+        match json::parse(source) {
+            Err(json::Error::UnexpectedCharacter { expected: e, offset: o, .. }) => {
+                assert_eq!((e, o), (expected, offset), "{:?}", source);
+            },
+            result => panic!("{:?} parsed as {:?}", source, result),
+        }
+    }
+
+    let options = json::ParserOptions::json5();
+
+    assert!(matches!(
+        json::parse_with("[0x]", &options),
+        Err(json::Error::UnexpectedCharacter { expected: Expected::HexDigit, offset: 3, .. })
+    ));
+    assert!(matches!(
+        json::parse_with("// comment\n[1 /x]", &options),
+        Err(json::Error::UnexpectedCharacter { expected: Expected::Comment, offset: 15, .. })
+    ));
+}
+
+#[test]
+fn parse_bytes_overlong_encoding() {
+    assert!(json::parse_bytes(b"\"\xC0\xAF\"").is_err());
+}
+
+#[test]
+fn parse_bytes_with_options() {
+    let options = json::ParserOptions::new().string_length_limit(2);
+
+    assert!(json::parse_bytes_with("\"ż\"".as_bytes(), &options).is_ok());
+    assert_eq!(json::parse_bytes_with("\"żż\"".as_bytes(), &options), Err(json::Error::ExceededStringLengthLimit));
+}
+
+#[test]
+fn parse_relaxed_syntax_disabled_by_default() {
+    let sources = [
+        "[1] // comment",
+        "[1 /* comment */]",
+        "[1,]",
+        r#"{"a":1,}"#,
+        "'single'",
+        "{a:1}",
+        "0x10",
+        "+1",
+        ".5",
+    ];
+
+    for source in &sources {
+        assert!(json::parse(source).is_err(), "{} should not parse", source);
+        assert!(json::parse_with(source, &json::ParserOptions::json5()).is_ok(), "{} should parse", source);
+    }
+}
+
+#[test]
+fn parse_with_comments() {
+    let options = json::ParserOptions::new().comments(true);
+
+    assert_eq!(json::parse_with("// one\n1 // two", &options), Ok(1.into()));
+    assert_eq!(json::parse_with("/**/[/* a */1/***/,/*\n*/2]/* end **/", &options), Ok(array![1, 2]));
+    assert_eq!(json::parse_with("{\"a\"//\n://\n1//\n}", &options), Ok(object!{ a: 1 }));
+    assert_eq!(json::parse_with("[\"// not a comment\"]", &options), Ok(array!["// not a comment"]));
+
+    assert_eq!(json::parse_with("[1 /* open", &options), Err(json::Error::UnexpectedEndOfJson));
+    assert_eq!(json::parse_with("[1 /", &options), Err(json::Error::UnexpectedEndOfJson));
+    assert_eq!(json::parse_with("[1 /x]", &options), Err(json::Error::UnexpectedCharacter {
+        ch: 'x',
+        line: 1,
+        column: 5,
+        offset: 4,
+        expected: json::Expected::Comment,
+    }));
+    assert_eq!(json::parse_bytes_with(b"1 // \xFF", &options), Err(json::Error::InvalidUtf8 {
+        offset: 5,
+        line: 1,
+        column: 6,
+    }));
+}
+
+#[test]
+fn parse_with_trailing_commas() {
+    let options = json::ParserOptions::new().trailing_commas(true);
+
+    assert_eq!(json::parse_with("[1, 2, ]", &options), Ok(array![1, 2]));
+    assert_eq!(json::parse_with(r#"{"a": [{},], }"#, &options), Ok(object!{ a: [{}] }));
+    assert!(json::parse_with("[,]", &options).is_err());
+    assert!(json::parse_with("{,}", &options).is_err());
+    assert!(json::parse_with("[1,,]", &options).is_err());
+}
+
+#[test]
+fn parse_with_single_quotes() {
+    let options = json::ParserOptions::new().single_quotes(true);
+
+    assert_eq!(json::parse_with("''", &options), Ok("".into()));
+    assert_eq!(json::parse_with(r#"'say "hi"'"#, &options), Ok(r#"say "hi""#.into()));
+    assert_eq!(json::parse_with(r#"'it\'s \u0041\n'"#, &options), Ok("it's A\n".into()));
+    assert_eq!(json::parse_with(r#""it\'s""#, &options), Ok("it's".into()));
+    assert_eq!(json::parse_with(r#"{'a': 'b', "c": 'd'}"#, &options), Ok(object!{ a: "b", c: "d" }));
+    assert_eq!(json::parse_with("'zażółć'", &options), Ok("zażółć".into()));
+    assert_eq!(json::parse_with("'open", &options), Err(json::Error::UnexpectedEndOfJson));
+}
+
+#[test]
+fn parse_with_unquoted_keys() {
+    let options = json::ParserOptions::new().unquoted_keys(true);
+
+    assert_eq!(json::parse_with("{a: 1, $b_2: 2, _: 3}", &options), Ok(object!{ a: 1, "$b_2" => 2, "_" => 3 }));
+    assert_eq!(json::parse_with("{ null : true }", &options), Ok(object!{ null: true }));
+    assert!(json::parse_with("{1a: 1}", &options).is_err());
+    assert!(json::parse_with("{a-b: 1}", &options).is_err());
+    assert!(json::parse_with("{a: b}", &options).is_err());
+}
+
+#[test]
+fn parse_with_number_extensions() {
+    let options = json::ParserOptions::new()
+        .hex_numbers(true)
+        .leading_plus(true)
+        .leading_decimal_point(true);
+
+    assert_eq!(json::parse_with("0xFF", &options), Ok(255.into()));
+    assert_eq!(json::parse_with("[0x0, 0Xabc, -0x10, +0x10]", &options), Ok(array![0, 0xABC, -16, 16]));
+    assert_eq!(json::parse_with("0xFFFFFFFFFFFFFFFF", &options), Ok(u64::MAX.into()));
+    assert_eq!(json::parse_with("0x100000000000000000", &options), Ok(2f64.powi(68).into()));
+    assert_eq!(json::parse_with("[+1, +1.5e1, .5, -.5, +.5e1]", &options), Ok(array![1, 15, 0.5, -0.5, 5]));
+
+    assert!(json::parse_with("0x", &options).is_err());
+    assert!(json::parse_with("0xG", &options).is_err());
+    assert!(json::parse_with("+-1", &options).is_err());
+    assert!(json::parse_with(".", &options).is_err());
+    assert!(json::parse_with(".e1", &options).is_err());
+    assert!(json::parse_with("+", &options).is_err());
+}
+
+#[test]
+fn parse_lossless_numbers() {
+    let options = json::ParserOptions::new().lossless_numbers(true);
+    let source = r#"{"id":340282366920938463463374607431768211455,"price":0.10,"big":1.0e400,"small":-123456789012345678901234567890e-50,"plain":[0,-1,2.5]}"#;
+    let data = json::parse_with(source, &options).unwrap();
+
+    assert_eq!(data.dump(), source);
+    assert_eq!(data["price"].as_decimal().map(|decimal| decimal.as_str()), Some("0.10"));
+    assert_eq!(data["price"], 0.1);
+    assert_eq!(data["plain"], array![0, -1, 2.5]);
+    assert_eq!(data["plain"][1].as_i64(), Some(-1));
+    assert!(data["id"].is_number());
+
+    // Same with everything else that parses
+    assert_eq!(json::parse_reader_with(source.as_bytes(), &options).unwrap().dump(), source);
+    assert_eq!(json::parse_recover_with(source, &options).0.dump(), source);
+
+    let mut parser = json::push::PushParser::with_options(&options);
+    let mut value = None;
+
+    for chunk in source.as_bytes().chunks(7) {
+        if let json::push::Status::Complete(done) = parser.feed(chunk).unwrap() {
+            value = Some(done);
+        }
+    }
+    assert_eq!(value.map(|value| value.dump()), Some(source.into()));
+
+    // Relaxed syntax is turned into JSON
+    let options = json::ParserOptions::json5().lossless_numbers(true);
+
+    assert_eq!(json::parse_with("[+1, .50, -.5, +.5e1, 0xFF, -0x10]", &options).unwrap().dump(), "[1,0.50,-0.5,0.5e1,255,-16]");
+    assert!(json::parse_with("[+]", &options).is_err());
+
+    // Every digit of them, however many there are
+    let source = format!("[0x{}, -0X{}, 0x00001]", "F".repeat(40), "f".repeat(300));
+    let data = json::parse_with(&source, &options).unwrap();
+
+    assert_eq!(data[0].as_decimal().map(|decimal| decimal.as_str()), Some("1461501637330902918203684832716283019655932542975"));
+    assert_eq!(data[1].as_decimal().map(|decimal| decimal.as_str().len()), Some(363));
+    assert!(data[1].as_decimal().unwrap().as_str().starts_with("-172184794563857506180673776960"));
+    assert!(data[1].as_decimal().unwrap().as_str().ends_with("205318914435913511141036261375"));
+    assert_eq!(data[2].dump(), "1");
+    assert_eq!(json::parse_with("[1.]", &options), json::parse_with("[1.]", &json::ParserOptions::json5()));
+}
+
+#[test]
+fn parse_with_duplicate_keys() {
+    use json::DuplicateKeys;
+
+    let source = r#"{"a": 1, "b": {"c": [], "c": {}}, "a": [2], "a": {"d": 3}}"#;
+    let parse = |policy| json::parse_with(source, &json::ParserOptions::new().duplicate_keys(policy));
+
+    assert_eq!(parse(DuplicateKeys::KeepLast), Ok(object!{ a: { d: 3 }, b: { c: {} } }));
+    assert_eq!(parse(DuplicateKeys::KeepFirst), Ok(object!{ a: 1, b: { c: [] } }));
+    assert_eq!(parse(DuplicateKeys::Collect), Ok(object!{ a: [1, [2], { d: 3 }], b: { c: [[], {}] } }));
+    assert_eq!(parse(DuplicateKeys::Error), Err(json::Error::DuplicateKey {
+        key: "c".into(),
+        line: 1,
+        column: 25,
+    }));
+
+    assert_eq!(json::parse_with(source, &json::ParserOptions::new()), parse(DuplicateKeys::KeepLast));
+
+    // Collected values make an array like any other
+    let options = json::ParserOptions::new().duplicate_keys(DuplicateKeys::Collect).array_length_limit(2);
+
+    assert_eq!(json::parse_with(r#"{"a":1,"a":2}"#, &options), Ok(object!{ a: [1, 2] }));
+    assert_eq!(json::parse_with(r#"{"a":1,"a":2,"a":3}"#, &options), Err(json::Error::ExceededArrayLengthLimit));
+}
+
+#[test]
+fn parse_with_duplicate_keys_in_sibling_objects() {
+    let options = json::ParserOptions::new().duplicate_keys(json::DuplicateKeys::Collect);
+
+    assert_eq!(
+        json::parse_with(r#"[{"a": 1, "a": 2}, {"a": 3, "b": 4, "a": 5}, {"a": [6]}]"#, &options),
+        Ok(array![{ a: [1, 2] }, { a: [3, 5], b: 4 }, { a: [6] }])
+    );
+}
+
+#[test]
+fn parse_with_duplicate_keys_error_position() {
+    let options = json::ParserOptions::json5().duplicate_keys(json::DuplicateKeys::Error);
+
+    assert_eq!(json::parse_with("{\n  'a\\n': 1,\n  \"a\\n\": 2\n}", &options), Err(json::Error::DuplicateKey {
+        key: "a\n".into(),
+        line: 3,
+        column: 3,
+    }));
+    assert_eq!(json::parse_with("{ key: 1, key: 2 }", &options), Err(json::Error::DuplicateKey {
+        key: "key".into(),
+        line: 1,
+        column: 11,
+    }));
+    assert_eq!(
+        json::Error::DuplicateKey { key: "a".into(), line: 1, column: 2 }.to_string(),
+        r#"Duplicate key: "a" at (1:2)"#
+    );
+}
+
+#[test]
+fn parse_large_documents_same_as_reader() {
+    // Big enough for the first pass over the source, with long strings and
+    // runs of whitespace on both sides of 64 byte blocks
+    let long = "x".repeat(150);
+    let good = format!(
+        "{{\n{pad}\"a\":   [1, 2.5e3, true,\t\tnull, \"{long}\", \"esc\\\"aped\\\\{long}\\u00e9\"],\r\n{pad}\"b\" : {{ \"c\" :\"zażółć {long}\" }} \n{pad}}}\n{pad}",
+        pad = " ".repeat(70),
+        long = long,
+    );
+    let json5 = format!("// {long}\n{{ a: '{long}', /* {long} */ b: [0x10, .5,   ],\n{pad}}}", long = long, pad = " ".repeat(90));
+
+    let mut broken = vec![
+        good.replace("true", "tru"),
+        good.replace("\t\t", "\t\t\"unterminated"),
+        good.replace("zażółć", "za\nżółć"),
+        good.replace("\"c\" :", "\"c\"  "),
+        format!("{}  x", good),
+        good[..good.len() - 80].to_string(),
+    ];
+    broken.push(format!("[\"{}", long));
+
+    let strict = json::ParserOptions::new();
+    let relaxed = json::ParserOptions::json5();
+
+    assert!(json::parse(&good).unwrap()["b"]["c"].as_str().unwrap().ends_with(&long));
+    assert!(json::parse_with(&json5, &relaxed).is_ok());
+    assert!(broken.iter().all(|source| json::parse(source).is_err()));
+
+    let mut cases = vec![(good, &strict), (json5, &relaxed)];
+    cases.extend(broken.into_iter().map(|source| (source, &strict)));
+
+    for (source, options) in &cases {
+        let options = *options;
+        let expected = json::parse_reader_with(source.as_bytes(), options);
+
+        assert_eq!(json::parse_with(source, options), expected, "{:?}", source);
+        assert_eq!(json::parse_bytes_with(source.as_bytes(), options), expected, "{:?}", source);
+        assert_eq!(json::parse_borrowed_with(source, options).map(json::JsonValue::from), expected, "{:?}", source);
+        assert_eq!(json::parse_document_with(source, options).map(|document| document.to_json_value()), expected, "{:?}", source);
+    }
+}
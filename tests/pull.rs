@@ -0,0 +1,171 @@
+extern crate json;
+
+mod common;
+
+use json::pull::{ PullParser, Event };
+use json::{ parse_with, Error, Expected, JsonValue, ParserOptions };
+
+// Put the value back together from the events.
+fn rebuild(parser: &mut PullParser) -> Result<JsonValue, Error> {
+    let mut stack: Vec<(JsonValue, Option<String>)> = Vec::new();
+    let mut key = None;
+
+    while let Some(event) = parser.next_event()? {
+        let value = match event {
+            Event::StartObject => {
+                stack.push((JsonValue::new_object(), key.take()));
+                continue;
+            },
+            Event::StartArray => {
+                stack.push((JsonValue::new_array(), key.take()));
+                continue;
+            },
+            Event::Key(name) => {
+                key = Some(name.to_owned());
+                continue;
+            },
+            Event::EndObject | Event::EndArray => {
+                let (value, name) = stack.pop().unwrap();
+                key = name;
+                value
+            },
+            Event::String(string) => string.into(),
+            Event::Number(number) => number.into(),
+            Event::Bool(boolean)  => boolean.into(),
+            Event::Null           => JsonValue::Null,
+        };
+
+        match stack.last_mut() {
+            Some(&mut (JsonValue::Object(ref mut object), _)) => object.insert(&key.take().unwrap(), value),
+            Some(&mut (ref mut array, _))                     => array.push(value).unwrap(),
+            None                                              => return Ok(value),
+        }
+    }
+
+    unreachable!()
+}
+
+fn pull_with(source: &str, options: &ParserOptions) -> Result<JsonValue, Error> {
+    let mut parser = PullParser::with_options(source, options);
+    let value = rebuild(&mut parser)?;
+
+    assert_eq!(parser.next_event()?, None);
+    assert_eq!(parser.depth(), 0);
+
+    Ok(value)
+}
+
+#[test]
+fn pull_events() {
+    let mut parser = PullParser::new(r#"{"a": [1, "two\n", true], "b\"": null, "c": {}}"#);
+
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartObject)));
+    assert_eq!(parser.next_event(), Ok(Some(Event::Key("a"))));
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartArray)));
+    assert_eq!(parser.depth(), 2);
+    assert_eq!(parser.next_event(), Ok(Some(Event::Number(1.into()))));
+    assert_eq!(parser.next_event(), Ok(Some(Event::String("two\n"))));
+    assert_eq!(parser.next_event(), Ok(Some(Event::Bool(true))));
+    assert_eq!(parser.next_event(), Ok(Some(Event::EndArray)));
+    assert_eq!(parser.next_event(), Ok(Some(Event::Key("b\""))));
+    assert_eq!(parser.next_event(), Ok(Some(Event::Null)));
+    assert_eq!(parser.next_event(), Ok(Some(Event::Key("c"))));
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartObject)));
+    assert_eq!(parser.next_event(), Ok(Some(Event::EndObject)));
+    assert_eq!(parser.next_event(), Ok(Some(Event::EndObject)));
+    assert_eq!(parser.next_event(), Ok(None));
+    assert_eq!(parser.next_event(), Ok(None));
+}
+
+// Duplicate keys are left to the caller, and numbers are always a
+// `Number`, so only the syntax and the limits apply.
+#[test]
+fn pull_matches_parse() {
+    common::matches_parse(&common::options(), pull_with, |value| value);
+}
+
+#[test]
+fn pull_error_positions() {
+    let options = ParserOptions::new();
+
+    for source in &["\n\nnulX\n", "\n  [\n    null,\n  ]  \n"] {
+        assert_eq!(pull_with(source, &options), parse_with(source, &options));
+    }
+}
+
+#[test]
+fn pull_relaxed_syntax() {
+    let options = ParserOptions::json5();
+    let sources = &[
+        "// comment\n{ /* block ** comment */ a: 1, 'b': 'it\\'s', \"c\": [+1, .5, 0xFf,], }",
+        "[ // zażółć\n 1, /* 🦄 */ 2 ] // end",
+        "[1 /x]",
+        "{a: 1, 2a: 2}",
+    ];
+
+    for source in sources {
+        assert_eq!(pull_with(source, &options), parse_with(source, &options));
+    }
+}
+
+#[test]
+fn pull_skip() {
+    let source = r#"{"skip": {"a": ["]", "}\"", {"b": [[]]}], "c": "{"}, "keep": [1, 2, 3]}"#;
+    let mut parser = PullParser::new(source);
+
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartObject)));
+    assert_eq!(parser.next_event(), Ok(Some(Event::Key("skip"))));
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartObject)));
+    assert_eq!(parser.skip(), Ok(()));
+    assert_eq!(parser.depth(), 1);
+    assert_eq!(parser.next_event(), Ok(Some(Event::Key("keep"))));
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartArray)));
+    assert_eq!(parser.next_event(), Ok(Some(Event::Number(1.into()))));
+
+    // Rest of the array, then rest of the object
+    assert_eq!(parser.skip(), Ok(()));
+    assert_eq!(parser.depth(), 1);
+    assert_eq!(parser.skip(), Ok(()));
+    assert_eq!(parser.depth(), 0);
+    assert_eq!(parser.next_event(), Ok(None));
+
+    // Nothing to skip at the top
+    assert_eq!(parser.skip(), Ok(()));
+    assert_eq!(parser.next_event(), Ok(None));
+}
+
+#[test]
+fn pull_skip_errors() {
+    let mut parser = PullParser::new("[[1, 2}]");
+
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartArray)));
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartArray)));
+
+    let error = Error::UnexpectedCharacter {
+        ch: '}',
+        line: 1,
+        column: 7,
+        offset: 6,
+        expected: Expected::CommaOrEndOfArray,
+    };
+
+    assert_eq!(parser.skip(), Err(error.clone()));
+    assert_eq!(parser.next_event(), Err(error));
+
+    let mut parser = PullParser::new("[\"abc");
+
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartArray)));
+    assert_eq!(parser.skip(), Err(Error::UnexpectedEndOfJson));
+
+    let options = ParserOptions::new().depth_limit(2);
+    let mut parser = PullParser::with_options("[[[[1]]]]", &options);
+
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartArray)));
+    assert_eq!(parser.skip(), Err(Error::ExceededDepthLimit));
+
+    let mut parser = PullParser::with_options("[[[ ], {}], 1]", &options);
+
+    assert_eq!(parser.next_event(), Ok(Some(Event::StartArray)));
+    assert_eq!(parser.skip(), Ok(()));
+    assert_eq!(parser.next_event(), Ok(None));
+}
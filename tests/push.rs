@@ -0,0 +1,260 @@
+#[macro_use]
+extern crate json;
+
+use json::push::{ PushParser, Status };
+use json::{ parse_bytes, Error, JsonValue, ParserOptions };
+
+// Feed the chunks to a fresh parser, collecting all values until the end,
+// or the first error.
+fn push_chunks(chunks: &[&[u8]], options: &ParserOptions) -> Result<Vec<JsonValue>, Error> {
+    let mut parser = PushParser::with_options(options);
+    let mut values = Vec::new();
+
+    for chunk in chunks {
+        let mut chunk = *chunk;
+
+        while let Status::Complete(value) = parser.feed(chunk)? {
+            values.push(value);
+            chunk = &[];
+        }
+    }
+
+    if let Some(value) = parser.finish()? {
+        values.push(value);
+    }
+
+    assert_eq!(parser.finish(), Ok(None));
+
+    Ok(values)
+}
+
+// Feed the source split in two at every possible point, and in chunks of
+// a few bytes, making sure the result is the same every time.
+fn push_split(source: &[u8], options: &ParserOptions) -> Result<Vec<JsonValue>, Error> {
+    let expected = push_chunks(&[source], options);
+
+    for split in 0..=source.len() {
+        assert_eq!(push_chunks(&[&source[..split], &source[split..]], options), expected);
+    }
+
+    for step in 1..5 {
+        let chunks: Vec<&[u8]> = source.chunks(step).collect();
+
+        assert_eq!(push_chunks(&chunks, options), expected);
+    }
+
+    expected
+}
+
+const DOCUMENTS: &[&str] = &[
+    "null",
+    "  true  ",
+    "0",
+    "-12.5e-3",
+    "18446744073709551616",
+    r#""simple string""#,
+    r#""escaped \"string\" with \\ \/ \b \f \n \r \t \u0041 \uD834\uDD1E""#,
+    "\"zażółć gęślą jaźń 🦄\"",
+    "\"zażółć\\ngęślą jaźń 🦄\"",
+    r#"{"a":1,"b":[true,false,null],"c":{"d":"e","f":[{}, []]}}"#,
+    r#"{"\u0041\\":-0.5,"a\"b":[1e10, 20E-2, 3.0e+1],"":{}}"#,
+    "[\n  1,\n  \"two\",\n  { \"three\": 3.0 }\n]\n",
+];
+
+#[test]
+fn push_parser_matches_parse_bytes() {
+    for source in DOCUMENTS {
+        let expected = parse_bytes(source.as_bytes()).unwrap();
+
+        assert_eq!(push_split(source.as_bytes(), &ParserOptions::new()), Ok(vec![expected]));
+    }
+}
+
+#[test]
+fn push_parser_error_positions() {
+    let sources: &[&[u8]] = &[
+        b"\n\nnulX\n",
+        "\n\nnul🦄\n".as_bytes(),
+        "[1, 🦄]".as_bytes(),
+        b"\n  [\n    null,\n  ]  \n",
+        b"{\"foo\" 1}",
+        b"{\"foo\": 1,}",
+        b"{\"foo\": 1]",
+        b"{1: 2}",
+        b"{\"a\": 1 \"b\": 2}",
+        b"[nul]",
+        b"[1}",
+        b"\"abc\x01\"",
+        b"[\"\\n\\x\"]",
+        b"[\"\\u12x4\"]",
+        b"[\"\\uD834\\u0041\"]",
+        b"[\"foo\", \"b\xC3\x28r\"]",
+        b"[\"\\nb\xC3\x28r\"]",
+        b"[\"\\nb\xE2\x82\"]",
+        b"[1, \xE9]",
+        b"[01]",
+        b"[1.]",
+        b"[1.e5]",
+        b"[-]",
+        b"[1e+]",
+        b"[1-2]",
+        b"[\"abc",
+        b"[\"\\nabc",
+        b"[\"\\nab\xC3",
+        b"[\"\\u00",
+        b"[1, 2",
+        b"[1, 2.",
+        b"[tru",
+        b"{\"foo\"",
+        b"-",
+    ];
+
+    for source in sources {
+        let expected = parse_bytes(source).unwrap_err();
+
+        assert_eq!(push_split(source, &ParserOptions::new()), Err(expected));
+    }
+}
+
+#[test]
+fn push_parser_multiple_values() {
+    let source = b"[1] {\"a\":true}\n\"foo\" 2 null 3.5\n-1 []";
+
+    assert_eq!(push_split(source, &ParserOptions::new()), Ok(vec![
+        array![1],
+        object!{ a: true },
+        "foo".into(),
+        2.into(),
+        JsonValue::Null,
+        3.5.into(),
+        (-1).into(),
+        array![],
+    ]));
+}
+
+#[test]
+fn push_parser_positions_continue_after_values() {
+    let mut parser = PushParser::new();
+
+    assert_eq!(parser.feed(b"[1]\n[2").unwrap(), Status::Complete(array![1]));
+    assert_eq!(parser.feed(b"").unwrap(), Status::Incomplete);
+    assert_eq!(parser.feed(b", x]"), Err(Error::UnexpectedCharacter {
+        ch: 'x',
+        line: 2,
+        column: 5,
+        offset: 8,
+        expected: json::Expected::Value,
+    }));
+}
+
+#[test]
+fn push_parser_keeps_returning_error() {
+    let mut parser = PushParser::new();
+    let error = parser.feed(b"[1 2]").unwrap_err();
+
+    assert_eq!(parser.feed(b"[]"), Err(error.clone()));
+    assert_eq!(parser.finish(), Err(error));
+}
+
+#[test]
+fn push_parser_finish() {
+    let mut parser = PushParser::new();
+
+    assert_eq!(parser.finish(), Ok(None));
+    assert_eq!(parser.feed(b"  \n").unwrap(), Status::Incomplete);
+    assert_eq!(parser.finish(), Ok(None));
+    assert_eq!(parser.feed(b"12").unwrap(), Status::Incomplete);
+    assert_eq!(parser.feed(b"34").unwrap(), Status::Incomplete);
+    assert_eq!(parser.finish(), Ok(Some(1234.into())));
+    assert_eq!(parser.finish(), Ok(None));
+    assert_eq!(parser.feed(b"[").unwrap(), Status::Incomplete);
+    assert_eq!(parser.finish(), Err(Error::UnexpectedEndOfJson));
+}
+
+#[test]
+fn push_parser_with_limits() {
+    let options = ParserOptions::new()
+        .depth_limit(2)
+        .array_length_limit(2)
+        .object_entries_limit(1)
+        .string_length_limit(3);
+
+    assert_eq!(push_chunks(&[b"[[1, 2]]"], &options), Ok(vec![array![[1, 2]]]));
+    assert_eq!(push_chunks(&[b"[[", b"[1]]]"], &options), Err(Error::ExceededDepthLimit));
+    assert_eq!(push_chunks(&[b"[1, 2", b", 3]"], &options), Err(Error::ExceededArrayLengthLimit));
+    assert_eq!(push_chunks(&[b"{\"a\":1, ", b"\"b\":2}"], &options), Err(Error::ExceededObjectEntriesLimit));
+    assert_eq!(push_chunks(&[b"[\"ab", b"cd\"]"], &options), Err(Error::ExceededStringLengthLimit));
+}
+
+#[test]
+fn push_parser_with_size_limit() {
+    let options = ParserOptions::new().size_limit(8);
+
+    // The limit applies to each value separately
+    assert_eq!(
+        push_chunks(&[b"[1,2,3] ", b"[4,", b"5,6]"], &options),
+        Ok(vec![array![1, 2, 3], array![4, 5, 6]])
+    );
+    assert_eq!(push_chunks(&[b"[1,2,3,", b"4]"], &options), Err(Error::ExceededSizeLimit));
+}
+
+#[test]
+fn push_parser_relaxed_syntax() {
+    let options = ParserOptions::json5();
+    let sources: &[&str] = &[
+        "// comment\n{ /* block ** comment */ a: 1, 'b': 'it\\'s', \"c\": [+1, .5, 0xFf,], }",
+        "[ // zażółć\n 1, /* 🦄 */ 2 ] // end",
+        "{ $key_1 : -0x10 }",
+        "'single \"quoted\"'",
+        "1// comment",
+    ];
+
+    for source in sources {
+        let expected = json::parse_with(source, &options).unwrap();
+
+        assert_eq!(push_split(source.as_bytes(), &options), Ok(vec![expected]));
+    }
+
+    let sources: &[&[u8]] = &[
+        b"[1 /* open",
+        b"[1 /",
+        b"[1 /x]",
+        "[1 /🦄]".as_bytes(),
+        b"[1 // \xFF\n]",
+        b"[1 /* \xE2\x82 */]",
+        b"{a: 1, 2a: 2}",
+        b"{a",
+        b"['abc",
+        b"['ab\\x']",
+        b"[0x]",
+        b"[0xG]",
+        b"[+-1]",
+        b"[.e1]",
+        b"[1,,]",
+    ];
+
+    for source in sources {
+        let expected = json::parse_bytes_with(source, &options).unwrap_err();
+
+        assert_eq!(push_split(source, &options), Err(expected));
+    }
+}
+
+#[test]
+fn push_parser_duplicate_keys() {
+    use json::DuplicateKeys;
+
+    let source = b"{\"a\": 1, \"b\": {\"c\": [], \"c\": {}},\n \"a\": [2], \"a\": {\"d\": 3}}";
+
+    for &policy in &[DuplicateKeys::KeepLast, DuplicateKeys::KeepFirst, DuplicateKeys::Collect, DuplicateKeys::Error] {
+        let options = ParserOptions::new().duplicate_keys(policy);
+        let expected = json::parse_bytes_with(source, &options);
+
+        assert_eq!(push_split(source, &options), expected.map(|value| vec![value]));
+    }
+
+    let options = ParserOptions::json5().duplicate_keys(DuplicateKeys::Error);
+    let source = b"{ key: 1,\n  key: 2 }";
+
+    assert_eq!(push_split(source, &options), Err(json::parse_bytes_with(source, &options).unwrap_err()));
+}
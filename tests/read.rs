@@ -0,0 +1,163 @@
+#[macro_use]
+extern crate json;
+
+mod common;
+
+use std::io::{ self, Read };
+use json::{ parse, parse_bytes, parse_reader, parse_reader_with, Error, ParserOptions };
+
+// Reader that only ever hands out a few bytes at a time, so that the
+// parser has to deal with everything being split up between reads.
+struct Trickle<'a> {
+    source: &'a [u8],
+    step: usize,
+}
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.step.min(buf.len()).min(self.source.len());
+
+        buf[..len].copy_from_slice(&self.source[..len]);
+        self.source = &self.source[len..];
+
+        Ok(len)
+    }
+}
+
+fn trickle(source: &[u8], step: usize) -> Trickle<'_> {
+    Trickle { source, step }
+}
+
+// Reader that fails once it's been read from a few times.
+struct Broken(usize);
+
+impl Read for Broken {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0 == 0 {
+            return Err(io::Error::new(io::ErrorKind::ConnectionReset, "gone"));
+        }
+        self.0 -= 1;
+        buf[0] = b'[';
+        Ok(1)
+    }
+}
+
+const DOCUMENTS: &[&str] = &[
+    "null",
+    "  true  ",
+    "-12.5e-3",
+    "18446744073709551616",
+    r#""simple string""#,
+    r#""escaped \"string\" with \\ \/ \b \f \n \r \t \u0041 \uD834\uDD1E""#,
+    "\"zażółć gęślą jaźń 🦄\"",
+    "\"zażółć\\ngęślą jaźń 🦄\"",
+    r#"{"a":1,"b":[true,false,null],"c":{"d":"e","f":[{}, []]}}"#,
+    "[\n  1,\n  \"two\",\n  { \"three\": 3.0 }\n]\n",
+];
+
+#[test]
+fn parse_reader_matches_parse() {
+    for source in DOCUMENTS {
+        let expected = parse(source).unwrap();
+
+        assert_eq!(parse_reader(source.as_bytes()).unwrap(), expected);
+
+        for step in 1..5 {
+            assert_eq!(parse_reader(trickle(source.as_bytes(), step)).unwrap(), expected);
+        }
+    }
+
+    // The size is only known once it's been read up to, see
+    // `parse_reader_with_size_limit`
+    let options: Vec<_> = common::all_options().into_iter().map(|options| options.size_limit(usize::MAX)).collect();
+
+    common::matches_parse(&options, |source, options| parse_reader_with(source.as_bytes(), options), |value| value);
+    common::matches_parse(&options, |source, options| parse_reader_with(trickle(source.as_bytes(), 3), options), |value| value);
+}
+
+#[test]
+fn parse_reader_long_strings() {
+    let long = "x".repeat(100_000);
+    let source = format!(r#"["{}", "\n{}", {{"{}": "{}"}}]"#, long, long, long, long);
+
+    assert_eq!(parse_reader(source.as_bytes()).unwrap(), parse(&source).unwrap());
+    assert_eq!(parse_reader(trickle(source.as_bytes(), 4000)).unwrap(), parse(&source).unwrap());
+}
+
+#[test]
+fn parse_reader_error_positions() {
+    let sources: &[&[u8]] = &[
+        b"\n\nnulX\n",
+        "\n\nnul🦄\n".as_bytes(),
+        b"\n  [\n    null,\n  ]  \n",
+        b"[1, 2] 3",
+        b"{\"foo\" 1}",
+        b"\"abc\x01\"",
+        b"[\"\\n\\x\"]",
+        b"[\"foo\", \"b\xC3\x28r\"]",
+        b"[\"\\nb\xC3\x28r\"]",
+        b"[\"\\nb\xE2\x82\"]",
+        b"[1, \xE9]",
+        b"[\"abc",
+        b"[\"\\nabc",
+        b"[\"\\nab\xC3",
+        b"[1, 2",
+    ];
+
+    for source in sources {
+        let expected = parse_bytes(source).unwrap_err();
+
+        assert_eq!(parse_reader(*source).unwrap_err(), expected);
+
+        for step in 1..5 {
+            assert_eq!(parse_reader(trickle(source, step)).unwrap_err(), expected);
+        }
+    }
+}
+
+#[test]
+fn parse_reader_io_error() {
+    match parse_reader(Broken(3)) {
+        Err(Error::Io { kind, .. }) => assert_eq!(kind, io::ErrorKind::ConnectionReset),
+        other                       => panic!("Expected I/O error, got {:?}", other),
+    }
+}
+
+#[test]
+fn parse_reader_with_size_limit() {
+    let options = ParserOptions::new().size_limit(8);
+
+    assert_eq!(parse_reader_with(trickle(b"[1,2,3] ", 3), &options).unwrap(), array![1, 2, 3]);
+    assert_eq!(parse_reader_with(trickle(b"[1,2,3]  ", 3), &options), Err(Error::ExceededSizeLimit));
+
+    // Errors before the limit are found before it's known to be hit
+    assert_eq!(json::parse_bytes_with(b"[x,2,3]  ", &options), Err(Error::ExceededSizeLimit));
+    assert!(matches!(parse_reader_with(trickle(b"[x,2,3]  ", 3), &options), Err(Error::UnexpectedCharacter { ch: 'x', .. })));
+}
+
+#[test]
+fn parse_reader_with_relaxed_syntax() {
+    let options = ParserOptions::json5();
+    let source = "// zażółć\n{ /* 🦄 */ unquoted_key: 'it\\'s', list: [+1, .5, 0xFF,], }";
+    let expected = json::parse_with(source, &options).unwrap();
+
+    for step in 1..5 {
+        assert_eq!(parse_reader_with(trickle(source.as_bytes(), step), &options).unwrap(), expected);
+    }
+}
+
+#[test]
+fn parse_reader_duplicate_key_position() {
+    let options = ParserOptions::new().duplicate_keys(json::DuplicateKeys::Error);
+    let long = "x".repeat(9_000);
+    let source = format!("{{\n\"{}\": 1,\n\"\\n{}\": 2,\n  \"\\n{}\": 3}}", long, long, long);
+    let expected = json::parse_with(&source, &options).unwrap_err();
+
+    assert_eq!(expected, Error::DuplicateKey { key: format!("\n{}", long), line: 4, column: 3 });
+
+    for step in 1..5 {
+        assert_eq!(parse_reader_with(trickle(source.as_bytes(), step), &options), Err(expected.clone()));
+    }
+
+    assert_eq!(parse_reader_with(source.as_bytes(), &options), Err(expected));
+}
@@ -0,0 +1,164 @@
+#[macro_use]
+extern crate json;
+
+mod common;
+
+use json::{ parse, parse_with, parse_recover, parse_recover_with, Error, Expected, JsonValue, ParserOptions };
+
+// The recovered value and how many errors there were.
+fn recover(source: &str) -> (JsonValue, usize) {
+    let (value, errors) = parse_recover(source);
+
+    // Whatever went wrong first is what `parse` fails with
+    assert_eq!(errors.first(), parse(source).err().as_ref(), "{:?}", source);
+
+    (value, errors.len())
+}
+
+#[test]
+fn recover_valid_documents() {
+    let sources = &[
+        "null",
+        "  true  ",
+        "-12.5e-3",
+        r#""escaped \"string\" A 𝄞""#,
+        r#"{"a":1,"b":[true,false,null],"c":{"d":"e","f":[{}, []]}}"#,
+        "[\n  1,\n  \"two\",\n  { \"three\": 3.0 }\n]\n",
+    ];
+
+    for source in sources {
+        assert_eq!(parse_recover(source), (parse(source).unwrap(), Vec::new()));
+    }
+}
+
+#[test]
+fn recover_matches_parse() {
+    common::matches_parse(&common::all_options(), |source, options| {
+        match parse_recover_with(source, options) {
+            (value, ref errors) if errors.is_empty() => Ok(value),
+            (_, errors)                              => Err(errors[0].clone()),
+        }
+    }, |value| value);
+}
+
+#[test]
+fn recover_missing_tokens() {
+    assert_eq!(recover("[1 2 3]"), (array![1, 2, 3], 2));
+    assert_eq!(recover(r#"{"a": 1 "b": 2}"#), (object!{ a: 1, b: 2 }, 1));
+    assert_eq!(recover(r#"{"a" 1, "b": 2}"#), (object!{ a: 1, b: 2 }, 1));
+    assert_eq!(recover(r#"{"a", "b": 2}"#), (object!{ a: null, b: 2 }, 1));
+    assert_eq!(recover(r#"{"a": , "b": }"#), (object!{ a: null, b: null }, 2));
+    assert_eq!(recover("[1, , 2]"), (array![1, 2], 1));
+    assert_eq!(recover("[1, 2,]"), (array![1, 2], 1));
+    assert_eq!(recover(r#"{"a": 1,}"#), (object!{ a: 1 }, 1));
+}
+
+#[test]
+fn recover_skipped_tokens() {
+    assert_eq!(recover("[1, @, 2]"), (array![1, 2], 1));
+    assert_eq!(recover("[1, foo bar, 2]"), (array![1, 2], 1));
+    assert_eq!(recover("[1, 🦄 2]"), (array![1, 2], 1));
+    assert_eq!(recover(r#"{"a": 1, 2: 3, "b": 4}"#), (object!{ a: 1, b: 4 }, 1));
+    assert_eq!(recover(r#"{a: 1}"#), (object!{ a: 1 }, 1));
+    assert_eq!(recover("[1] ]"), (array![1], 1));
+    assert_eq!(recover("] [1]"), (array![1], 1));
+}
+
+#[test]
+fn recover_broken_values() {
+    assert_eq!(recover("[tru, nul]"), (array![true, null], 2));
+    assert_eq!(recover("[1, fasle, 2]"), (array![1, 2], 1));
+    assert_eq!(recover("[1, -, 1.e5, 2]"), (array![1, null, null, 2], 2));
+    assert_eq!(recover(r#"["a\x", "b"]"#), (array!["a\\x", "b"], 1));
+    assert_eq!(recover("[\"a\tb\", \"c\"]"), (array!["a\tb", "c"], 1));
+    assert_eq!(recover("{\"a\": \"unterminated,\n \"b\": 2}"), (object!{ a: "unterminated,", b: 2 }, 1));
+}
+
+#[test]
+fn recover_unclosed_containers() {
+    assert_eq!(recover(""), (JsonValue::Null, 1));
+    assert_eq!(recover("[1, [2, {\"a\": 3"), (array![1, [2, { a: 3 }]], 1));
+    assert_eq!(recover("{\"a\": [1, 2}"), (object!{ a: [1, 2] }, 1));
+    assert_eq!(recover("[{\"a\": 1]"), (array![{ a: 1 }], 1));
+    assert_eq!(recover("[1, 2}]"), (array![1, 2], 1));
+    assert_eq!(recover("{\"a\": [1, \"b"), (object!{ a: [1, "b"] }, 1));
+    assert_eq!(recover("{\"a\": tr"), (object!{ a: true }, 1));
+    assert_eq!(recover("{\"a\": "), (object!{ a: null }, 1));
+    assert_eq!(recover("{\"a"), (object!{ a: null }, 1));
+}
+
+#[test]
+fn recover_error_positions() {
+    let (value, errors) = parse_recover("{\n  \"a\": [1 2],\n  \"b\" 3,\n  \"c\": x\n}");
+
+    assert_eq!(value, object!{ a: [1, 2], b: 3, c: null });
+    assert_eq!(errors, vec![
+        Error::UnexpectedCharacter {
+            ch: '2',
+            line: 2,
+            column: 11,
+            offset: 12,
+            expected: Expected::CommaOrEndOfArray,
+        },
+        Error::UnexpectedCharacter {
+            ch: '3',
+            line: 3,
+            column: 7,
+            offset: 22,
+            expected: Expected::Colon,
+        },
+        Error::UnexpectedCharacter {
+            ch: 'x',
+            line: 4,
+            column: 8,
+            offset: 32,
+            expected: Expected::Value,
+        },
+    ]);
+}
+
+#[test]
+fn recover_with_options() {
+    use json::DuplicateKeys;
+
+    let options = ParserOptions::json5();
+    let source = "{ // comment\n a: [1, 2,], 'b': 0x10 /x }";
+    let (value, errors) = parse_recover_with(source, &options);
+
+    assert_eq!(value, object!{ a: [1, 2], b: 16 });
+    assert_eq!(errors, vec![parse_with(source, &options).unwrap_err()]);
+
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Error);
+    let source = r#"{"a": 1, "b": 2, "a": 3}"#;
+    let (value, errors) = parse_recover_with(source, &options);
+
+    assert_eq!(value, object!{ a: 3, b: 2 });
+    assert_eq!(errors, vec![parse_with(source, &options).unwrap_err()]);
+}
+
+#[test]
+fn recover_stops_at_limits() {
+    let options = ParserOptions::new().depth_limit(2).array_length_limit(3);
+
+    assert_eq!(
+        parse_recover_with("[1 [2, [3]], 4]", &options),
+        (array![1, [2]], vec![
+            parse("[1 [").unwrap_err(),
+            Error::ExceededDepthLimit,
+        ])
+    );
+    assert_eq!(
+        parse_recover_with("[1, 2, 3, 4, x]", &options),
+        (array![1, 2, 3], vec![Error::ExceededArrayLengthLimit])
+    );
+
+    // Empty containers are fine at the limit, same as for `parse`
+    let options = ParserOptions::new().depth_limit(1);
+
+    for source in &["[[]]", "[[ ], {}]", r#"{"a":[]}"#, r#"{"a": { }, "b": 1}"#, "[[1]]", r#"{"a":{"b":1}}"#] {
+        match parse_with(source, &options) {
+            Ok(value)  => assert_eq!(parse_recover_with(source, &options), (value, vec![]), "{}", source),
+            Err(error) => assert_eq!(parse_recover_with(source, &options).1, vec![error], "{}", source),
+        }
+    }
+}
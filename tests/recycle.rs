@@ -0,0 +1,101 @@
+#[macro_use]
+extern crate json;
+
+mod common;
+
+use json::{ parse, parse_with, parse_into, parse_into_with, Error, JsonValue, ParserOptions, ReusableParser };
+
+const SOURCES: &[&str] = &[
+    "null",
+    "[]",
+    "{}",
+    r#"{"id":1,"tags":["a","b"],"nested":{"deep":[[1],[2,3]]}}"#,
+    r#"["a string long enough not to fit in a short one", "and another one of those"]"#,
+    r#"{"escaped \"key\"":"escaped \"value\" A 𝄞, long enough to allocate"}"#,
+    r#"[1, {"a": [true, false, null]}, "x", -2.5e10]"#,
+    "[1, 2",
+    r#"{"a": 1,}"#,
+    "\"lone string with \\n escapes, long enough to allocate\"",
+];
+
+#[test]
+fn parse_into_matches_parse() {
+    let mut parser = ReusableParser::new();
+    let mut value = JsonValue::Null;
+    let mut single = JsonValue::Null;
+
+    // Every source after every other one, so that whatever is recycled
+    // gets a go at every shape
+    for first in SOURCES {
+        for second in SOURCES {
+            for source in &[first, second] {
+                let result = parser.parse_into(source, &mut value).map(|_| value.clone());
+
+                assert_eq!(result, parse(source), "{}", source);
+                assert_eq!(parse_into(source, &mut single).map(|_| single.clone()), result, "{}", source);
+
+                if result.is_err() {
+                    assert!(value.is_null());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn parse_into_matches_parse_with() {
+    for options in common::all_options() {
+        let mut parser = ReusableParser::with_options(&options);
+        let mut value = JsonValue::Null;
+
+        // Everything parsed so far is recycled for the next one
+        common::matches_parse(&[options], |source, _| parser.parse_into(source, &mut value).map(|_| value.clone()), |value| value);
+    }
+}
+
+#[test]
+fn parse_into_reuses_allocations() {
+    let long = "a string long enough not to fit in a short one";
+    let mut value = parse(&format!(r#"[["{}"], {{"a": 1, "b": 2, "c": 3, "d": 4}}]"#, long)).unwrap();
+    let string = value[0][0].as_str().unwrap().as_ptr();
+
+    json::parse_into(r#"[["another one that won't fit in a short string"], {"e": 5}]"#, &mut value).unwrap();
+
+    assert_eq!(value[0][0].as_str().unwrap().as_ptr(), string);
+
+    match value[1] {
+        JsonValue::Object(ref object) => assert!(object.capacity() >= 4),
+        _                             => panic!("Expected an object"),
+    }
+
+    let mut parser = ReusableParser::new();
+    let array = parse(&format!("[{}]", "1, ".repeat(99) + "1")).unwrap();
+
+    parser.recycle(array);
+
+    match parser.parse("[[1, 2]]").unwrap() {
+        JsonValue::Array(ref outer) => assert!(outer.capacity() >= 100),
+        _                           => panic!("Expected an array"),
+    }
+}
+
+#[test]
+fn parse_into_with_options() {
+    let options = ParserOptions::json5().depth_limit(2);
+    let mut parser = ReusableParser::with_options(&options);
+    let mut value = array![1, 2, 3];
+
+    parser.parse_into("{ a: ['x', +1,], }", &mut value).unwrap();
+
+    assert_eq!(value, object!{ a: ["x", 1] });
+    assert_eq!(parser.parse_into("[[[1]]]", &mut value), Err(Error::ExceededDepthLimit));
+    assert!(value.is_null());
+
+    // The limits aren't loosened by anything left over
+    assert_eq!(parser.parse("[[1]] // done"), parse_with("[[1]] // done", &options));
+
+    let mut value = object!{ a: "b" };
+
+    assert_eq!(parse_into_with("[1, 2]", &mut value, &ParserOptions::new().array_length_limit(1)), Err(Error::ExceededArrayLengthLimit));
+    assert!(value.is_null());
+}
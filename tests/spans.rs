@@ -0,0 +1,102 @@
+extern crate json;
+
+mod common;
+
+use json::spans::{ SourceMap, Span };
+use json::{ parse_spanned, parse_spanned_with, DuplicateKeys, Error, JsonValue, ParserOptions };
+
+fn spanned_with(source: &str, options: &ParserOptions) -> Result<JsonValue, Error> {
+    parse_spanned_with(source, options).map(|(value, _)| value)
+}
+
+// Text of the value a pointer leads to
+fn text<'a>(source: &'a str, spans: &SourceMap, pointer: &str) -> &'a str {
+    &source[spans.get(pointer).expect(pointer).value.range()]
+}
+
+#[test]
+fn spanned_matches_parse() {
+    common::matches_parse(&common::all_options(), spanned_with, |value| value);
+}
+
+#[test]
+fn spanned_positions() {
+    let source = "{\n  \"id\": 42,\n  \"zażółć\": [true, {\"x\": null}],\n\t\"a/b~c\": \"d\"\n}";
+    let (value, spans) = parse_spanned(source).unwrap();
+
+    assert_eq!(value["zażółć"][1]["x"], JsonValue::Null);
+    assert_eq!(spans.len(), 7);
+
+    assert_eq!(text(source, &spans, ""), source);
+    assert_eq!(text(source, &spans, "/id"), "42");
+    assert_eq!(text(source, &spans, "/zażółć"), "[true, {\"x\": null}]");
+    assert_eq!(text(source, &spans, "/zażółć/1"), "{\"x\": null}");
+    assert_eq!(text(source, &spans, "/zażółć/1/x"), "null");
+    assert_eq!(text(source, &spans, "/a~1b~0c"), "\"d\"");
+
+    let id = spans.get("/id").unwrap();
+
+    assert_eq!(id.key, Some(Span { start: 4, end: 8, line: 2, column: 3 }));
+    assert_eq!(id.value, Span { start: 10, end: 12, line: 2, column: 9 });
+    assert_eq!(id.parent, Some(0));
+
+    // Columns count characters, not bytes
+    let x = spans.get("/zażółć/1/x").unwrap();
+
+    assert_eq!((x.key.unwrap().line, x.key.unwrap().column), (3, 21));
+    assert_eq!((x.value.line, x.value.column), (3, 26));
+    assert_eq!(spans.get("/a~1b~0c").unwrap().key.map(|key| key.column), Some(2));
+
+    assert_eq!(spans.get("").unwrap().key, None);
+    assert_eq!(spans.get("/zażółć/0").unwrap().key, None);
+
+    assert!(spans.get("/missing").is_none());
+    assert!(spans.get("/zażółć/2").is_none());
+    assert!(spans.get("/zażółć/01").is_none());
+    assert!(spans.get("/zażółć/+1").is_none());
+    assert!(spans.get("/id/0").is_none());
+    assert!(spans.get("id").is_none());
+
+    for id in 0..spans.len() {
+        let pointer = spans.pointer(id).unwrap();
+
+        assert_eq!(spans.find(&pointer), Some(id), "{}", pointer);
+    }
+
+    assert_eq!(spans.pointer(6).as_deref(), Some("/a~1b~0c"));
+    assert_eq!(spans.pointer(7), None);
+}
+
+#[test]
+fn spanned_duplicate_keys() {
+    let source = r#"{"a": 1, "b": {"c": 2}, "a": {"x": 3}, "a": 4}"#;
+    let text = |options: &ParserOptions, pointer| {
+        let (_, spans) = parse_spanned_with(source, options).unwrap();
+
+        spans.get(pointer).map(|node| &source[node.value.range()])
+    };
+
+    let options = ParserOptions::new();
+
+    assert_eq!(text(&options, "/a"), Some("4"));
+    assert_eq!(text(&options, "/a/x"), None);
+
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::KeepFirst);
+
+    assert_eq!(text(&options, "/a"), Some("1"));
+    assert_eq!(text(&options, "/b/c"), Some("2"));
+
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Collect);
+
+    assert_eq!(text(&options, "/a/0"), Some("1"));
+    assert_eq!(text(&options, "/a/1/x"), Some("3"));
+    assert_eq!(text(&options, "/a/2"), Some("4"));
+    assert_eq!(text(&options, "/a/3"), None);
+    assert_eq!(text(&options, "/a"), Some("1"));
+
+    let (_, spans) = parse_spanned_with(source, &options).unwrap();
+
+    assert_eq!(spans.pointer(4).as_deref(), Some("/a/1"));
+    assert_eq!(spans.pointer(5).as_deref(), Some("/a/1/x"));
+    assert_eq!(spans.pointer(3).as_deref(), Some("/b/c"));
+}
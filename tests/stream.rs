@@ -0,0 +1,332 @@
+#[macro_use]
+extern crate json;
+
+mod common;
+
+use std::io::{ self, Read };
+use json::stream::{ Values, ReadValues, Elements, Writer };
+use json::{ parse_reader_with, DuplicateKeys, Error, Expected, JsonValue, ParserOptions };
+
+// Reader that only ever hands out a few bytes at a time.
+struct Trickle<'a> {
+    source: &'a [u8],
+    step: usize,
+}
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.step.min(buf.len()).min(self.source.len());
+
+        buf[..len].copy_from_slice(&self.source[..len]);
+        self.source = &self.source[len..];
+
+        Ok(len)
+    }
+}
+
+fn values(source: &str) -> Vec<Result<JsonValue, Error>> {
+    let expected: Vec<_> = Values::new(source).collect();
+
+    // Reading makes no difference, however the source is split up
+    for step in 1..8 {
+        let trickle = Trickle { source: source.as_bytes(), step };
+
+        assert_eq!(ReadValues::new(trickle).collect::<Vec<_>>(), expected, "{:?} by {}", source, step);
+    }
+
+    expected
+}
+
+#[test]
+fn stream_separators() {
+    assert_eq!(values(""), []);
+    assert_eq!(values(" \n\t\r\n "), []);
+    assert_eq!(values("1 2\n3"), [Ok(1.into()), Ok(2.into()), Ok(3.into())]);
+    assert_eq!(values("12"), [Ok(12.into())]);
+    assert_eq!(values("\"a\"\r\n{\"b\":null}\r\n"), [Ok("a".into()), Ok(object!{ b: null })]);
+    assert_eq!(values("\x1e[1]\n\x1e\x1etrue\n\x1e"), [Ok(array![1]), Ok(true.into())]);
+}
+
+#[test]
+fn stream_pretty_values() {
+    let source = "{\n  \"a\": [\n    1,\n    2\n  ]\n}\n{\n  \"b\": \"multi\\nline\"\n}\n";
+
+    assert_eq!(values(source), [Ok(object!{ a: [1, 2] }), Ok(object!{ b: "multi\nline" })]);
+}
+
+#[test]
+fn stream_error_positions() {
+    let source = "{\"a\": 1}\n{\"b\" 2}\n{\"c\": 3}\n";
+
+    assert_eq!(values(source), [
+        Ok(object!{ a: 1 }),
+        Err(Error::UnexpectedCharacter {
+            ch: '2',
+            line: 2,
+            column: 6,
+            offset: 14,
+            expected: Expected::Colon,
+        }),
+        Ok(object!{ c: 3 }),
+    ]);
+}
+
+#[test]
+fn stream_skips_broken_records() {
+    // Up to the end of the line
+    let items = values("1x\n\"a\tb\"\n4\n[2,\n3\n");
+
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0], Err(Error::UnexpectedCharacter {
+        ch: 'x',
+        line: 1,
+        column: 2,
+        offset: 1,
+        expected: Expected::EndOfJson,
+    }));
+    assert!(items[1].is_err());
+    assert_eq!(items[2], Ok(4.into()));
+
+    // A value can span lines, this one is only cut short by the end
+    assert_eq!(items[3], Err(Error::UnexpectedEndOfJson));
+
+    // Up to the next record separator
+    let items = values("\x1e{\"a\":\n1 2}\n\x1e3\n");
+
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_err());
+    assert_eq!(items[1], Ok(3.into()));
+
+    assert_eq!(values("[1][2]").len(), 1);
+    assert_eq!(values("[1, 2").last(), Some(&Err(Error::UnexpectedEndOfJson)));
+}
+
+#[test]
+fn stream_with_options() {
+    let options = ParserOptions::new().size_limit(5);
+    let items: Vec<_> = Values::with_options("[1,2]\n[1, 2]\n[3]", &options).collect();
+
+    assert_eq!(items, [Ok(array![1, 2]), Err(Error::ExceededSizeLimit), Ok(array![3])]);
+
+    let options = ParserOptions::json5();
+    let items: Vec<_> = Values::with_options("// header\n{a: 1,} /* x */ {b: 2}", &options).collect();
+
+    assert_eq!(items, [Ok(object!{ a: 1 }), Ok(object!{ b: 2 })]);
+}
+
+#[test]
+fn stream_reader_errors() {
+    struct Broken(usize);
+
+    impl Read for Broken {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0 == 0 {
+                return Err(io::Error::new(io::ErrorKind::ConnectionReset, "gone"));
+            }
+            self.0 -= 1;
+            buf[..2].copy_from_slice(b"1\n");
+            Ok(2)
+        }
+    }
+
+    let items: Vec<_> = ReadValues::new(Broken(2)).collect();
+
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0], Ok(1.into()));
+    assert_eq!(items[1], Ok(1.into()));
+
+    match items[2] {
+        Err(Error::Io { .. }) => {},
+        ref other => panic!("Expected an I/O error, got {:?}", other),
+    }
+}
+
+#[test]
+fn stream_writer() {
+    let records = [
+        object!{ level: "info", message: "two\nlines" },
+        array![1, 2.5, null],
+        JsonValue::from("plain"),
+    ];
+
+    let mut writer = Writer::new(Vec::new());
+
+    for record in &records {
+        writer.write(record).unwrap();
+    }
+
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+
+    assert_eq!(output.lines().count(), 3);
+    assert_eq!(values(&output), records.iter().cloned().map(Ok).collect::<Vec<_>>());
+
+    let mut writer = Writer::json_seq(Vec::new());
+
+    writer.write(&array![1]).unwrap();
+    writer.write(&true.into()).unwrap();
+
+    assert_eq!(writer.into_inner(), b"\x1e[1]\n\x1etrue\n");
+}
+
+// Elements of the array at `pointer`, from each way of reading the source,
+// which all have to be the same.
+fn elements(source: &str, pointer: &str, options: &ParserOptions) -> Vec<Result<JsonValue, Error>> {
+    let expected: Vec<_> = Elements::with_options(source.as_bytes(), options).at(pointer).collect();
+
+    for step in 1..8 {
+        let trickle = Trickle { source: source.as_bytes(), step };
+
+        assert_eq!(Elements::with_options(trickle, options).at(pointer).collect::<Vec<_>>(), expected, "{:?} by {}", source, step);
+    }
+
+    expected
+}
+
+// Elements of the array at `pointer` are the ones `parse` comes up with,
+// or the same error once the ones before it are read.
+fn elements_match_parse(source: &str, pointer: &str, options: &ParserOptions) {
+    let items = elements(source, pointer, options);
+
+    match parse_reader_with(source.as_bytes(), options) {
+        Ok(ref value) => {
+            let target = pointer.strip_prefix('/').map_or(
+                if pointer.is_empty() { Some(value) } else { None },
+                |pointer| pointer.split('/').try_fold(value, |value, token| {
+                    let token = token.replace("~1", "/").replace("~0", "~");
+
+                    match *value {
+                        JsonValue::Object(ref object) => object.get(&token),
+                        JsonValue::Array(ref array)   => array.get(token.parse::<usize>().ok()?),
+                        _                             => None,
+                    }
+                }),
+            );
+
+            match target {
+                Some(JsonValue::Array(array)) => {
+                    assert_eq!(items, array.iter().cloned().map(Ok).collect::<Vec<_>>(), "{} at {}", source, pointer);
+                },
+                Some(_) => assert_eq!(items, [Err(Error::wrong_type("array"))], "{} at {}", source, pointer),
+                None    => assert_eq!(items, [], "{} at {}", source, pointer),
+            }
+        },
+        Err(ref error) => {
+            let last = items.last().expect("Must have the error");
+
+            // Whatever came before the error was fine
+            if *last != Err(Error::wrong_type("array")) {
+                assert_eq!(last, &Err(error.clone()), "{} at {} with {:?}", source, pointer, options);
+            }
+            assert!(items[..items.len() - 1].iter().all(Result::is_ok));
+        },
+    }
+}
+
+#[test]
+fn stream_elements_match_parse() {
+    let sources = &[
+        "[1, \"two\", [3], {\"four\": 4}, null]",
+        "[]",
+        " [ [] , {} ] ",
+        r#"{"meta": {"count": 2}, "rows": [{"id": 1}, {"id": 2}], "after": [1, {"x": "y"}]}"#,
+        r#"{"rows": {"deep": [[1, 2], [3], []]}, "a~b/c": [true]}"#,
+        r#"[[0], [1, [10, 11]], "x"]"#,
+        "[1, 2,]",
+        "[1, 2",
+        "[1, tru]",
+        "[1, 2] 3",
+        r#"{"rows": [1, 2], "after": [1 2]}"#,
+        r#"{"rows": [1, 2], "after": x}"#,
+        r#"{"before": [1 2], "rows": [1]}"#,
+        "",
+    ];
+    let pointers = &["", "/rows", "/rows/deep", "/rows/deep/0", "/a~0b~1c", "/1/1", "/1", "/missing", "/0/5", "rows"];
+
+    for source in sources {
+        for pointer in pointers {
+            elements_match_parse(source, pointer, &ParserOptions::new());
+        }
+    }
+
+    // Everything else, as elements of the document itself. Whether the
+    // size limit is hit before anything else depends on how much is read
+    // at a time, that's left to `stream_elements_with_options`.
+    for options in common::all_options() {
+        let options = options.size_limit(usize::MAX);
+
+        for source in common::sources() {
+            elements_match_parse(&source, "", &options);
+        }
+    }
+
+    let options = ParserOptions::json5();
+    let source = "// export\n{ rows: [ 'a', /* b */ 'c', ], }";
+
+    assert_eq!(elements(source, "/rows", &options), [Ok("a".into()), Ok("c".into())]);
+}
+
+#[test]
+fn stream_elements_with_options() {
+    let options = ParserOptions::new().array_length_limit(2);
+
+    assert_eq!(elements("[1, 2, 3]", "", &options), [Ok(1.into()), Ok(2.into()), Err(Error::ExceededArrayLengthLimit)]);
+    assert_eq!(elements("[[1, 2, 3], [1]]", "/1", &options), [Err(Error::ExceededArrayLengthLimit)]);
+    assert_eq!(elements("[[1], [1, 2], 3]", "/1", &options), [Ok(1.into()), Ok(2.into()), Err(Error::ExceededArrayLengthLimit)]);
+
+    let options = ParserOptions::new().depth_limit(3);
+
+    assert_eq!(elements("{\"a\": [[1], [[2]]]}", "/a", &options), [Ok(array![1]), Err(Error::ExceededDepthLimit)]);
+    assert_eq!(elements("{\"a\": {\"b\": [[1]]}}", "/a/b", &options), [Err(Error::ExceededDepthLimit)]);
+    assert_eq!(elements("{\"a\": {\"b\": [1]}}", "/a/b", &options), [Ok(1.into())]);
+
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Error);
+    let items = elements("{\"a\": [1], \"b\": 2, \"a\": 3}", "/a", &options);
+
+    assert_eq!(items[0], Ok(1.into()));
+    assert_eq!(items[1], Err(Error::DuplicateKey { key: "a".into(), line: 1, column: 20 }));
+
+    // The first of them is followed
+    assert_eq!(elements("{\"a\": [1], \"a\": [2]}", "/a", &ParserOptions::new()), [Ok(1.into())]);
+
+    // Keys that are already there don't count towards the limit
+    let options = ParserOptions::new().object_entries_limit(1);
+
+    assert_eq!(elements("{\"a\": [1], \"a\": 2}", "/a", &options), [Ok(1.into())]);
+    assert_eq!(elements("{\"b\": 1, \"b\": 2, \"a\": [1]}", "/a", &options), [Err(Error::ExceededObjectEntriesLimit)]);
+
+    // The limit is for the whole document, elements read before it's hit are fine
+    let options = ParserOptions::new().size_limit(20);
+    let trickle = Trickle { source: b"[1, 2, 3, 4, 5, 6, 7, 8, 9]", step: 4 };
+    let items: Vec<_> = Elements::with_options(trickle, &options).collect();
+
+    assert_eq!(items[0], Ok(1.into()));
+    assert_eq!(items.last(), Some(&Err(Error::ExceededSizeLimit)));
+}
+
+#[test]
+fn stream_elements_without_end() {
+    // An array that never ends, only what's been taken out is ever read
+    struct Endless(usize);
+
+    impl Read for Endless {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let element = match self.0 {
+                0 => String::from("{\"rows\": ["),
+                n => format!("{{\"id\": {}, \"tags\": [\"x\"]}},", n),
+            };
+
+            self.0 += 1;
+            buf[..element.len()].copy_from_slice(element.as_bytes());
+            Ok(element.len())
+        }
+    }
+
+    let ids: Vec<_> = Elements::new(Endless(0))
+        .at("/rows")
+        .take(100_000)
+        .map(|row| row.unwrap()["id"].as_usize().unwrap())
+        .collect();
+
+    assert_eq!(ids.len(), 100_000);
+    assert_eq!(ids[99_999], 100_000);
+}
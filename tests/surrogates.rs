@@ -0,0 +1,130 @@
+extern crate json;
+
+use std::io::Cursor;
+
+use json::codegen::{ DumpGenerator, PrettyGenerator, WriterGenerator, Generator };
+use json::{ parse, parse_with, parse_reader_with, validate_with, Error, Expected, LoneSurrogates, ParserOptions };
+
+fn options(policy: LoneSurrogates) -> ParserOptions {
+    ParserOptions::new().lone_surrogates(policy)
+}
+
+#[test]
+fn lone_surrogates_are_errors() {
+    let lone = |codepoint, line, column, offset| Err(Error::LoneSurrogate { codepoint, line, column, offset });
+
+    assert_eq!(parse(r#""\uDD1E""#), lone(0xDD1E, 1, 2, 1));
+    assert_eq!(parse(r#"["ok", "x\uD834\u0041"]"#), lone(0xD834, 1, 10, 9));
+    assert_eq!(parse("{\n  \"é\": \"\\uD834\\uD834\\uDD1E\"\n}"), lone(0xD834, 2, 9, 11));
+
+    // Not followed by an escape at all, the low surrogate is expected
+    assert_eq!(parse(r#""\uD834x""#), Err(Error::UnexpectedCharacter {
+        ch: 'x',
+        line: 1,
+        column: 8,
+        offset: 7,
+        expected: Expected::LowSurrogate,
+    }));
+
+    // Pairs are fine, whatever the policy
+    for &policy in &[LoneSurrogates::Error, LoneSurrogates::Replace, LoneSurrogates::Preserve] {
+        assert_eq!(parse_with(r#""𝄞""#, &options(policy)).unwrap(), "𝄞");
+    }
+
+    // Escapes split between reads
+    for padding in 8185..8195 {
+        let source = format!("[\"{}\\uD834\\uD834\"]", "a".repeat(padding));
+        let error = parse_reader_with(Cursor::new(source), &ParserOptions::new());
+
+        assert_eq!(error, lone(0xD834, 1, padding + 3, padding + 2));
+    }
+
+    let error = parse(r#""\uDD1E""#).unwrap_err();
+
+    assert_eq!(error.to_string(), "Lone surrogate: \\uDD1E at (1:2)");
+}
+
+#[test]
+fn lone_surrogates_replaced() {
+    let options = options(LoneSurrogates::Replace);
+    let sources = &[
+        (r#""\uD834""#, "\u{FFFD}"),
+        (r#""\uDD1E\uD834""#, "\u{FFFD}\u{FFFD}"),
+        (r#""\uD834x""#, "\u{FFFD}x"),
+        (r#""\uD834\n""#, "\u{FFFD}\n"),
+        (r#""\uD834A""#, "\u{FFFD}A"),
+        (r#""\uD834𝄞""#, "\u{FFFD}𝄞"),
+    ];
+
+    for &(source, expected) in sources {
+        let source = format!("[{}]", source);
+
+        assert_eq!(parse_with(&source, &options).unwrap()[0], expected, "{}", source);
+        assert_eq!(validate_with(&source, &options), parse_with(&source, &options).map(drop));
+    }
+
+    // What follows is still read as an escape of its own
+    assert!(parse_with(r#""\uD834\uDG00""#, &options).is_err());
+
+    // Escapes split between reads
+    for padding in 8180..8195 {
+        let source = format!("[\"{}\\uD834\\uDD1E\\uD834\"]", "a".repeat(padding));
+        let value = parse_reader_with(Cursor::new(source), &options).unwrap();
+
+        assert_eq!(value[0], format!("{}𝄞\u{FFFD}", "a".repeat(padding)));
+    }
+}
+
+#[test]
+fn lone_surrogates_round_trip() {
+    let options = options(LoneSurrogates::Preserve);
+    let source = r#"{"cut":"\ud83d","pair":"😀","low":"x\ude00\ud800y","tab":"\t"}"#;
+    let value = parse_with(source, &options).unwrap();
+
+    assert_eq!(value["cut"], "\u{FFFF}\u{10F83D}");
+    assert_eq!(value["pair"], "😀");
+
+    let mut generator = DumpGenerator::new().escape_lone_surrogates(true);
+
+    generator.write_json(&value).unwrap();
+
+    assert_eq!(generator.consume(), source);
+
+    // Not escaped unless asked
+    assert_eq!(value["cut"].dump(), "\"\u{FFFF}\u{10F83D}\"");
+
+    let mut generator = PrettyGenerator::new(2).escape_lone_surrogates(true);
+
+    generator.write_json(&value["low"]).unwrap();
+
+    assert_eq!(generator.consume(), r#""x\ude00\ud800y""#);
+
+    let mut written = Vec::new();
+
+    WriterGenerator::new(&mut written).escape_lone_surrogates(true).write_json(&value["cut"]).unwrap();
+
+    assert_eq!(written, br#""\ud83d""#);
+}
+
+#[test]
+fn lone_surrogates_leave_private_use_alone() {
+    let options = options(LoneSurrogates::Preserve);
+
+    // Private use characters of their own, from the source or not
+    let source = "[\"\u{10F83D}\", \"\\udbfe\\udc3d\", \"\u{FFFF}x\", \"\u{FFFF}\\ud83d\"]";
+    let mut value = parse_with(source, &options).unwrap();
+
+    value.push("\u{10F800}\u{FFFF}\u{FFFF}\u{10FFFF}".to_owned()).unwrap();
+
+    let mut generator = DumpGenerator::new().escape_lone_surrogates(true);
+
+    generator.write_json(&value).unwrap();
+
+    assert_eq!(generator.consume(), "[\"\u{10F83D}\",\"\u{10F83D}\",\"\u{FFFF}x\",\"\u{FFFF}\\ud83d\",\"\u{10F800}\u{FFFF}\\udfff\"]");
+
+    // Both characters count towards the limit
+    let options = options.string_length_limit(6);
+
+    assert_eq!(parse_with(r#""\ud83d""#, &options), Err(Error::ExceededStringLengthLimit));
+    assert_eq!(validate_with(r#""\ud83d""#, &options), Err(Error::ExceededStringLengthLimit));
+}
@@ -0,0 +1,159 @@
+#[macro_use]
+extern crate json;
+
+mod common;
+
+use json::{ parse, parse_with, parse_truncated, parse_truncated_with, Completion, Error, Incomplete, JsonValue, ParserOptions, Truncated };
+
+fn value(source: &str) -> JsonValue {
+    match parse_truncated(source) {
+        Ok(truncated) => truncated.value,
+        Err(error)    => panic!("Failed to complete {:?}: {:?}", source, error),
+    }
+}
+
+fn kinds(source: &str) -> Vec<Incomplete> {
+    parse_truncated(source).unwrap().completed.iter().map(|completion| completion.kind).collect()
+}
+
+#[test]
+fn truncated_containers() {
+    assert_eq!(value(r#"{"items":[{"name":"ab"#), object!{ items: [{ name: "ab" }] });
+    assert_eq!(value("["), array![]);
+    assert_eq!(value("[1, [2, {"), array![1, [2, {}]]);
+    assert_eq!(value("[1,"), array![1]);
+    assert_eq!(value("{"), object!{});
+    assert_eq!(value(r#"{"a""#), object!{});
+    assert_eq!(value(r#"{"a":1, "b"#), object!{ a: 1 });
+    assert_eq!(value(r#"{"a":1, "b" :"#), object!{ a: 1 });
+    assert_eq!(value(r#"{"a":[1, 2], "b": {"c": ["#), object!{ a: [1, 2], b: { c: [] } });
+
+    assert_eq!(kinds("[1, [2, {"), [Incomplete::Object, Incomplete::Array, Incomplete::Array]);
+    assert_eq!(kinds(r#"{"a":1, "b"#), [Incomplete::Object]);
+
+    assert_eq!(parse_truncated("{\n  \"a\": [\n    1,").unwrap().completed, [
+        Completion { kind: Incomplete::Array, offset: 9, line: 2, column: 8 },
+        Completion { kind: Incomplete::Object, offset: 0, line: 1, column: 1 },
+    ]);
+}
+
+#[test]
+fn truncated_scalars() {
+    let sources: &[(&str, JsonValue, &[Incomplete])] = &[
+        (r#"["ab"#, array!["ab"], &[Incomplete::String, Incomplete::Array]),
+        (r#""ab\"#, "ab".into(), &[Incomplete::String]),
+        (r#""ab\n"#, "ab\n".into(), &[Incomplete::String]),
+        (r#""ab\u00"#, "ab".into(), &[Incomplete::String]),
+        (r#""ab\u0041"#, "abA".into(), &[Incomplete::String]),
+        (r#""a\uD834"#, "a".into(), &[Incomplete::String]),
+        (r#""a\uD834\"#, "a".into(), &[Incomplete::String]),
+        (r#""a\uD834\uDD"#, "a".into(), &[Incomplete::String]),
+        (r#""a\uD834\uDD1E"#, "a𝄞".into(), &[Incomplete::String]),
+        ("\"zażółć", "zażółć".into(), &[Incomplete::String]),
+        ("[1, 2.", array![1, 2], &[Incomplete::Number, Incomplete::Array]),
+        ("[1.5e", array![1.5], &[Incomplete::Number, Incomplete::Array]),
+        ("[1.5e-", array![1.5], &[Incomplete::Number, Incomplete::Array]),
+        ("[1.5e-3", array![1.5e-3], &[Incomplete::Array]),
+        ("[1, -", array![1], &[Incomplete::Number, Incomplete::Array]),
+        (r#"{"a": -"#, object!{}, &[Incomplete::Number, Incomplete::Object]),
+        ("-12", (-12).into(), &[]),
+        ("[tr", array![true], &[Incomplete::Literal, Incomplete::Array]),
+        (r#"{"a": nul"#, object!{ a: null }, &[Incomplete::Literal, Incomplete::Object]),
+        ("f", false.into(), &[Incomplete::Literal]),
+        ("null", JsonValue::Null, &[]),
+    ];
+
+    for &(source, ref expected, completed) in sources {
+        assert_eq!(value(source), *expected, "{}", source);
+        assert_eq!(kinds(source), completed, "{}", source);
+    }
+}
+
+#[test]
+fn truncated_at_every_byte() {
+    let documents = &[
+        r#"{"id":1,"tags":["a","b"],"nested":{"deep":[[1],[-2.5e+3,true]]},"text":"x\"y\\z\u0041\uD834\uDD1E","none":null}"#,
+        "[\n  { \"name\": \"zażółć 🦄\", \"ok\": false },\n  [],\n  {}\n]\n",
+    ];
+
+    for document in documents {
+        for end in (1..document.len() + 1).filter(|&end| document.is_char_boundary(end)) {
+            let prefix = &document[..end];
+
+            match parse_truncated(prefix) {
+                Ok(truncated) => assert!(truncated.value.dump().len() <= parse(document).unwrap().dump().len(), "{}", prefix),
+                Err(error)    => panic!("Failed to complete {:?}: {:?}", prefix, error),
+            }
+        }
+
+        let whole = parse_truncated(document).unwrap();
+
+        assert_eq!(whole.value, parse(document).unwrap());
+        assert!(whole.completed.is_empty());
+    }
+}
+
+#[test]
+fn truncated_whole_documents() {
+    for options in &common::all_options() {
+        for source in common::sources() {
+            if let Ok(value) = parse_with(&source, options) {
+                assert_eq!(parse_truncated_with(&source, options), Ok(Truncated { value, completed: Vec::new() }), "{:?}", source);
+            }
+        }
+    }
+}
+
+#[test]
+fn truncated_errors() {
+    // Errors before the end are the same as from `parse`
+    for source in &["[1 2", "[1] x", "[1, @", "{\"a\" 1", "[tx", "[1.5e-, tr", "{1", "[1}", "\"\\uD834x\""] {
+        assert_eq!(parse_truncated(source).map(|truncated| truncated.value), parse(source), "{}", source);
+    }
+
+    // Nothing to complete
+    for source in &["", "   ", "-"] {
+        assert_eq!(parse_truncated(source), Err(Error::UnexpectedEndOfJson), "{}", source);
+    }
+
+    let options = ParserOptions::new().depth_limit(2);
+
+    assert_eq!(parse_truncated_with("[[[1", &options), Err(Error::ExceededDepthLimit));
+    assert_eq!(parse_truncated_with("[[1", &ParserOptions::new().size_limit(3)), Ok(parse_truncated("[[1").unwrap()));
+    assert_eq!(parse_truncated_with("[[1,", &ParserOptions::new().size_limit(3)), Err(Error::ExceededSizeLimit));
+
+    // Not a number that was cut off without hex numbers
+    for source in &["[0x", "[0X", "[-0x", "[0xe"] {
+        assert_eq!(parse_truncated(source).map(|truncated| truncated.value), parse(&format!("{}1]", source)), "{}", source);
+    }
+
+    let dropped = parse_truncated("[1,\n -").unwrap();
+
+    assert_eq!(dropped.completed[0], Completion { kind: Incomplete::Number, offset: 5, line: 2, column: 2 });
+}
+
+#[test]
+fn truncated_json5() {
+    let options = ParserOptions::json5();
+    let sources = &[
+        ("{ a: 'x", object!{ a: "x" }),
+        ("{ a: 1, bc", object!{ a: 1 }),
+        ("[0x", array![0]),
+        ("[0x1e", array![30]),
+        ("[+", array![]),
+        ("[.", array![]),
+        ("[-0x", array![0]),
+        ("[1, /* comment", array![1]),
+        ("[1, // comment", array![1]),
+        ("[1, 2, ", array![1, 2]),
+        ("[1] /", array![1]),
+    ];
+
+    for &(source, ref expected) in sources {
+        assert_eq!(parse_truncated_with(source, &options).map(|truncated| truncated.value), Ok(expected.clone()), "{}", source);
+    }
+
+    let whole = "{ a: 'x', b: [+1, .5, 0x10,], } // done";
+
+    assert_eq!(parse_truncated_with(whole, &options).map(|truncated| truncated.value), parse_with(whole, &options));
+}
@@ -0,0 +1,115 @@
+extern crate json;
+
+mod common;
+
+use std::alloc::{ GlobalAlloc, Layout, System };
+use std::cell::Cell;
+
+use json::{ validate, validate_with, validate_bytes, validate_bytes_with, parse_with, parse_bytes_with };
+use json::{ DuplicateKeys, Error, ParserOptions };
+
+// Counts allocations made on each thread, tests run side by side
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+
+    f();
+
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn validate_matches_parse() {
+    common::matches_parse(&common::all_options(), validate_with, drop);
+    common::matches_parse(&common::all_options(), |source, options| validate_bytes_with(source.as_bytes(), options), drop);
+
+    // Keys that are already there don't count as another entry, unless
+    // they're an error anyway
+    for &policy in &[DuplicateKeys::KeepLast, DuplicateKeys::KeepFirst, DuplicateKeys::Collect, DuplicateKeys::Error] {
+        let options = ParserOptions::new().duplicate_keys(policy).object_entries_limit(1);
+
+        for source in &[r#"{"a":1,"a":2}"#, r#"{"a":1,"a":2,"b":3}"#, r#"[{"a":{"b":1,"b":2},"a":3}]"#] {
+            assert_eq!(validate_with(source, &options), parse_with(source, &options).map(drop), "{}", source);
+        }
+    }
+
+    let options = ParserOptions::json5();
+    let sources = &[
+        "// comment\n{ a: 'single \\' \" quoted', 'b': [+1, 0x10, .5,], /* } */ c: [ 'x' ], }",
+        "{ a: 'unterminated }",
+        "{ 'a\\q': 1 }",
+        "[1, /* unterminated ]",
+    ];
+
+    for source in sources {
+        assert_eq!(validate_with(source, &options), parse_with(source, &options).map(drop), "{}", source);
+    }
+
+    let sources: &[&[u8]] = &[
+        b"[\"fo\xFF\"]",
+        b"[\"f\\no\xC3\"]",
+        b"{\"\xE2\x82\": 1}",
+        b"[\n\"zaz\xC3\xB3\xC5\x82\xC4\x87\", 1]",
+    ];
+
+    for source in sources {
+        assert_eq!(validate_bytes(source), parse_bytes_with(source, &ParserOptions::new()).map(drop));
+    }
+}
+
+#[test]
+fn validate_limits() {
+    // Strings are as long as they are once their escapes are decoded
+    let options = ParserOptions::new().string_length_limit(3);
+
+    assert_eq!(validate_with("[\"a\\u0041\\nb\"]", &options), Err(Error::ExceededStringLengthLimit));
+    assert_eq!(validate_with("[\"a\\u00DF\"]", &options), Ok(()));
+
+    let options = ParserOptions::new().depth_limit(1000);
+    let deep = format!("{}{}", "[{\"a\":".repeat(400), "}]".repeat(400));
+
+    assert_eq!(validate_with(&deep, &options), parse_with(&deep, &options).map(drop));
+    assert_eq!(validate(&deep), Err(Error::ExceededDepthLimit));
+}
+
+#[test]
+fn validate_does_not_allocate() {
+    let mut large = String::from("[");
+
+    for n in 0..100 {
+        large.push_str(&format!("{{\"id\": {}, \"text\": \"line \\\"{}\\\"\\n\\u00e9\", \"tags\": [\"a\", \"b\"]}}, ", n, n));
+    }
+    large.push_str(&"[".repeat(500));
+    large.push_str(&"]".repeat(500));
+    large.push(']');
+
+    for source in common::sources().iter().map(String::as_str).chain(Some(large.as_str())) {
+        assert_eq!(allocations(|| { let _ = validate(source); }), 0, "{}", source);
+        assert_eq!(allocations(|| { let _ = validate_bytes(source.as_bytes()); }), 0);
+    }
+
+    assert_eq!(validate(&large), Ok(()));
+
+    let options = ParserOptions::json5();
+
+    assert_eq!(allocations(|| validate_with("{ a: 'x\\'y', b: [0x1F, +1,], } // done", &options).unwrap()), 0);
+}
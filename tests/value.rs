@@ -573,9 +573,11 @@ fn error_unexpected_character() {
         ch: 'X',
         line: 3,
         column: 4,
+        offset: 5,
+        expected: json::Expected::Literal("null"),
     });
 
-    assert_eq!(format!("{}", err), "Unexpected character: X at (3:4)");
+    assert_eq!(format!("{}", err), "Unexpected character: X at (3:4), expected `null`");
 }
 
 #[test]
@@ -586,9 +588,11 @@ fn error_unexpected_unicode_character() {
         ch: '🦄',
         line: 3,
         column: 4,
+        offset: 5,
+        expected: json::Expected::Literal("null"),
     });
 
-    assert_eq!(format!("{}", err), "Unexpected character: 🦄 at (3:4)");
+    assert_eq!(format!("{}", err), "Unexpected character: 🦄 at (3:4), expected `null`");
 }
 
 #[test]
@@ -599,9 +603,40 @@ fn error_unexpected_token() {
         ch: ']',
         line: 4,
         column: 3,
+        offset: 17,
+        expected: json::Expected::Value,
     });
 
-    assert_eq!(format!("{}", err), "Unexpected character: ] at (4:3)");
+    assert_eq!(format!("{}", err), "Unexpected character: ] at (4:3), expected a value");
+}
+
+#[test]
+fn error_render() {
+    let source = "[\n\t1,\n\t\"zażółć\", 🦄\n]";
+    let err = parse(source).unwrap_err();
+
+    assert_eq!(err.render(source), [
+        "Unexpected character: 🦄 at (3:12), expected a value",
+        "  |",
+        "3 | \t\"zażółć\", 🦄",
+        "  | \t          ^",
+    ].join("\n"));
+
+    let source = "{\r\n  \"a\": [1, 2\r\n";
+    let err = parse(source).unwrap_err();
+
+    assert_eq!(err, JsonError::UnexpectedEndOfJson);
+    assert_eq!(err.render(source), [
+        "Unexpected end of JSON",
+        "  |",
+        "2 |   \"a\": [1, 2",
+        "  |             ^",
+    ].join("\n"));
+
+    let err = parse("").unwrap_err();
+
+    assert_eq!(err.render(""), "Unexpected end of JSON\n  |\n1 | \n  | ^");
+    assert_eq!(JsonError::ExceededDepthLimit.render("[[[]]]"), "Exceeded depth limit");
 }
 
 #[test]